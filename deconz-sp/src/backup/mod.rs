@@ -0,0 +1,263 @@
+//! Backup of the network identity, in the Open Coordinator Backup format shared
+//! by zigpy and zigbee2mqtt.
+//!
+//! A backup taken from one coordinator can be restored to another one, so the
+//! devices keep talking to the network without being paired again.
+
+use serde::{Deserialize, Serialize};
+
+use crate::network::{channel_mask, channels};
+use crate::protocol::constants::ParameterCode;
+use crate::protocol::types::ParameterValue;
+use crate::registry::Device;
+use crate::serde_hex::{hex, hex_option};
+use crate::zdo::LogicalType;
+use crate::Error;
+
+#[cfg(test)]
+mod tests;
+
+pub const FORMAT: &str = "zigpy/open-coordinator-backup";
+pub const VERSION: u32 = 1;
+
+/// Added to the frame counter of the backup when it is restored, as zigpy
+/// does: the backed up coordinator may have kept sending frames after the
+/// backup was taken.
+pub const FRAME_COUNTER_INCREMENT: u32 = 10_000;
+
+/// Parameters read to take a backup, before the link keys.
+pub const PARAMETERS: [ParameterCode; 9] = [
+    ParameterCode::MacAddress,
+    ParameterCode::NwkPanId,
+    ParameterCode::NwkExtendedPanId,
+    ParameterCode::ApsExtendedPanId,
+    ParameterCode::CurrentChannel,
+    ParameterCode::ChannelMask,
+    ParameterCode::NwkUpdateId,
+    ParameterCode::NetworkKey,
+    ParameterCode::NwkFrameCounter,
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub format: String,
+    pub version: u32,
+    /// Software which took the backup.
+    pub source: String,
+    /// Free-form data of the software which took the backup.
+    #[serde(default)]
+    pub internal: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkKey {
+    #[serde(with = "hex")]
+    pub key: [u8; 16],
+    pub sequence_number: u8,
+    pub frame_counter: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkKey {
+    #[serde(with = "hex")]
+    pub key: [u8; 16],
+    #[serde(default)]
+    pub rx_counter: u32,
+    #[serde(default)]
+    pub tx_counter: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceBackup {
+    #[serde(with = "hex")]
+    pub ieee_address: u64,
+    #[serde(default, with = "hex_option")]
+    pub nwk_address: Option<u16>,
+    /// End devices which have the coordinator as parent.
+    #[serde(default)]
+    pub is_child: bool,
+    #[serde(default)]
+    pub link_key: Option<LinkKey>,
+}
+
+impl DeviceBackup {
+    pub fn new(device: &Device, link_key: Option<[u8; 16]>) -> Self {
+        let is_child = device
+            .node_descriptor
+            .as_ref()
+            .map(|descriptor| descriptor.logical_type == LogicalType::EndDevice)
+            .unwrap_or(false);
+        DeviceBackup {
+            ieee_address: device.ieee_address,
+            nwk_address: Some(device.nwk_address),
+            is_child,
+            link_key: link_key.map(|key| LinkKey {
+                key,
+                rx_counter: 0,
+                tx_counter: 0,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    pub metadata: Metadata,
+    /// Data only understood by a given stack, kept as is.
+    #[serde(default)]
+    pub stack_specific: serde_json::Value,
+    #[serde(with = "hex")]
+    pub coordinator_ieee: u64,
+    #[serde(with = "hex")]
+    pub pan_id: u16,
+    #[serde(with = "hex")]
+    pub extended_pan_id: u64,
+    pub nwk_update_id: u8,
+    pub security_level: u8,
+    pub channel: u8,
+    pub channel_mask: Vec<u8>,
+    pub network_key: NetworkKey,
+    #[serde(default)]
+    pub devices: Vec<DeviceBackup>,
+}
+
+impl Backup {
+    /// Builds the backup from the values of `PARAMETERS`.
+    pub fn from_parameters(
+        values: &[(ParameterCode, ParameterValue)],
+        devices: Vec<DeviceBackup>,
+    ) -> Result<Self, Error> {
+        let find = |parameter: ParameterCode| {
+            values
+                .iter()
+                .find(|(code, _)| *code == parameter)
+                .map(|(_, value)| value)
+                .ok_or(Error::Decoding("Missing parameter in backup"))
+        };
+        let integer = |parameter: ParameterCode| {
+            find(parameter)?
+                .as_u64()
+                .ok_or(Error::Decoding("Invalid parameter in backup"))
+        };
+        let (key, sequence_number) = match find(ParameterCode::NetworkKey)? {
+            ParameterValue::NetworkKey { index, key } => (*key, *index),
+            _ => return Err(Error::Decoding("Invalid network key in backup")),
+        };
+        // The APS extended PAN ID is only set when it was chosen explicitly
+        let extended_pan_id = match integer(ParameterCode::NwkExtendedPanId)? {
+            0 => integer(ParameterCode::ApsExtendedPanId)?,
+            extended_pan_id => extended_pan_id,
+        };
+        Ok(Backup {
+            metadata: Metadata {
+                format: FORMAT.to_string(),
+                version: VERSION,
+                source: format!("deconz-sp@{}", env!("CARGO_PKG_VERSION")),
+                internal: serde_json::Value::Object(Default::default()),
+            },
+            stack_specific: serde_json::Value::Object(Default::default()),
+            coordinator_ieee: integer(ParameterCode::MacAddress)?,
+            pan_id: integer(ParameterCode::NwkPanId)? as u16,
+            extended_pan_id,
+            nwk_update_id: integer(ParameterCode::NwkUpdateId)? as u8,
+            security_level: 5,
+            channel: integer(ParameterCode::CurrentChannel)? as u8,
+            channel_mask: channels(integer(ParameterCode::ChannelMask)? as u32),
+            network_key: NetworkKey {
+                key,
+                sequence_number,
+                frame_counter: integer(ParameterCode::NwkFrameCounter)? as u32,
+            },
+            devices,
+        })
+    }
+
+    /// Returns the parameters to write to restore the backup, in order.
+    ///
+    /// The network is restored on the channel it was using, whatever the
+    /// channel mask of the backup.
+    pub fn parameters(&self) -> Vec<(ParameterCode, ParameterValue)> {
+        let mut parameters = vec![
+            (ParameterCode::ApsDesignedCoordinator, ParameterValue::U8(1)),
+            (
+                ParameterCode::MacAddress,
+                ParameterValue::U64(self.coordinator_ieee),
+            ),
+            (ParameterCode::NwkAddress, ParameterValue::U16(0x0000)),
+            (ParameterCode::NwkPanId, ParameterValue::U16(self.pan_id)),
+            (
+                ParameterCode::ApsExtendedPanId,
+                ParameterValue::U64(self.extended_pan_id),
+            ),
+            (
+                ParameterCode::ChannelMask,
+                ParameterValue::U32(channel_mask(&[self.channel])),
+            ),
+            (ParameterCode::SecurityMode, ParameterValue::U8(3)),
+            (
+                ParameterCode::TrustCenterAddress,
+                ParameterValue::U64(self.coordinator_ieee),
+            ),
+            // The firmware keeps the key in use at index 0
+            (
+                ParameterCode::NetworkKey,
+                ParameterValue::NetworkKey {
+                    index: 0,
+                    key: self.network_key.key,
+                },
+            ),
+            // Devices drop frames whose counter is lower than the last one seen
+            (
+                ParameterCode::NwkFrameCounter,
+                ParameterValue::U32(
+                    self.network_key
+                        .frame_counter
+                        .saturating_add(FRAME_COUNTER_INCREMENT),
+                ),
+            ),
+            (
+                ParameterCode::NwkUpdateId,
+                ParameterValue::U8(self.nwk_update_id),
+            ),
+        ];
+        parameters.extend(self.devices.iter().filter_map(|device| {
+            device.link_key.as_ref().map(|link_key| {
+                (
+                    ParameterCode::LinkKey,
+                    ParameterValue::LinkKey {
+                        ieee_address: device.ieee_address,
+                        key: link_key.key,
+                    },
+                )
+            })
+        }));
+        parameters
+    }
+
+    /// Returns the parameters read back once the network is restored, with their
+    /// expected value.
+    pub fn identity(&self) -> Vec<(ParameterCode, ParameterValue)> {
+        vec![
+            (
+                ParameterCode::MacAddress,
+                ParameterValue::U64(self.coordinator_ieee),
+            ),
+            (ParameterCode::NwkPanId, ParameterValue::U16(self.pan_id)),
+            (
+                ParameterCode::NwkExtendedPanId,
+                ParameterValue::U64(self.extended_pan_id),
+            ),
+            (
+                ParameterCode::CurrentChannel,
+                ParameterValue::U8(self.channel),
+            ),
+            (
+                ParameterCode::NetworkKey,
+                ParameterValue::NetworkKey {
+                    index: 0,
+                    key: self.network_key.key,
+                },
+            ),
+        ]
+    }
+}
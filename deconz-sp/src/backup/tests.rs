@@ -0,0 +1,160 @@
+use super::*;
+
+const KEY: [u8; 16] = [
+    0x01, 0x03, 0x05, 0x07, 0x09, 0x0b, 0x0d, 0x0f, 0x00, 0x02, 0x04, 0x06, 0x08, 0x0a, 0x0c, 0x0d,
+];
+
+fn parameters() -> Vec<(ParameterCode, ParameterValue)> {
+    vec![
+        (
+            ParameterCode::MacAddress,
+            ParameterValue::U64(0x0021_2eff_ff01_2345),
+        ),
+        (ParameterCode::NwkPanId, ParameterValue::U16(0x1a62)),
+        (
+            ParameterCode::NwkExtendedPanId,
+            ParameterValue::U64(0x00dd_dddd_dddd_dddd),
+        ),
+        (ParameterCode::ApsExtendedPanId, ParameterValue::U64(0)),
+        (ParameterCode::CurrentChannel, ParameterValue::U8(15)),
+        (ParameterCode::ChannelMask, ParameterValue::U32(0x0000_8000)),
+        (ParameterCode::NwkUpdateId, ParameterValue::U8(2)),
+        (
+            ParameterCode::NetworkKey,
+            ParameterValue::NetworkKey { index: 0, key: KEY },
+        ),
+        (ParameterCode::NwkFrameCounter, ParameterValue::U32(12345)),
+    ]
+}
+
+#[test]
+fn backup_from_parameters() {
+    let device = DeviceBackup {
+        ieee_address: 0x0015_8d00_0123_4567,
+        nwk_address: Some(0x1234),
+        is_child: true,
+        link_key: None,
+    };
+    let backup = Backup::from_parameters(&parameters(), vec![device.clone()]).unwrap();
+    assert_eq!(backup.coordinator_ieee, 0x0021_2eff_ff01_2345);
+    assert_eq!(backup.pan_id, 0x1a62);
+    assert_eq!(backup.extended_pan_id, 0x00dd_dddd_dddd_dddd);
+    assert_eq!(backup.nwk_update_id, 2);
+    assert_eq!(backup.channel, 15);
+    assert_eq!(backup.channel_mask, vec![15]);
+    assert_eq!(backup.network_key.key, KEY);
+    assert_eq!(backup.network_key.frame_counter, 12345);
+    assert_eq!(backup.devices, vec![device]);
+}
+
+#[test]
+fn backup_missing_parameter() {
+    let mut parameters = parameters();
+    parameters.retain(|(code, _)| *code != ParameterCode::NetworkKey);
+    assert!(Backup::from_parameters(&parameters, vec![]).is_err());
+}
+
+#[test]
+fn backup_json_format() {
+    let backup = Backup::from_parameters(&parameters(), vec![]).unwrap();
+    let json = serde_json::to_value(&backup).unwrap();
+    assert_eq!(json["metadata"]["format"], "zigpy/open-coordinator-backup");
+    assert_eq!(json["metadata"]["version"], 1);
+    assert_eq!(json["coordinator_ieee"], "00212effff012345");
+    assert_eq!(json["pan_id"], "1a62");
+    assert_eq!(json["extended_pan_id"], "00dddddddddddddd");
+    assert_eq!(
+        json["network_key"]["key"],
+        "01030507090b0d0f00020406080a0c0d"
+    );
+}
+
+#[test]
+fn read_zigpy_backup() {
+    let json = r#"{
+        "metadata": {
+            "format": "zigpy/open-coordinator-backup",
+            "version": 1,
+            "source": "zigpy-znp@0.9.2",
+            "internal": {"creation_time": "2022-01-01T00:00:00+00:00"}
+        },
+        "stack_specific": {"zstack": {"tclk_seed": "c04884427c8a1ed7bb8412815ccce7aa"}},
+        "coordinator_ieee": "00124b0009d69f77",
+        "pan_id": "1a62",
+        "extended_pan_id": "dddddddddddddddd",
+        "nwk_update_id": 0,
+        "security_level": 5,
+        "channel": 25,
+        "channel_mask": [11, 15, 20, 25],
+        "network_key": {
+            "key": "01030507090b0d0f00020406080a0c0d",
+            "sequence_number": 0,
+            "frame_counter": 66012
+        },
+        "devices": [
+            {"nwk_address": "1234", "ieee_address": "00158d0001234567", "is_child": true},
+            {
+                "nwk_address": null,
+                "ieee_address": "000b57fffe012345",
+                "is_child": false,
+                "link_key": {
+                    "key": "5a6967426565416c6c69616e63653039",
+                    "rx_counter": 0,
+                    "tx_counter": 10
+                }
+            }
+        ]
+    }"#;
+    let backup: Backup = serde_json::from_str(json).unwrap();
+    assert_eq!(backup.coordinator_ieee, 0x0012_4b00_09d6_9f77);
+    assert_eq!(backup.channel_mask, vec![11, 15, 20, 25]);
+    assert_eq!(backup.devices[0].nwk_address, Some(0x1234));
+    assert_eq!(backup.devices[1].nwk_address, None);
+    assert_eq!(backup.devices[1].link_key.as_ref().unwrap().tx_counter, 10);
+    assert_eq!(
+        backup.stack_specific["zstack"]["tclk_seed"],
+        "c04884427c8a1ed7bb8412815ccce7aa"
+    );
+    // Written back as read
+    let again: Backup = serde_json::from_str(&serde_json::to_string(&backup).unwrap()).unwrap();
+    assert_eq!(again, backup);
+}
+
+#[test]
+fn restore_parameters() {
+    let mut backup = Backup::from_parameters(&parameters(), vec![]).unwrap();
+    backup.channel_mask = vec![11, 15, 20, 25];
+    backup.devices.push(DeviceBackup {
+        ieee_address: 0x000b_57ff_fe01_2345,
+        nwk_address: None,
+        is_child: false,
+        link_key: Some(LinkKey {
+            key: [0xaa; 16],
+            rx_counter: 0,
+            tx_counter: 0,
+        }),
+    });
+    let parameters = backup.parameters();
+    backup.network_key.frame_counter = u32::MAX - 1;
+    assert!(backup.parameters().contains(&(
+        ParameterCode::NwkFrameCounter,
+        ParameterValue::U32(u32::MAX)
+    )));
+    assert_eq!(
+        parameters[0],
+        (ParameterCode::ApsDesignedCoordinator, ParameterValue::U8(1))
+    );
+    assert!(parameters.contains(&(ParameterCode::ChannelMask, ParameterValue::U32(0x0000_8000))));
+    // The counter is ahead of the one of the backup
+    assert!(parameters.contains(&(ParameterCode::NwkFrameCounter, ParameterValue::U32(22345))));
+    assert_eq!(
+        parameters.last(),
+        Some(&(
+            ParameterCode::LinkKey,
+            ParameterValue::LinkKey {
+                ieee_address: 0x000b_57ff_fe01_2345,
+                key: [0xaa; 16],
+            }
+        ))
+    );
+}
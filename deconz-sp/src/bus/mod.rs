@@ -0,0 +1,174 @@
+//! Bounded broadcast of the client events, and of the attribute reports, to
+//! independent subscribers.
+//!
+//! Each subscriber has its own queue. When a subscriber does not keep up and
+//! its queue is full, the oldest events are dropped and the subscriber receives
+//! an `Event::Lagged` telling how many it missed.
+
+use futures::task::{self, Task};
+use futures::{try_ready, Async, Poll, Stream};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::Event;
+
+#[cfg(test)]
+mod tests;
+
+/// Number of events kept for each subscriber, by default.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+struct Queue<T> {
+    items: VecDeque<T>,
+    missed: u64,
+    closed: bool,
+    task: Option<Task>,
+}
+
+impl<T> Queue<T> {
+    fn notify(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.notify();
+        }
+    }
+}
+
+/// Queue of a receiver, which is forgotten once the receiver is dropped.
+type WeakQueue<T> = Weak<Mutex<Queue<T>>>;
+
+/// Bounded broadcast of any item, e.g. the attribute reports. Each receiver
+/// has its own queue, whose oldest items are dropped when it is full.
+pub(crate) struct Bus<T> {
+    capacity: usize,
+    subscribers: Arc<Mutex<Vec<WeakQueue<T>>>>,
+}
+
+impl<T> Clone for Bus<T> {
+    fn clone(&self) -> Self {
+        Bus {
+            capacity: self.capacity,
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+pub(crate) type EventBus = Bus<Event>;
+
+impl<T: Clone> Bus<T> {
+    pub fn new(capacity: usize) -> Self {
+        Bus {
+            capacity: capacity.max(1),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn receiver(&self) -> Receiver<T> {
+        let queue = Arc::new(Mutex::new(Queue {
+            items: VecDeque::new(),
+            missed: 0,
+            closed: false,
+            task: None,
+        }));
+        self.subscribers
+            .lock()
+            .expect("Cannot obtain lock on subscribers")
+            .push(Arc::downgrade(&queue));
+        Receiver { queue }
+    }
+
+    /// Hands the item to every receiver. Returns `false` if there is none.
+    pub fn publish(&self, item: T) -> bool {
+        self.for_each_queue(|queue| {
+            if queue.items.len() >= self.capacity {
+                queue.items.pop_front();
+                queue.missed += 1;
+            }
+            queue.items.push_back(item.clone());
+        })
+    }
+
+    /// Ends the streams of the receivers, once they received the pending
+    /// items.
+    pub fn close(&self) {
+        self.for_each_queue(|queue| queue.closed = true);
+    }
+
+    fn for_each_queue(&self, mut apply: impl FnMut(&mut Queue<T>)) -> bool {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .expect("Cannot obtain lock on subscribers");
+        // Dropped receivers are forgotten
+        subscribers.retain(|queue| match queue.upgrade() {
+            Some(queue) => {
+                let mut queue = queue.lock().expect("Cannot obtain lock on queue");
+                apply(&mut queue);
+                queue.notify();
+                true
+            }
+            None => false,
+        });
+        !subscribers.is_empty()
+    }
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> Subscription {
+        Subscription {
+            receiver: self.receiver(),
+        }
+    }
+}
+
+/// What a receiver gets from the bus.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Received<T> {
+    Item(T),
+    /// Number of items dropped because the receiver did not keep up.
+    Lagged(u64),
+}
+
+/// Receiving end of a `Bus`.
+pub(crate) struct Receiver<T> {
+    queue: Arc<Mutex<Queue<T>>>,
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = Received<T>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Received<T>>, ()> {
+        let mut queue = self.queue.lock().expect("Cannot obtain lock on queue");
+        if queue.missed > 0 {
+            let missed = queue.missed;
+            queue.missed = 0;
+            return Ok(Async::Ready(Some(Received::Lagged(missed))));
+        }
+        match queue.items.pop_front() {
+            Some(item) => Ok(Async::Ready(Some(Received::Item(item)))),
+            None if queue.closed => Ok(Async::Ready(None)),
+            None => {
+                queue.task = Some(task::current());
+                Ok(Async::NotReady)
+            }
+        }
+    }
+}
+
+/// Stream of the events of the client. See `Client::subscribe`.
+pub struct Subscription {
+    receiver: Receiver<Event>,
+}
+
+impl Stream for Subscription {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Event>, ()> {
+        let received = try_ready!(self.receiver.poll());
+        Ok(Async::Ready(received.map(|received| match received {
+            Received::Item(event) => event,
+            Received::Lagged(missed) => Event::Lagged { missed },
+        })))
+    }
+}
@@ -0,0 +1,53 @@
+use super::*;
+use futures::Future;
+
+fn event(ieee: u64) -> Event {
+    Event::DeviceUpdated { ieee }
+}
+
+fn ieee_addresses(events: Vec<Event>) -> Vec<Option<u64>> {
+    events
+        .into_iter()
+        .map(|event| match event {
+            Event::DeviceUpdated { ieee } => Some(ieee),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn subscribers_receive_every_event() {
+    let bus = EventBus::new(4);
+    let first = bus.subscribe();
+    let second = bus.subscribe();
+    assert!(bus.publish(event(1)));
+    assert!(bus.publish(event(2)));
+    bus.close();
+    let first = first.collect().wait().unwrap();
+    let second = second.collect().wait().unwrap();
+    assert_eq!(ieee_addresses(first), vec![Some(1), Some(2)]);
+    assert_eq!(ieee_addresses(second), vec![Some(1), Some(2)]);
+}
+
+#[test]
+fn slow_subscriber_is_told_it_lagged() {
+    let bus = EventBus::new(2);
+    let subscription = bus.subscribe();
+    for ieee in 1..=5 {
+        bus.publish(event(ieee));
+    }
+    bus.close();
+    let events = subscription.collect().wait().unwrap();
+    match events[0] {
+        Event::Lagged { missed } => assert_eq!(missed, 3),
+        ref other => panic!("Unexpected event: {:?}", other),
+    }
+    assert_eq!(ieee_addresses(events), vec![None, Some(4), Some(5)]);
+}
+
+#[test]
+fn dropped_subscriptions_are_forgotten() {
+    let bus = EventBus::new(2);
+    drop(bus.subscribe());
+    assert!(!bus.publish(event(1)));
+}
@@ -3,19 +3,21 @@ use crate::Error;
 use futures::sync::oneshot::Receiver;
 use futures::{try_ready, Async, Future, Poll};
 use log::*;
+use std::time::Duration;
+use tokio::timer::Timeout;
 
-pub struct Call {
-    receiver: Receiver<IncomingMessage>,
+pub struct Call<T = IncomingMessage> {
+    receiver: Receiver<T>,
 }
 
-impl Call {
-    pub fn new(receiver: Receiver<IncomingMessage>) -> Self {
+impl<T> Call<T> {
+    pub fn new(receiver: Receiver<T>) -> Self {
         Call { receiver }
     }
 }
 
-impl Future for Call {
-    type Item = IncomingMessage;
+impl<T> Future for Call<T> {
+    type Item = T;
     type Error = Error;
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
@@ -26,3 +28,18 @@ impl Future for Call {
         Ok(Async::Ready(msg))
     }
 }
+
+/// Fails with `Error::Timeout` if the future does not complete within `timeout`.
+pub fn with_timeout<F>(future: F, timeout: Duration) -> impl Future<Item = F::Item, Error = Error>
+where
+    F: Future<Error = Error>,
+{
+    Timeout::new(future, timeout).map_err(|err| {
+        if err.is_elapsed() {
+            Error::Timeout
+        } else {
+            err.into_inner()
+                .unwrap_or(Error::Internal("Timer unavailable"))
+        }
+    })
+}
@@ -0,0 +1,68 @@
+use byteorder::{ByteOrder, LittleEndian};
+use futures::{stream, Future, Stream};
+use log::*;
+
+use super::Client;
+use crate::backup::{Backup, DeviceBackup, PARAMETERS};
+use crate::protocol::constants::ParameterCode;
+use crate::protocol::types::ParameterValue;
+use crate::Error;
+
+impl Client {
+    /// Reads the identity and keys of the network. The link keys are read for
+    /// the devices of the registry.
+    pub fn backup(&self) -> impl Future<Item = Backup, Error = Error> {
+        let parameters = {
+            let client = self.clone();
+            stream::iter_ok(PARAMETERS.to_vec())
+                .and_then(move |parameter| {
+                    client
+                        .read_parameter(parameter)
+                        .map(move |value| (parameter, value))
+                })
+                .collect()
+        };
+        let client = self.clone();
+        let devices = stream::iter_ok(self.devices())
+            .and_then(move |device| {
+                client.link_key(device.ieee_address).then(move |result| {
+                    // Devices using the global trust center link key have none
+                    let link_key = result.ok().and_then(|link_key| link_key);
+                    Ok(DeviceBackup::new(&device, link_key))
+                })
+            })
+            .collect();
+        parameters
+            .join(devices)
+            .and_then(|(parameters, devices)| Backup::from_parameters(&parameters, devices))
+    }
+
+    /// Writes the backup to the device and restarts the network. This replaces
+    /// the network the device was part of.
+    pub fn restore(&self, backup: &Backup) -> impl Future<Item = (), Error = Error> {
+        let parameters = backup.parameters();
+        let identity = backup.identity();
+        let client = self.clone();
+        debug!("Restoring network backup");
+        self.restart_with_parameters(parameters)
+            .and_then(move |_| client.verify_parameters(identity))
+    }
+
+    /// Reads the trust center link key of the device, if the coordinator has
+    /// one for it.
+    pub fn link_key(
+        &self,
+        ieee_address: u64,
+    ) -> impl Future<Item = Option<[u8; 16]>, Error = Error> {
+        let mut argument = vec![0; 8];
+        LittleEndian::write_u64(&mut argument, ieee_address);
+        self.read_parameter_with_argument(ParameterCode::LinkKey, argument)
+            .map(move |value| match value {
+                ParameterValue::LinkKey {
+                    ieee_address: address,
+                    key,
+                } if address == ieee_address => Some(key),
+                _ => None,
+            })
+    }
+}
@@ -0,0 +1,126 @@
+use futures::future::{self, Either};
+use futures::Future;
+
+use super::topology::read_table;
+use super::{Client, CLIENT_ENDPOINT};
+use crate::protocol::types::Address;
+use crate::zcl::{ConfigureReportingStatus, ReportingConfiguration};
+use crate::zdo::{BindingDestination, BindingEntry, BindingTarget};
+use crate::Error;
+
+impl Client {
+    /// Binds the cluster of the device endpoint to the destination, which then
+    /// receives the reports and commands of the cluster. The device must be in
+    /// the registry.
+    pub fn bind_cluster(
+        &self,
+        ieee_address: u64,
+        endpoint: u8,
+        cluster_id: u16,
+        destination: BindingDestination,
+    ) -> impl Future<Item = BindingEntry, Error = Error> {
+        let client = self.clone();
+        self.binding_entry(ieee_address, endpoint, cluster_id, destination)
+            .and_then(move |(nwk_address, entry)| {
+                client.bind(nwk_address, entry).map(move |_| entry)
+            })
+    }
+
+    /// Removes the binding of the cluster of the device endpoint to the
+    /// destination.
+    pub fn unbind_cluster(
+        &self,
+        ieee_address: u64,
+        endpoint: u8,
+        cluster_id: u16,
+        destination: BindingDestination,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.binding_entry(ieee_address, endpoint, cluster_id, destination)
+            .and_then(move |(nwk_address, entry)| client.unbind(nwk_address, entry))
+    }
+
+    /// Reads all the pages of the binding table of the device.
+    pub fn binding_table(
+        &self,
+        nwk_address: u16,
+    ) -> impl Future<Item = Vec<BindingEntry>, Error = Error> {
+        let client = self.clone();
+        read_table(move |start_index| client.mgmt_bind(nwk_address, start_index))
+    }
+
+    /// Binds the cluster of the device endpoint to the coordinator, then asks
+    /// the device to report the attributes. Sleepy sensors are set up this way
+    /// while they are awake after joining.
+    pub fn bind_and_configure_reporting(
+        &self,
+        ieee_address: u64,
+        endpoint: u8,
+        cluster_id: u16,
+        configurations: Vec<ReportingConfiguration>,
+    ) -> impl Future<Item = Vec<ConfigureReportingStatus>, Error = Error> {
+        let client = self.clone();
+        self.binding_entry(
+            ieee_address,
+            endpoint,
+            cluster_id,
+            BindingDestination::Coordinator,
+        )
+        .and_then(move |(nwk_address, entry)| {
+            client.bind(nwk_address, entry).and_then(move |_| {
+                client.configure_reporting(
+                    Address::NWK(nwk_address, endpoint),
+                    cluster_id,
+                    &configurations,
+                )
+            })
+        })
+    }
+
+    /// Returns the NWK address of the device, and the binding entry to the
+    /// destination.
+    fn binding_entry(
+        &self,
+        ieee_address: u64,
+        endpoint: u8,
+        cluster_id: u16,
+        destination: BindingDestination,
+    ) -> impl Future<Item = (u16, BindingEntry), Error = Error> {
+        let nwk_address = match self.device(ieee_address) {
+            Some(device) => device.nwk_address,
+            None => return Either::A(future::err(Error::UnknownDevice(ieee_address))),
+        };
+        let target = match destination {
+            BindingDestination::Coordinator => {
+                let coordinator = self
+                    .mac_address()
+                    .map(|ieee_address| BindingTarget::Device {
+                        ieee_address,
+                        endpoint: CLIENT_ENDPOINT,
+                    });
+                Either::A(coordinator)
+            }
+            BindingDestination::Group(group_id) => {
+                Either::B(future::ok(BindingTarget::Group(group_id)))
+            }
+            BindingDestination::Device {
+                ieee_address,
+                endpoint,
+            } => Either::B(future::ok(BindingTarget::Device {
+                ieee_address,
+                endpoint,
+            })),
+        };
+        Either::B(target.map(move |destination| {
+            (
+                nwk_address,
+                BindingEntry {
+                    source_address: ieee_address,
+                    source_endpoint: endpoint,
+                    cluster_id,
+                    destination,
+                },
+            )
+        }))
+    }
+}
@@ -0,0 +1,329 @@
+use futures::future::{self, loop_fn, Either, Loop};
+use futures::{Future, Stream};
+use std::collections::BTreeMap;
+
+use super::zcl::{expect_cluster_command, expect_default_response};
+use super::Client;
+use crate::protocol::constants::ParameterCode;
+use crate::protocol::types::{Address, ParameterValue, UNUSED_GROUP};
+use crate::zcl::groups::{self, GroupMembership, GroupResponse, ViewGroupResponse};
+use crate::zcl::{ZclFrame, ZclStatus, HA_PROFILE_ID};
+use crate::Error;
+
+/// The group table of the firmware: the coordinator receives the frames sent
+/// to the groups of the table. Its size depends on the firmware, so it is read
+/// entry after entry until the firmware rejects the index.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct GroupTable {
+    /// Number of entries of the table.
+    pub size: usize,
+    /// Entry of each group of the table.
+    pub groups: BTreeMap<u16, u8>,
+}
+
+impl GroupTable {
+    /// Returns the free entry the group takes, or `None` if the group is in
+    /// the table already.
+    pub fn allocate(&mut self, group_id: u16) -> Result<Option<u8>, Error> {
+        if self.groups.contains_key(&group_id) {
+            return Ok(None);
+        }
+        let used: Vec<_> = self.groups.values().cloned().collect();
+        let index = (0..self.size)
+            .map(|index| index as u8)
+            .find(|index| !used.contains(index))
+            .ok_or(Error::GroupTableFull)?;
+        self.groups.insert(group_id, index);
+        Ok(Some(index))
+    }
+}
+
+impl Client {
+    /// Adds the endpoint of the device to the group. The coordinator joins the
+    /// group too, so it receives the frames the members send to the group.
+    pub fn add_to_group(
+        &self,
+        destination: Address,
+        group_id: u16,
+        name: &str,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.groups_request(
+            destination,
+            groups::add_group(group_id, name),
+            groups::ADD_GROUP,
+        )
+        .and_then(|frame| GroupResponse::read(&frame.payload))
+        .and_then(|response| match response.status {
+            ZclStatus::Success | ZclStatus::DuplicateExists => Ok(()),
+            status => Err(Error::ZclStatus(status)),
+        })
+        .and_then(move |_| {
+            if let Some((nwk_address, endpoint)) = client.member(destination) {
+                client.update_device_by_nwk(nwk_address, |registry| {
+                    registry.add_group(nwk_address, endpoint, group_id)
+                });
+            }
+            client.join_group(group_id)
+        })
+    }
+
+    /// Removes the endpoint of the device from the group. The coordinator
+    /// leaves the group when it has no known member left.
+    pub fn remove_from_group(
+        &self,
+        destination: Address,
+        group_id: u16,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.groups_request(
+            destination,
+            groups::remove_group(group_id),
+            groups::REMOVE_GROUP,
+        )
+        .and_then(|frame| GroupResponse::read(&frame.payload))
+        .and_then(|response| match response.status {
+            ZclStatus::Success | ZclStatus::NotFound => Ok(()),
+            status => Err(Error::ZclStatus(status)),
+        })
+        .and_then(move |_| {
+            if let Some((nwk_address, endpoint)) = client.member(destination) {
+                client.update_device_by_nwk(nwk_address, |registry| {
+                    registry.remove_group(nwk_address, endpoint, group_id)
+                });
+            }
+            client.leave_unused_groups(vec![group_id])
+        })
+    }
+
+    /// Returns which of the groups the endpoint is a member of, or all its
+    /// groups if `groups` is empty. In the latter case, the registry is updated
+    /// with the answer.
+    pub fn get_group_membership(
+        &self,
+        destination: Address,
+        groups: &[u16],
+    ) -> impl Future<Item = GroupMembership, Error = Error> {
+        let client = self.clone();
+        let all = groups.is_empty();
+        self.groups_request(
+            destination,
+            groups::get_group_membership(groups),
+            groups::GET_GROUP_MEMBERSHIP,
+        )
+        .and_then(|frame| GroupMembership::read(&frame.payload))
+        .map(move |membership| {
+            if let (Some((nwk_address, endpoint)), true) = (client.member(destination), all) {
+                let groups = membership.groups.iter().cloned().collect();
+                client.update_device_by_nwk(nwk_address, |registry| {
+                    registry.set_groups(nwk_address, endpoint, groups)
+                });
+            }
+            membership
+        })
+    }
+
+    /// Returns the name of the group, as stored by the endpoint.
+    pub fn view_group(
+        &self,
+        destination: Address,
+        group_id: u16,
+    ) -> impl Future<Item = String, Error = Error> {
+        self.groups_request(
+            destination,
+            groups::view_group(group_id),
+            groups::VIEW_GROUP,
+        )
+        .and_then(|frame| ViewGroupResponse::read(&frame.payload))
+        .and_then(|response| match response.status {
+            ZclStatus::Success => Ok(response.name),
+            status => Err(Error::ZclStatus(status)),
+        })
+    }
+
+    /// Removes the endpoint of the device from all its groups.
+    pub fn remove_all_groups(&self, destination: Address) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.zcl_request(
+            destination,
+            HA_PROFILE_ID,
+            groups::CLUSTER_ID,
+            groups::remove_all_groups(),
+        )
+        .and_then(|frame| expect_default_response(frame, "RemoveAllGroups"))
+        .and_then(move |_| {
+            let groups = match client.member(destination) {
+                Some((nwk_address, endpoint)) => {
+                    let groups: Vec<_> = client
+                        .device_by_nwk(nwk_address)
+                        .map(|device| {
+                            device
+                                .groups
+                                .into_iter()
+                                .filter(|(member, _)| *member == endpoint)
+                                .map(|(_, group_id)| group_id)
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    client.update_device_by_nwk(nwk_address, |registry| {
+                        registry.set_groups(nwk_address, endpoint, Default::default())
+                    });
+                    groups
+                }
+                None => Vec::new(),
+            };
+            client.leave_unused_groups(groups)
+        })
+    }
+
+    /// Returns the IEEE address and the endpoint of the known members of the
+    /// group.
+    pub fn group_members(&self, group_id: u16) -> Vec<(u64, u8)> {
+        self.read_registry(|registry| registry.group_members(group_id))
+    }
+
+    fn groups_request(
+        &self,
+        destination: Address,
+        frame: ZclFrame,
+        command_id: u8,
+    ) -> impl Future<Item = ZclFrame, Error = Error> {
+        self.zcl_request(destination, HA_PROFILE_ID, groups::CLUSTER_ID, frame)
+            .and_then(move |frame| expect_cluster_command(frame, command_id, "Groups"))
+    }
+
+    /// Returns the NWK address and the endpoint the request was sent to, if the
+    /// device is known.
+    fn member(&self, destination: Address) -> Option<(u16, u8)> {
+        match destination {
+            Address::NWK(nwk_address, endpoint) => Some((nwk_address, endpoint)),
+            Address::IEEE(ieee_address, endpoint) => self
+                .device(ieee_address)
+                .map(|device| (device.nwk_address, endpoint)),
+            Address::Group(_) => None,
+        }
+    }
+
+    /// Reads the group table of the firmware, unless it was read already.
+    fn load_group_table(&self) -> impl Future<Item = (), Error = Error> {
+        let loaded = self
+            .coordinator_groups
+            .lock()
+            .expect("Cannot obtain lock on coordinator groups")
+            .is_some();
+        if loaded {
+            return Either::A(future::ok(()));
+        }
+        let client = self.clone();
+        let read = loop_fn((0, GroupTable::default()), move |(index, mut table)| {
+            client
+                .read_parameter_with_argument(ParameterCode::GroupAddress, vec![index])
+                .then(move |result| match result {
+                    Ok(ParameterValue::GroupAddress { group_id, .. }) => {
+                        table.size += 1;
+                        if group_id != UNUSED_GROUP {
+                            table.groups.insert(group_id, index);
+                        }
+                        if index == u8::MAX {
+                            Ok(Loop::Break(table))
+                        } else {
+                            Ok(Loop::Continue((index + 1, table)))
+                        }
+                    }
+                    Ok(_) => Err(Error::Decoding("Invalid group table entry")),
+                    // End of the table
+                    Err(Error::NonSuccessResponse(_)) => Ok(Loop::Break(table)),
+                    Err(err) => Err(err),
+                })
+        });
+        let client = self.clone();
+        Either::B(read.map(move |table| {
+            client
+                .coordinator_groups
+                .lock()
+                .expect("Cannot obtain lock on coordinator groups")
+                .get_or_insert(table);
+        }))
+    }
+
+    /// Adds the coordinator to the group, unless it is already a member, by
+    /// writing a free entry of the group table of the firmware.
+    fn join_group(&self, group_id: u16) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.load_group_table().and_then(move |_| {
+            let index = client
+                .coordinator_groups
+                .lock()
+                .expect("Cannot obtain lock on coordinator groups")
+                .as_mut()
+                .map_or(Ok(None), |table| table.allocate(group_id));
+            let index = match index {
+                Ok(Some(index)) => index,
+                Ok(None) => return Either::A(future::ok(())),
+                Err(err) => return Either::A(future::err(err)),
+            };
+            let write = client
+                .write_parameter(
+                    ParameterCode::GroupAddress,
+                    ParameterValue::GroupAddress { index, group_id },
+                )
+                .map_err(move |err| {
+                    // The entry stays free
+                    if let Some(table) = client
+                        .coordinator_groups
+                        .lock()
+                        .expect("Cannot obtain lock on coordinator groups")
+                        .as_mut()
+                    {
+                        table.groups.remove(&group_id);
+                    }
+                    err
+                });
+            Either::B(write)
+        })
+    }
+
+    /// Removes the coordinator from the groups without known members.
+    fn leave_unused_groups(&self, groups: Vec<u16>) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.load_group_table().and_then(move |_| {
+            let unused: Vec<_> = {
+                let table = client
+                    .coordinator_groups
+                    .lock()
+                    .expect("Cannot obtain lock on coordinator groups");
+                groups
+                    .into_iter()
+                    .filter(|group_id| client.group_members(*group_id).is_empty())
+                    .filter_map(|group_id| {
+                        table
+                            .as_ref()
+                            .and_then(|table| table.groups.get(&group_id))
+                            .map(|index| (group_id, *index))
+                    })
+                    .collect()
+            };
+            futures::stream::iter_ok(unused).for_each(move |(group_id, index)| {
+                let client = client.clone();
+                client
+                    .write_parameter(
+                        ParameterCode::GroupAddress,
+                        ParameterValue::GroupAddress {
+                            index,
+                            group_id: UNUSED_GROUP,
+                        },
+                    )
+                    .map(move |_| {
+                        if let Some(table) = client
+                            .coordinator_groups
+                            .lock()
+                            .expect("Cannot obtain lock on coordinator groups")
+                            .as_mut()
+                        {
+                            table.groups.remove(&group_id);
+                        }
+                    })
+            })
+        })
+    }
+}
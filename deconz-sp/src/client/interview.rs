@@ -0,0 +1,114 @@
+use futures::future::{self, Either};
+use futures::{stream, Future, Stream};
+use log::*;
+
+use super::Client;
+use crate::protocol::types::Address;
+use crate::registry::{Device, InterviewState};
+use crate::zcl::basic;
+use crate::{Error, Event};
+
+/// Attributes of the Basic cluster read during the interview.
+const BASIC_ATTRIBUTES: [u16; 4] = [
+    basic::MANUFACTURER_NAME,
+    basic::MODEL_IDENTIFIER,
+    basic::POWER_SOURCE,
+    basic::SW_BUILD_ID,
+];
+
+impl Client {
+    /// Reads the node descriptor, the simple descriptor of each active endpoint
+    /// and the Basic cluster attributes of the device. The responses are
+    /// recorded in the registry, and the updated device is returned.
+    pub fn interview(&self, ieee_address: u64) -> impl Future<Item = Device, Error = Error> {
+        let nwk_address = match self.read_registry(|registry| registry.nwk_address(ieee_address)) {
+            Some(nwk_address) => nwk_address,
+            None => return Either::A(future::err(Error::UnknownDevice(ieee_address))),
+        };
+        let client = self.clone();
+        let descriptors = {
+            let client = self.clone();
+            self.node_descriptor(nwk_address)
+                .and_then(move |_| client.active_endpoints(nwk_address))
+        };
+        let interview = descriptors
+            .and_then({
+                let client = self.clone();
+                move |endpoints| {
+                    stream::iter_ok(endpoints)
+                        .and_then(move |endpoint| client.simple_descriptor(nwk_address, endpoint))
+                        .collect()
+                }
+            })
+            .and_then({
+                let client = self.clone();
+                move |descriptors| {
+                    // Devices without a Basic server endpoint are asked on the first one
+                    let endpoint = descriptors
+                        .iter()
+                        .find(|descriptor| descriptor.input_clusters.contains(&basic::CLUSTER_ID))
+                        .or_else(|| descriptors.first())
+                        .map(|descriptor| descriptor.endpoint);
+                    match endpoint {
+                        Some(endpoint) => Either::A(
+                            client
+                                .read_attributes(
+                                    Address::NWK(nwk_address, endpoint),
+                                    basic::CLUSTER_ID,
+                                    &BASIC_ATTRIBUTES,
+                                )
+                                .map(|_| ()),
+                        ),
+                        None => Either::B(future::ok(())),
+                    }
+                }
+            })
+            .and_then(move |_| {
+                client
+                    .device(ieee_address)
+                    .ok_or(Error::UnknownDevice(ieee_address))
+            });
+        Either::B(interview)
+    }
+
+    /// Starts the interview of the device in the background, unless it is
+    /// already running or completed.
+    pub(super) fn start_interview(&self, ieee_address: u64) {
+        let start = self.write_registry(|registry| {
+            match registry.get(ieee_address).map(|device| device.interview) {
+                Some(InterviewState::NotStarted) | Some(InterviewState::Failed) => {
+                    registry.set_interview(ieee_address, InterviewState::Running)
+                }
+                _ => false,
+            }
+        });
+        if !start {
+            return;
+        }
+        debug!("Interviewing device {:#018x}", ieee_address);
+        let client = self.clone();
+        let interview = self.interview(ieee_address).then(move |result| {
+            let (state, event) = match result {
+                Ok(_) => (
+                    InterviewState::Completed,
+                    Event::InterviewCompleted { ieee: ieee_address },
+                ),
+                Err(err) => {
+                    debug!("Interview of {:#018x} failed: {}", ieee_address, err);
+                    (
+                        InterviewState::Failed,
+                        Event::InterviewFailed {
+                            ieee: ieee_address,
+                            reason: err.to_string(),
+                        },
+                    )
+                }
+            };
+            client.write_registry(|registry| registry.set_interview(ieee_address, state));
+            client.persist(ieee_address);
+            client.emit(event);
+            Ok(())
+        });
+        tokio::spawn(interview);
+    }
+}
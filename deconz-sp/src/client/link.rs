@@ -0,0 +1,230 @@
+use futures::future::{loop_fn, Loop};
+use futures::stream::{SplitSink, SplitStream};
+use futures::sync::mpsc::{unbounded, UnboundedSender};
+use futures::{Future, Sink, Stream};
+use log::*;
+use std::collections::BTreeMap;
+use std::mem;
+use std::path::Path;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+
+use super::sleepy::HeldRequests;
+use super::zcl::ZclSubscription;
+use super::zdo::ZdoSubscription;
+use super::{Client, PendingRequest, SubscriptionId};
+use crate::protocol::constants::CommandCode;
+use crate::protocol::OutgoingMessage;
+use crate::serial::{self, SerialFrames};
+use crate::{Error, Event};
+
+/// An opened serial device.
+pub(super) type Link = (SplitSink<SerialFrames>, SplitStream<SerialFrames>);
+
+/// First delay before reopening the serial device, doubled after each failed
+/// attempt.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Fails with `Error::Disconnected` the requests waiting for an answer of the
+/// device or of the network, and the requests held for sleepy devices.
+pub(super) fn fail_pending_requests(
+    subscriptions: &RwLock<BTreeMap<SubscriptionId, PendingRequest>>,
+    zcl_subscriptions: &RwLock<Vec<ZclSubscription>>,
+    zdo_subscriptions: &RwLock<Vec<ZdoSubscription>>,
+    held_requests: &Mutex<HeldRequests>,
+) {
+    let subscriptions = mem::take(
+        &mut *subscriptions
+            .write()
+            .expect("Cannot obtain write-lock on subscriptions"),
+    );
+    for (_, subscription) in subscriptions {
+        let _ = subscription.send(Err(Error::Disconnected));
+    }
+    let zcl_subscriptions = mem::take(
+        &mut *zcl_subscriptions
+            .write()
+            .expect("Cannot obtain write-lock on ZCL subscriptions"),
+    );
+    for subscription in zcl_subscriptions {
+        let _ = subscription.sender.send(Err(Error::Disconnected));
+    }
+    let zdo_subscriptions = mem::take(
+        &mut *zdo_subscriptions
+            .write()
+            .expect("Cannot obtain write-lock on ZDO subscriptions"),
+    );
+    for subscription in zdo_subscriptions {
+        let _ = subscription.sender.send(Err(Error::Disconnected));
+    }
+    let held_requests = mem::take(
+        &mut *held_requests
+            .lock()
+            .expect("Cannot obtain lock on held requests"),
+    );
+    for (_, requests) in held_requests {
+        for request in requests {
+            let _ = request.send(Err(Error::Disconnected));
+        }
+    }
+}
+
+#[derive(Default)]
+pub(super) struct LinkState {
+    /// Messages to write to the serial device, `None` while the link is down.
+    sender: Option<UnboundedSender<OutgoingMessage>>,
+    /// APS requests made while the link was down, sent once it is back.
+    queued: Vec<OutgoingMessage>,
+}
+
+pub(super) fn open(device_path: &Path, baud_rate: u32) -> Result<Link, Error> {
+    Ok(serial::open(device_path, baud_rate)?.split())
+}
+
+fn reconnect_delay(attempt: u32) -> Duration {
+    RECONNECT_DELAY
+        .checked_mul(1 << attempt.min(16))
+        .map_or(MAX_RECONNECT_DELAY, |delay| delay.min(MAX_RECONNECT_DELAY))
+}
+
+impl Client {
+    /// Writes the message to the serial device. While the link is down, APS
+    /// requests are queued if `ClientOptions::replay_aps_requests` is set, and
+    /// other messages fail with `Error::Disconnected`.
+    ///
+    /// `subscribe` registers the caller waiting for the response. It is called
+    /// with the link state locked, so the caller is failed if the link goes
+    /// down before the message is written.
+    pub(super) fn write_message(
+        &self,
+        message: OutgoingMessage,
+        subscribe: impl FnOnce(),
+    ) -> Result<(), Error> {
+        let mut link = self.link.lock().expect("Cannot obtain lock on link");
+        match &link.sender {
+            Some(sender) => {
+                subscribe();
+                sender
+                    .unbounded_send(message)
+                    .map_err(|_| Error::Internal("Cannot send message"))
+            }
+            None if self.replay_aps_requests && message.command == CommandCode::ApsDataRequest => {
+                debug!("Link is down, APS request queued");
+                subscribe();
+                link.queued.push(message);
+                Ok(())
+            }
+            None => Err(Error::Disconnected),
+        }
+    }
+
+    /// Starts reading and writing the serial device, and sends the queued APS
+    /// requests.
+    pub(super) fn connect(&self, (sink, stream): Link) {
+        let (sender, receiver) = unbounded();
+        let queued = {
+            let mut link = self.link.lock().expect("Cannot obtain lock on link");
+            link.sender = Some(sender.clone());
+            mem::take(&mut link.queued)
+        };
+        if !queued.is_empty() {
+            debug!("Replaying {} queued APS requests", queued.len());
+        }
+        for message in queued {
+            if sender.unbounded_send(message).is_err() {
+                warn!("Cannot replay queued APS request");
+            }
+        }
+        let forward_to_sink = receiver
+            .forward(sink.sink_map_err(|err| error!("Cannot write to device: {}", err)))
+            .map(|_| ());
+        let client = self.clone();
+        let process_stream = stream
+            .for_each({
+                let client = client.clone();
+                move |message| {
+                    client.handle_message(message);
+                    Ok(())
+                }
+            })
+            .then(move |result| {
+                client.link_down(result.err());
+                Ok(())
+            });
+        tokio::spawn(forward_to_sink);
+        tokio::spawn(process_stream);
+    }
+
+    /// Fails the pending and held requests, and reopens the device if
+    /// `ClientOptions::reconnect` is set.
+    fn link_down(&self, error: Option<Error>) {
+        if let Some(err) = error {
+            error!("Error occured while processing stream: {}", err);
+            self.emit(Event::LinkError {
+                reason: err.to_string(),
+            });
+        }
+        warn!("Link to device {} is down", self.device_path.display());
+        {
+            let mut link = self.link.lock().expect("Cannot obtain lock on link");
+            // Dropping the sender ends the writing task
+            link.sender = None;
+            fail_pending_requests(
+                &self.subscriptions,
+                &self.zcl_subscriptions,
+                &self.zdo_subscriptions,
+                &self.held_requests,
+            );
+        }
+        self.emit(Event::LinkDown);
+        if self.reconnect {
+            self.reconnect();
+        } else {
+            self.emit(Event::LinkClosed);
+            self.events.close();
+            self.reports.close();
+        }
+    }
+
+    /// Reopens the serial device, waiting longer after each failed attempt.
+    fn reconnect(&self) {
+        let client = self.clone();
+        let reconnect = loop_fn(0, move |attempt| {
+            let delay = reconnect_delay(attempt);
+            let client = client.clone();
+            Delay::new(Instant::now() + delay).then(move |_| {
+                match open(&client.device_path, client.baud_rate) {
+                    Ok(link) => Ok(Loop::Break((client, link))),
+                    Err(err) => {
+                        debug!(
+                            "Cannot reopen device {}: {}",
+                            client.device_path.display(),
+                            err
+                        );
+                        Ok(Loop::Continue(attempt + 1))
+                    }
+                }
+            })
+        })
+        .map(|(client, link): (Client, Link)| {
+            client.connect(link);
+            client.emit(Event::LinkUp);
+            client.link_up();
+        });
+        tokio::spawn(reconnect);
+    }
+
+    /// Reads the network state, and the frames received while the link was
+    /// down.
+    fn link_up(&self) {
+        let client = self.clone();
+        let state = self
+            .device_state()
+            .map(move |state| client.network_state_changed(state))
+            .map_err(|err| warn!("Cannot read device state: {}", err));
+        tokio::spawn(state);
+        self.poll_device();
+    }
+}
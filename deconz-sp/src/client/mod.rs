@@ -0,0 +1,586 @@
+use futures::future::{loop_fn, Loop};
+use futures::sync::oneshot::{channel, Sender};
+use futures::Future;
+use log::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use crate::bus::{Bus, EventBus, Subscription, DEFAULT_CAPACITY};
+use crate::call::Call;
+use crate::network::PermitJoinWindows;
+use crate::protocol::constants::{NetworkStateCode, ParameterCode, StatusCode};
+use crate::protocol::types::{Address, ParameterValue};
+use crate::protocol::{IncomingMessage, IncomingPayload, OutgoingMessage};
+use crate::registry::DeviceRegistry;
+use crate::reports::Report;
+use crate::serial::DEFAULT_BAUD_RATE;
+use crate::storage::Storage;
+use crate::zdo::{is_broadcast, DEVICE_ANNOUNCE, ZDO_PROFILE_ID};
+use crate::{Error, Event};
+
+mod backup;
+mod binding;
+mod groups;
+mod interview;
+mod link;
+mod network;
+mod registry;
+mod reports;
+mod scenes;
+mod sleepy;
+mod topology;
+mod zcl;
+mod zdo;
+
+#[cfg(test)]
+mod tests;
+
+use groups::GroupTable;
+use link::LinkState;
+use network::NetworkStateWaiter;
+use sleepy::HeldRequests;
+use zcl::ZclSubscription;
+use zdo::ZdoSubscription;
+
+type SubscriptionId = (u8, u8); // (seq,command_code)
+
+/// Caller waiting for the response to a request sent to the device.
+type PendingRequest = Sender<Result<IncomingMessage, Error>>;
+
+/// Endpoint of the coordinator used as source of APS requests.
+pub const CLIENT_ENDPOINT: u8 = 0x01;
+
+/// Radius of APS requests, 0 lets the firmware use the maximum radius.
+const DEFAULT_RADIUS: u8 = 0;
+
+/// How long to wait for a remote device to answer a ZCL or ZDO request.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const DEFAULT_SLEEPY_HOLD_TIME: Duration = Duration::from_secs(3600);
+
+/// How long changed attribute values wait before their device is saved.
+const ATTRIBUTE_SAVE_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct PollState {
+    running: bool,
+    pending: bool,
+}
+
+/// Options of `Client::with_options`.
+pub struct ClientOptions {
+    /// Where the known devices are saved. They are reloaded when the client
+    /// starts.
+    pub storage: Option<Box<dyn Storage>>,
+    /// Defaults to the baud rate of the ConBee, see `discover::Adapter` for the
+    /// other adapters.
+    pub baud_rate: u32,
+    /// Number of events, or attribute reports, kept for each subscriber which
+    /// does not keep up.
+    pub event_capacity: usize,
+    /// Whether the serial device is reopened when the link goes down. Enabled
+    /// by default.
+    pub reconnect: bool,
+    /// Whether the APS requests made while the link is down are sent once it is
+    /// back, instead of failing with `Error::Disconnected`.
+    pub replay_aps_requests: bool,
+    /// How long ZCL requests to a sleepy device are held until it polls, one
+    /// hour by default. `None` sends them at once.
+    pub sleepy_hold_time: Option<Duration>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            storage: None,
+            baud_rate: DEFAULT_BAUD_RATE,
+            event_capacity: DEFAULT_CAPACITY,
+            reconnect: true,
+            replay_aps_requests: false,
+            sleepy_hold_time: Some(DEFAULT_SLEEPY_HOLD_TIME),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Client {
+    device_path: Arc<Path>,
+    baud_rate: u32,
+    reconnect: bool,
+    replay_aps_requests: bool,
+    sleepy_hold_time: Option<Duration>,
+    link: Arc<Mutex<LinkState>>,
+    events: EventBus,
+    next_seq: Arc<RwLock<u8>>,
+    next_request_id: Arc<RwLock<u8>>,
+    next_transaction_sequence: Arc<RwLock<u8>>,
+    subscriptions: Arc<RwLock<BTreeMap<SubscriptionId, PendingRequest>>>,
+    zcl_subscriptions: Arc<RwLock<Vec<ZclSubscription>>>,
+    zdo_subscriptions: Arc<RwLock<Vec<ZdoSubscription>>>,
+    /// Devices asked to leave by a Mgmt_Leave_req, by ZDO transaction sequence.
+    pending_leaves: Arc<Mutex<BTreeMap<u8, u64>>>,
+    registry: Arc<RwLock<DeviceRegistry>>,
+    storage: Option<Arc<Mutex<Box<dyn Storage>>>>,
+    /// Devices whose cached attributes changed since they were saved.
+    unsaved_devices: Arc<Mutex<BTreeSet<u64>>>,
+    network_state_waiters: Arc<Mutex<Vec<NetworkStateWaiter>>>,
+    permit_join_windows: Arc<Mutex<PermitJoinWindows>>,
+    /// Group table of the firmware, read when first needed.
+    coordinator_groups: Arc<Mutex<Option<GroupTable>>>,
+    poll_state: Arc<Mutex<PollState>>,
+    reports: Bus<Report>,
+    held_requests: Arc<Mutex<HeldRequests>>,
+}
+
+/// Whether a response from `source` may answer a request sent to `destination`.
+/// The addresses of the known devices are translated through the registry.
+fn is_from(destination: &Address, source: &Address, registry: &DeviceRegistry) -> bool {
+    match (destination, source) {
+        // Any device receiving the broadcast may answer
+        (Address::NWK(destination, _), _) if is_broadcast(*destination) => true,
+        (Address::NWK(destination, _), Address::NWK(source, _)) => destination == source,
+        (Address::IEEE(destination, _), Address::IEEE(source, _)) => destination == source,
+        // Indications mostly carry the NWK address of the source
+        (Address::IEEE(destination, _), Address::NWK(source, _)) => {
+            registry.ieee_address(*source) == Some(*destination)
+        }
+        (Address::NWK(destination, _), Address::IEEE(source, _)) => {
+            registry.nwk_address(*source) == Some(*destination)
+        }
+        // Any member of the group may answer
+        (Address::Group(_), _) => true,
+        _ => false,
+    }
+}
+
+fn next(counter: &RwLock<u8>) -> u8 {
+    let mut counter = counter
+        .write()
+        .expect("Cannot obtain write-lock on counter");
+    let value = *counter;
+    *counter = counter.wrapping_add(1);
+    value
+}
+
+impl Client {
+    pub fn new<P: AsRef<Path>>(device_path: P) -> Result<(Self, Subscription), Error> {
+        Client::with_options(device_path, ClientOptions::default())
+    }
+
+    pub fn with_options<P: AsRef<Path>>(
+        device_path: P,
+        options: ClientOptions,
+    ) -> Result<(Self, Subscription), Error> {
+        let mut registry = DeviceRegistry::new();
+        let storage = match options.storage {
+            Some(mut storage) => {
+                for device in storage.load_devices()? {
+                    registry.insert(device);
+                }
+                debug!("{} devices loaded from storage", registry.len());
+                Some(Arc::new(Mutex::new(storage)))
+            }
+            None => None,
+        };
+        let device_path = Arc::from(device_path.as_ref());
+        let link = link::open(&device_path, options.baud_rate)?;
+        let events = EventBus::new(options.event_capacity);
+        let subscription = events.subscribe();
+        let client = Self {
+            device_path,
+            baud_rate: options.baud_rate,
+            reconnect: options.reconnect,
+            replay_aps_requests: options.replay_aps_requests,
+            sleepy_hold_time: options.sleepy_hold_time,
+            link: Arc::new(Mutex::new(LinkState::default())),
+            events,
+            next_seq: Arc::new(RwLock::new(0)),
+            next_request_id: Arc::new(RwLock::new(0)),
+            next_transaction_sequence: Arc::new(RwLock::new(0)),
+            subscriptions: Arc::new(RwLock::new(BTreeMap::new())),
+            zcl_subscriptions: Arc::new(RwLock::new(Vec::new())),
+            zdo_subscriptions: Arc::new(RwLock::new(Vec::new())),
+            pending_leaves: Arc::new(Mutex::new(BTreeMap::new())),
+            registry: Arc::new(RwLock::new(registry)),
+            storage,
+            unsaved_devices: Arc::new(Mutex::new(BTreeSet::new())),
+            network_state_waiters: Arc::new(Mutex::new(Vec::new())),
+            permit_join_windows: Arc::new(Mutex::new(PermitJoinWindows::default())),
+            coordinator_groups: Arc::new(Mutex::new(None)),
+            poll_state: Arc::new(Mutex::new(PollState::default())),
+            reports: Bus::new(options.event_capacity),
+            held_requests: Arc::new(Mutex::new(HeldRequests::new())),
+        };
+        client.connect(link);
+        Ok((client, subscription))
+    }
+
+    /// Returns a new stream of the events of the client. Each stream receives
+    /// all the events emitted after it was created.
+    pub fn subscribe(&self) -> Subscription {
+        self.events.subscribe()
+    }
+
+    fn next_seq(&self) -> u8 {
+        next(&self.next_seq)
+    }
+
+    fn next_transaction_sequence(&self) -> u8 {
+        next(&self.next_transaction_sequence)
+    }
+
+    fn send_request(
+        &self,
+        msg: OutgoingMessage,
+    ) -> impl Future<Item = IncomingMessage, Error = Error> {
+        let (sender, receiver) = channel();
+        let id = (msg.seq, msg.command.code());
+        let subscriptions = self.subscriptions.clone();
+        let written = self.write_message(msg, move || {
+            subscriptions
+                .write()
+                .expect("Cannot get write-lock on subscription")
+                .insert(id, sender);
+        });
+        futures::future::result(written)
+            .and_then(|_| Call::new(receiver))
+            .and_then(|response| response)
+    }
+
+    /// Hands a message read from the device to the request waiting for it, or
+    /// handles it as a notification.
+    fn handle_message(&self, message: IncomingMessage) {
+        trace!("Received message: {:?}", message);
+        let subscription = self
+            .subscriptions
+            .write()
+            .expect("Cannot obtain write-lock on subscriptions")
+            .remove(&(message.seq, message.command.code()));
+        if let Some(subscription) = subscription {
+            trace!("Subscription exists!");
+            if subscription.send(Ok(message)).is_err() {
+                debug!("Response received after the request was abandoned");
+            }
+        } else {
+            debug!("No subscription");
+            self.handle_notification(message.payload);
+        }
+    }
+
+    fn emit(&self, event: Event) {
+        if !self.events.publish(event) {
+            trace!("Event dropped: no subscriber");
+        }
+    }
+
+    fn notify(&self, payload: IncomingPayload) {
+        self.emit(Event::Notification(payload));
+    }
+
+    fn handle_notification(&self, payload: IncomingPayload) {
+        if let IncomingPayload::DeviceState { state, .. } = payload {
+            self.network_state_changed(state);
+        }
+        match payload {
+            IncomingPayload::DeviceState {
+                apsde_data_indication,
+                apsde_data_confirm,
+                ..
+            } if apsde_data_indication || apsde_data_confirm => {
+                self.poll_device();
+                self.notify(payload);
+            }
+            payload @ IncomingPayload::ApsDataIndication { .. } => {
+                self.dispatch_indication(payload)
+            }
+            IncomingPayload::MacPoll { address, lqi, rssi } => self.mac_poll(address, lqi, rssi),
+            payload => self.notify(payload),
+        }
+    }
+
+    fn dispatch_indication(&self, payload: IncomingPayload) {
+        if let IncomingPayload::ApsDataIndication {
+            source,
+            profile_id,
+            cluster_id,
+            ref asdu,
+            lqi,
+            rssi,
+            ..
+        } = payload
+        {
+            // The announce of a device is handled with its IEEE address
+            let announce = profile_id == ZDO_PROFILE_ID && cluster_id == DEVICE_ANNOUNCE;
+            self.track_source(&source, lqi, rssi, !announce);
+            let dispatched = if profile_id == ZDO_PROFILE_ID {
+                self.dispatch_zdo(source, cluster_id, asdu)
+            } else {
+                self.dispatch_zcl(source, cluster_id, asdu)
+            };
+            if dispatched {
+                return;
+            }
+        }
+        self.notify(payload);
+    }
+
+    /// Reads the pending APS indications and confirms until the device reports
+    /// none is left. Only one polling loop runs at a time: a request arriving
+    /// while it runs makes the loop check the device state once more.
+    fn poll_device(&self) {
+        {
+            let mut state = self
+                .poll_state
+                .lock()
+                .expect("Cannot obtain lock on poll state");
+            if state.running {
+                state.pending = true;
+                return;
+            }
+            state.running = true;
+            state.pending = false;
+        }
+        let client = self.clone();
+        let poll_state = self.poll_state.clone();
+        let polling = loop_fn(client, |client| {
+            let seq = client.next_seq();
+            client
+                .send_request(OutgoingMessage::new_device_state(seq))
+                .and_then(move |response| match response.payload {
+                    IncomingPayload::DeviceState {
+                        apsde_data_indication: true,
+                        ..
+                    } => futures::future::Either::A(futures::future::Either::A(
+                        client.aps_data_indication().map(move |payload| {
+                            client.dispatch_indication(payload);
+                            Loop::Continue(client)
+                        }),
+                    )),
+                    IncomingPayload::DeviceState {
+                        apsde_data_confirm: true,
+                        ..
+                    } => futures::future::Either::A(futures::future::Either::B(
+                        client.aps_data_confirm().map(move |payload| {
+                            client.notify(payload);
+                            Loop::Continue(client)
+                        }),
+                    )),
+                    _ => {
+                        let mut state = client
+                            .poll_state
+                            .lock()
+                            .expect("Cannot obtain lock on poll state");
+                        if state.pending {
+                            state.pending = false;
+                            futures::future::Either::B(futures::future::ok(Loop::Continue(
+                                client.clone(),
+                            )))
+                        } else {
+                            state.running = false;
+                            futures::future::Either::B(futures::future::ok(Loop::Break(())))
+                        }
+                    }
+                })
+        })
+        .map_err(move |err| {
+            error!("Error occured while polling device: {}", err);
+            poll_state
+                .lock()
+                .expect("Cannot obtain lock on poll state")
+                .running = false;
+        });
+        tokio::spawn(polling);
+    }
+
+    pub fn read_parameter(
+        &self,
+        parameter: ParameterCode,
+    ) -> impl Future<Item = ParameterValue, Error = Error> {
+        let seq = self.next_seq();
+        self.read_parameter_request(OutgoingMessage::new_read_parameter(seq, parameter))
+    }
+
+    /// Reads a parameter selected by an argument, e.g. the link key of a
+    /// device, selected by its IEEE address.
+    pub fn read_parameter_with_argument(
+        &self,
+        parameter: ParameterCode,
+        argument: Vec<u8>,
+    ) -> impl Future<Item = ParameterValue, Error = Error> {
+        let seq = self.next_seq();
+        self.read_parameter_request(OutgoingMessage::new_read_parameter_with_argument(
+            seq, parameter, argument,
+        ))
+    }
+
+    fn read_parameter_request(
+        &self,
+        request: OutgoingMessage,
+    ) -> impl Future<Item = ParameterValue, Error = Error> {
+        self.send_request(request)
+            .and_then(|response| match response.status {
+                StatusCode::Success => match response.payload {
+                    IncomingPayload::ReadParameter { value, .. } => futures::future::ok(value),
+                    payload => futures::future::err(Error::UnexpectedResponsePayload(
+                        "ReadParameter",
+                        payload,
+                    )),
+                },
+                status => futures::future::err(Error::NonSuccessResponse(status)),
+            })
+    }
+
+    pub fn write_parameter(
+        &self,
+        parameter: ParameterCode,
+        value: ParameterValue,
+    ) -> impl Future<Item = (), Error = Error> {
+        let seq = self.next_seq();
+        self.send_request(OutgoingMessage::new_write_parameter(seq, parameter, value))
+            .and_then(|response| match response.status {
+                StatusCode::Success => match response.payload {
+                    IncomingPayload::WriteParameter { .. } => futures::future::ok(()),
+                    payload => futures::future::err(Error::UnexpectedResponsePayload(
+                        "WriteParameter",
+                        payload,
+                    )),
+                },
+                status => futures::future::err(Error::NonSuccessResponse(status)),
+            })
+    }
+
+    pub fn device_state(&self) -> impl Future<Item = NetworkStateCode, Error = Error> {
+        let seq = self.next_seq();
+        self.send_request(OutgoingMessage::new_device_state(seq))
+            .and_then(|response| match response.status {
+                StatusCode::Success => match response.payload {
+                    IncomingPayload::DeviceState { state, .. } => futures::future::ok(state),
+                    payload => futures::future::err(Error::UnexpectedResponsePayload(
+                        "DeviceState",
+                        payload,
+                    )),
+                },
+                status => futures::future::err(Error::NonSuccessResponse(status)),
+            })
+    }
+
+    /// Sends a command the crate may not model, and returns the response
+    /// frame as received, whatever its status. `payload` follows the header
+    /// of the frame, including the payload length for the commands which have
+    /// one.
+    pub fn send_raw(
+        &self,
+        command: u8,
+        payload: &[u8],
+    ) -> impl Future<Item = Vec<u8>, Error = Error> {
+        let seq = self.next_seq();
+        self.send_request(OutgoingMessage::new_raw(seq, command, Vec::from(payload)))
+            .map(|response| response.frame)
+    }
+
+    /// Reads the version of the firmware, see `IncomingPayload::Version`.
+    pub fn firmware_version(&self) -> impl Future<Item = u32, Error = Error> {
+        let seq = self.next_seq();
+        self.send_request(OutgoingMessage::new_version(seq))
+            .and_then(|response| match response.status {
+                StatusCode::Success => match response.payload {
+                    IncomingPayload::Version { version } => futures::future::ok(version),
+                    payload => {
+                        futures::future::err(Error::UnexpectedResponsePayload("Version", payload))
+                    }
+                },
+                status => futures::future::err(Error::NonSuccessResponse(status)),
+            })
+    }
+
+    pub fn change_network_state(
+        &self,
+        state: NetworkStateCode,
+    ) -> impl Future<Item = (), Error = Error> {
+        let seq = self.next_seq();
+        self.send_request(OutgoingMessage::new_change_network_state(seq, state))
+            .and_then(|response| match response.status {
+                StatusCode::Success => match response.payload {
+                    IncomingPayload::ChangeNetworkState { .. } => futures::future::ok(()),
+                    payload => futures::future::err(Error::UnexpectedResponsePayload(
+                        "ChangeNetworkState",
+                        payload,
+                    )),
+                },
+                status => futures::future::err(Error::NonSuccessResponse(status)),
+            })
+    }
+
+    /// Reads a received APS frame. The client already reads indications when
+    /// the device signals them, and dispatches them to the pending ZCL requests
+    /// or to the notification stream.
+    pub fn aps_data_indication(&self) -> impl Future<Item = IncomingPayload, Error = Error> {
+        let seq = self.next_seq();
+        self.send_request(OutgoingMessage::new_aps_data_indication(seq))
+            .and_then(|response| match response.status {
+                StatusCode::Success => match response.payload {
+                    payload @ IncomingPayload::ApsDataIndication { .. } => {
+                        futures::future::ok(payload)
+                    }
+                    payload => futures::future::err(Error::UnexpectedResponsePayload(
+                        "ApsDataIndication",
+                        payload,
+                    )),
+                },
+                status => futures::future::err(Error::NonSuccessResponse(status)),
+            })
+    }
+
+    /// Enqueues an APS frame for sending, and returns the request id that the
+    /// matching `ApsDataConfirm` will carry.
+    pub fn aps_data_request(
+        &self,
+        destination: Address,
+        profile_id: u16,
+        cluster_id: u16,
+        source_endpoint: u8,
+        asdu: Vec<u8>,
+    ) -> impl Future<Item = u8, Error = Error> {
+        let seq = self.next_seq();
+        let request_id = next(&self.next_request_id);
+        self.send_request(OutgoingMessage::new_aps_data_request(
+            seq,
+            request_id,
+            destination,
+            profile_id,
+            cluster_id,
+            source_endpoint,
+            DEFAULT_RADIUS,
+            asdu,
+        ))
+        .and_then(|response| match response.status {
+            StatusCode::Success => match response.payload {
+                IncomingPayload::ApsDataRequest { request_id } => futures::future::ok(request_id),
+                payload => futures::future::err(Error::UnexpectedResponsePayload(
+                    "ApsDataRequest",
+                    payload,
+                )),
+            },
+            status => futures::future::err(Error::NonSuccessResponse(status)),
+        })
+    }
+
+    pub fn aps_data_confirm(&self) -> impl Future<Item = IncomingPayload, Error = Error> {
+        let seq = self.next_seq();
+        self.send_request(OutgoingMessage::new_aps_data_confirm(seq))
+            .and_then(|response| match response.status {
+                StatusCode::Success => match response.payload {
+                    payload @ IncomingPayload::ApsDataConfirm { .. } => {
+                        futures::future::ok(payload)
+                    }
+                    payload => futures::future::err(Error::UnexpectedResponsePayload(
+                        "ApsDataConfirm",
+                        payload,
+                    )),
+                },
+                status => futures::future::err(Error::NonSuccessResponse(status)),
+            })
+    }
+}
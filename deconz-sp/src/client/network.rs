@@ -0,0 +1,409 @@
+use futures::future::{self, Either};
+use futures::sync::oneshot::{channel, Sender};
+use futures::{stream, Future, Stream};
+use log::*;
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+
+use super::Client;
+use crate::call::{with_timeout, Call};
+use crate::install_code;
+use crate::network::{
+    channel_mask, plan, EnergyScan, NetworkConfig, ParameterChange, PermitJoinTarget,
+};
+use crate::protocol::constants::{NetworkStateCode, ParameterCode};
+use crate::protocol::types::ParameterValue;
+use crate::zdo::{NwkUpdate, BROADCAST_ROUTERS, BROADCAST_RX_ON_WHEN_IDLE};
+use crate::{Error, Event};
+
+/// How long the device may take to leave or form the network.
+const NETWORK_STATE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time given to the devices to switch channel, a bit more than the default
+/// nwkChannelSwitchDelay (500 ms).
+const CHANNEL_SWITCH_DELAY: Duration = Duration::from_secs(2);
+
+/// A task waiting for the network to reach a state.
+pub(super) struct NetworkStateWaiter {
+    state: NetworkStateCode,
+    sender: Sender<()>,
+}
+
+impl Client {
+    /// Wakes up the tasks waiting for the network state reported by the device.
+    pub(super) fn network_state_changed(&self, state: NetworkStateCode) {
+        let mut waiters = self
+            .network_state_waiters
+            .lock()
+            .expect("Cannot obtain lock on network state waiters");
+        let (reached, waiting) = waiters
+            .drain(..)
+            .partition::<Vec<_>, _>(|waiter| waiter.state == state);
+        *waiters = waiting;
+        for waiter in reached {
+            let _ = waiter.sender.send(());
+        }
+    }
+
+    /// Resolves when the device reports the network state, either now or in a
+    /// `DeviceStateChanged` notification.
+    pub fn wait_network_state(
+        &self,
+        state: NetworkStateCode,
+    ) -> impl Future<Item = (), Error = Error> {
+        let (sender, receiver) = channel();
+        {
+            let mut waiters = self
+                .network_state_waiters
+                .lock()
+                .expect("Cannot obtain lock on network state waiters");
+            waiters.retain(|waiter| !waiter.sender.is_canceled());
+            waiters.push(NetworkStateWaiter { state, sender });
+        }
+        let client = self.clone();
+        let wait = self.device_state().and_then(move |current| {
+            if current == state {
+                client.network_state_changed(current);
+            }
+            Call::new(receiver)
+        });
+        with_timeout(wait, NETWORK_STATE_TIMEOUT)
+    }
+
+    /// Takes the network offline, writes the configuration, then starts the
+    /// network and checks the device kept the written values.
+    pub fn form_network(&self, config: NetworkConfig) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.trust_center_address(&config)
+            .map(move |trust_center_address| config.parameters(trust_center_address))
+            .and_then(move |parameters| {
+                debug!("Writing network configuration");
+                client
+                    .restart_with_parameters(parameters.clone())
+                    .and_then(move |_| client.verify_parameters(parameters))
+            })
+    }
+
+    /// Compares the parameters of the device with the configuration, and returns
+    /// the differences. Unless `dry_run` is set, the parameters which differ are
+    /// written and the network is restarted.
+    pub fn reconcile(
+        &self,
+        config: NetworkConfig,
+        dry_run: bool,
+    ) -> impl Future<Item = Vec<ParameterChange>, Error = Error> {
+        let client = self.clone();
+        self.trust_center_address(&config)
+            .map(move |trust_center_address| config.parameters(trust_center_address))
+            .and_then({
+                let client = self.clone();
+                move |desired| {
+                    let codes: Vec<_> = desired.iter().map(|(parameter, _)| *parameter).collect();
+                    stream::iter_ok(codes)
+                        .and_then(move |parameter| {
+                            // Unreadable parameters are written
+                            client
+                                .read_parameter(parameter)
+                                .then(move |result| Ok(result.ok().map(|value| (parameter, value))))
+                        })
+                        .filter_map(|value| value)
+                        .collect()
+                        .map(move |current| plan(&desired, &current))
+                }
+            })
+            .and_then(move |changes| {
+                for change in &changes {
+                    info!("{}", change);
+                }
+                if dry_run || changes.is_empty() {
+                    return Either::A(future::ok(changes));
+                }
+                let parameters = changes
+                    .iter()
+                    .map(|change| (change.parameter, change.desired.clone()))
+                    .collect();
+                Either::B(client.restart_with_parameters(parameters).map(|_| changes))
+            })
+    }
+
+    /// Opens the network for joining for `duration` seconds (0 closes it, 0xff
+    /// leaves it open) through the target. `PermitJoinChanged` is emitted when
+    /// the window opens, and again when it closes.
+    pub fn permit_join(
+        &self,
+        duration: u8,
+        target: PermitJoinTarget,
+    ) -> impl Future<Item = (), Error = Error> {
+        let coordinator = match target {
+            PermitJoinTarget::All | PermitJoinTarget::Coordinator => Either::A(
+                self.write_parameter(ParameterCode::PermitJoin, ParameterValue::U8(duration)),
+            ),
+            PermitJoinTarget::Router(_) => Either::B(future::ok(())),
+        };
+        let routers = match target {
+            PermitJoinTarget::All => Some(BROADCAST_ROUTERS),
+            PermitJoinTarget::Router(nwk_address) => Some(nwk_address),
+            PermitJoinTarget::Coordinator => None,
+        };
+        let client = self.clone();
+        coordinator
+            .and_then({
+                let client = self.clone();
+                move |_| match routers {
+                    Some(nwk_address) => {
+                        Either::A(client.mgmt_permit_joining(nwk_address, duration, true))
+                    }
+                    None => Either::B(future::ok(())),
+                }
+            })
+            .map(move |_| client.permit_join_changed(duration, target))
+    }
+
+    /// Emits `PermitJoinChanged`, and schedules the one of the end of the window.
+    fn permit_join_changed(&self, duration: u8, target: PermitJoinTarget) {
+        let window = self
+            .permit_join_windows
+            .lock()
+            .expect("Cannot obtain lock on permit join windows")
+            .open(target);
+        self.emit(Event::PermitJoinChanged { target, duration });
+        if duration == 0 || duration == 0xff {
+            return;
+        }
+        let client = self.clone();
+        let close = Delay::new(Instant::now() + Duration::from_secs(u64::from(duration)))
+            .map_err(|err| error!("Permit join timer failed: {}", err))
+            .map(move |_| {
+                let current = client
+                    .permit_join_windows
+                    .lock()
+                    .expect("Cannot obtain lock on permit join windows")
+                    .is_current(target, window);
+                if current {
+                    client.emit(Event::PermitJoinChanged {
+                        target,
+                        duration: 0,
+                    });
+                }
+            });
+        tokio::spawn(close);
+    }
+
+    /// Gives the coordinator the link key derived from the install code of the
+    /// device, so the device can join. The install code includes its CRC.
+    pub fn add_install_code(
+        &self,
+        ieee_address: u64,
+        install_code: &[u8],
+    ) -> impl Future<Item = (), Error = Error> {
+        match install_code::link_key(install_code) {
+            Ok(key) => Either::A(self.set_link_key(ieee_address, key)),
+            Err(err) => Either::B(future::err(err)),
+        }
+    }
+
+    /// Writes the trust center link key of the device.
+    pub fn set_link_key(
+        &self,
+        ieee_address: u64,
+        key: [u8; 16],
+    ) -> impl Future<Item = (), Error = Error> {
+        self.write_parameter(
+            ParameterCode::LinkKey,
+            ParameterValue::LinkKey { ieee_address, key },
+        )
+    }
+
+    /// Asks each router of the registry to measure the energy on the channels,
+    /// during `duration` (exponent of the scan time, 0 to 5), and aggregates
+    /// the results.
+    pub fn energy_scan(
+        &self,
+        channels: &[u8],
+        duration: u8,
+    ) -> impl Future<Item = EnergyScan, Error = Error> {
+        let update = NwkUpdate::EnergyScan {
+            channels: channel_mask(channels),
+            duration,
+            count: 1,
+        };
+        let client = self.clone();
+        stream::iter_ok(self.routers()).fold(EnergyScan::default(), move |mut scan, nwk_address| {
+            client
+                .mgmt_nwk_update(nwk_address, update.clone())
+                .then(move |result| {
+                    match result {
+                        Ok(Some(notify)) => scan.add(&notify),
+                        Ok(None) => scan.failed.push(nwk_address),
+                        Err(err) => {
+                            debug!("Energy scan of {:#06x} failed: {}", nwk_address, err);
+                            scan.failed.push(nwk_address);
+                        }
+                    }
+                    Ok::<_, Error>(scan)
+                })
+        })
+    }
+
+    /// Moves the network to the channel: the change is broadcast to the
+    /// devices, then the coordinator is restarted on the channel with a new
+    /// network update ID. Returns the routers which do not answer on the new
+    /// channel, by IEEE address.
+    pub fn change_channel(&self, channel: u8) -> impl Future<Item = Vec<u64>, Error = Error> {
+        let client = self.clone();
+        self.read_parameter(ParameterCode::NwkUpdateId)
+            .and_then(|value| {
+                value
+                    .as_u64()
+                    .ok_or(Error::Decoding("Invalid NWK update ID"))
+            })
+            .and_then({
+                let client = self.clone();
+                move |nwk_update_id| {
+                    let nwk_update_id = (nwk_update_id as u8).wrapping_add(1);
+                    debug!(
+                        "Moving network to channel {} (update ID {})",
+                        channel, nwk_update_id
+                    );
+                    let update = NwkUpdate::ChannelChange {
+                        channels: channel_mask(&[channel]),
+                        nwk_update_id,
+                    };
+                    client
+                        .mgmt_nwk_update(BROADCAST_RX_ON_WHEN_IDLE, update)
+                        .map(move |_| nwk_update_id)
+                }
+            })
+            .and_then({
+                let client = self.clone();
+                move |nwk_update_id| {
+                    // Devices switch after nwkChannelSwitchDelay
+                    Delay::new(Instant::now() + CHANNEL_SWITCH_DELAY)
+                        .map_err(|_| Error::Internal("Timer unavailable"))
+                        .and_then(move |_| {
+                            client.restart_with_parameters(vec![
+                                (
+                                    ParameterCode::ChannelMask,
+                                    ParameterValue::U32(channel_mask(&[channel])),
+                                ),
+                                (
+                                    ParameterCode::NwkUpdateId,
+                                    ParameterValue::U8(nwk_update_id),
+                                ),
+                            ])
+                        })
+                }
+            })
+            .and_then({
+                let client = self.clone();
+                move |_| {
+                    client.verify_parameters(vec![(
+                        ParameterCode::CurrentChannel,
+                        ParameterValue::U8(channel),
+                    )])
+                }
+            })
+            .and_then(move |_| client.unreachable_routers())
+    }
+
+    /// Returns the routers of the registry which do not answer a
+    /// Node_Desc_req, by IEEE address.
+    fn unreachable_routers(&self) -> impl Future<Item = Vec<u64>, Error = Error> {
+        let client = self.clone();
+        stream::iter_ok(self.routers())
+            .and_then(move |nwk_address| {
+                client
+                    .node_descriptor(nwk_address)
+                    .then(move |result| Ok::<_, Error>((nwk_address, result.is_ok())))
+            })
+            .filter(|(_, reachable)| !reachable)
+            .map({
+                let client = self.clone();
+                move |(nwk_address, _)| {
+                    client.read_registry(|registry| registry.ieee_address(nwk_address))
+                }
+            })
+            .filter_map(|ieee_address| ieee_address)
+            .collect()
+    }
+
+    /// Returns the NWK address of the routers of the registry.
+    fn routers(&self) -> Vec<u16> {
+        self.read_registry(|registry| {
+            registry
+                .devices()
+                .filter(|device| device.is_router())
+                .map(|device| device.nwk_address)
+                .collect()
+        })
+    }
+
+    /// Takes the network offline, writes the parameters, and waits for the
+    /// network to be up again.
+    pub(super) fn restart_with_parameters(
+        &self,
+        parameters: Vec<(ParameterCode, ParameterValue)>,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.change_network_state(NetworkStateCode::Offline)
+            .and_then({
+                let client = client.clone();
+                move |_| client.wait_network_state(NetworkStateCode::Offline)
+            })
+            .and_then({
+                let client = client.clone();
+                move |_| client.write_parameters(parameters)
+            })
+            .and_then({
+                let client = client.clone();
+                move |_| client.change_network_state(NetworkStateCode::Connected)
+            })
+            .and_then(move |_| client.wait_network_state(NetworkStateCode::Connected))
+    }
+
+    /// Returns the trust center address of the configuration, or the MAC
+    /// address of the coordinator.
+    fn trust_center_address(
+        &self,
+        config: &NetworkConfig,
+    ) -> impl Future<Item = u64, Error = Error> {
+        match config.trust_center_address {
+            Some(address) => Either::A(future::ok(address)),
+            None => Either::B(self.mac_address()),
+        }
+    }
+
+    /// Reads the IEEE address of the coordinator.
+    pub(super) fn mac_address(&self) -> impl Future<Item = u64, Error = Error> {
+        self.read_parameter(ParameterCode::MacAddress)
+            .and_then(|value| value.as_u64().ok_or(Error::Decoding("Invalid MAC address")))
+    }
+
+    /// Writes the parameters one after the other, in order.
+    pub(super) fn write_parameters(
+        &self,
+        parameters: Vec<(ParameterCode, ParameterValue)>,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        stream::iter_ok(parameters)
+            .for_each(move |(parameter, value)| client.write_parameter(parameter, value))
+    }
+
+    /// Reads the parameters back, and fails on the first one that differs from
+    /// the expected value.
+    pub(super) fn verify_parameters(
+        &self,
+        parameters: Vec<(ParameterCode, ParameterValue)>,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        stream::iter_ok(parameters).for_each(move |(parameter, expected)| {
+            client.read_parameter(parameter).and_then(move |value| {
+                if value == expected {
+                    Ok(())
+                } else {
+                    Err(Error::ParameterMismatch(parameter))
+                }
+            })
+        })
+    }
+}
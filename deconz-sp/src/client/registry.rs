@@ -0,0 +1,329 @@
+use futures::future::{self, Either};
+use futures::Future;
+use log::*;
+use std::mem;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::timer::Delay;
+
+use super::{Client, ATTRIBUTE_SAVE_DELAY};
+use crate::protocol::types::Address;
+use crate::registry::{CachedAttribute, Device, DeviceRegistry, InterviewState};
+use crate::zcl::{
+    basic, AttributeReport, AttributeValue, GlobalCommand, ReadAttributeRecord, ZclFrame, ZclStatus,
+};
+use crate::zdo::{is_broadcast, AddressRequestType};
+use crate::{Error, Event};
+
+impl Client {
+    /// Returns the device with this IEEE address, if known.
+    pub fn device(&self, ieee_address: u64) -> Option<Device> {
+        self.read_registry(|registry| registry.get(ieee_address).cloned())
+    }
+
+    /// Returns the device currently reachable on this NWK address, if known.
+    pub fn device_by_nwk(&self, nwk_address: u16) -> Option<Device> {
+        self.read_registry(|registry| registry.get_by_nwk(nwk_address).cloned())
+    }
+
+    /// Returns all the known devices.
+    pub fn devices(&self) -> Vec<Device> {
+        self.read_registry(|registry| registry.devices().cloned().collect())
+    }
+
+    /// Returns the latest value read from, or reported by, the device for the
+    /// attribute.
+    pub fn get_cached(
+        &self,
+        ieee_address: u64,
+        endpoint: u8,
+        cluster_id: u16,
+        attribute_id: u16,
+    ) -> Option<CachedAttribute> {
+        self.read_registry(|registry| {
+            registry
+                .get(ieee_address)
+                .and_then(|device| device.attribute(endpoint, cluster_id, attribute_id))
+                .cloned()
+        })
+    }
+
+    /// Returns the cached value of the attribute, unless it is older than
+    /// `max_age`, in which case the attribute is read from the device.
+    pub fn read_attribute_if_older(
+        &self,
+        ieee_address: u64,
+        endpoint: u8,
+        cluster_id: u16,
+        attribute_id: u16,
+        max_age: Duration,
+    ) -> impl Future<Item = AttributeValue, Error = Error> {
+        let device = match self.device(ieee_address) {
+            Some(device) => device,
+            None => return Either::A(future::err(Error::UnknownDevice(ieee_address))),
+        };
+        if let Some(cached) = device.attribute(endpoint, cluster_id, attribute_id) {
+            if !cached.is_older_than(max_age, SystemTime::now()) {
+                return Either::A(future::ok(cached.value.clone()));
+            }
+        }
+        // The cache is updated when the response is dispatched
+        let read = self
+            .read_attributes(
+                Address::NWK(device.nwk_address, endpoint),
+                cluster_id,
+                &[attribute_id],
+            )
+            .and_then(move |records| {
+                match records
+                    .into_iter()
+                    .find(|record| record.attribute_id == attribute_id)
+                {
+                    Some(ReadAttributeRecord {
+                        value: Some(value), ..
+                    }) => Ok(value),
+                    Some(record) => Err(Error::ZclStatus(record.status)),
+                    None => Err(Error::ZclStatus(ZclStatus::UnsupportedAttribute)),
+                }
+            });
+        Either::B(read)
+    }
+
+    pub(super) fn read_registry<T>(&self, read: impl FnOnce(&DeviceRegistry) -> T) -> T {
+        read(
+            &self
+                .registry
+                .read()
+                .expect("Cannot obtain read-lock on registry"),
+        )
+    }
+
+    pub(super) fn write_registry<T>(&self, write: impl FnOnce(&mut DeviceRegistry) -> T) -> T {
+        write(
+            &mut self
+                .registry
+                .write()
+                .expect("Cannot obtain write-lock on registry"),
+        )
+    }
+
+    /// Records the NWK address of a device. The device which had the address
+    /// before left the network: it is forgotten, and `DeviceLeft` is emitted.
+    pub(super) fn update_address(&self, ieee_address: u64, nwk_address: u16) {
+        let (released, event) = self.write_registry(|registry| {
+            (
+                registry.release_address(ieee_address, nwk_address),
+                registry.update_address(ieee_address, nwk_address),
+            )
+        });
+        if let Some(device) = released {
+            self.forget(device.ieee_address);
+            self.emit(Event::DeviceLeft {
+                ieee: Some(device.ieee_address),
+                nwk: nwk_address,
+            });
+        }
+        if let Some(event) = event {
+            self.persist(ieee_address);
+            self.emit(event);
+        }
+    }
+
+    /// Applies the update to the registry, and emits `DeviceUpdated` if the
+    /// device changed.
+    pub(super) fn update_device(
+        &self,
+        ieee_address: u64,
+        update: impl FnOnce(&mut DeviceRegistry) -> bool,
+    ) {
+        if self.write_registry(update) {
+            self.persist(ieee_address);
+            self.emit(Event::DeviceUpdated { ieee: ieee_address });
+        }
+    }
+
+    /// Saves the device to the storage, if any.
+    pub(super) fn persist(&self, ieee_address: u64) {
+        if let (Some(storage), Some(device)) = (&self.storage, self.device(ieee_address)) {
+            let mut storage = storage.lock().expect("Cannot obtain lock on storage");
+            if let Err(err) = storage.save_device(&device) {
+                warn!("Cannot save device {:#018x}: {}", ieee_address, err);
+            }
+        }
+    }
+
+    /// Saves the device after `ATTRIBUTE_SAVE_DELAY`, together with the other
+    /// devices whose attributes changed meanwhile. Reports are frequent, so
+    /// the storage is not written for each of them, nor while reading the
+    /// serial port.
+    fn persist_later(&self, ieee_address: u64) {
+        if self.storage.is_none() {
+            return;
+        }
+        let scheduled = {
+            let mut unsaved = self
+                .unsaved_devices
+                .lock()
+                .expect("Cannot obtain lock on unsaved devices");
+            let scheduled = !unsaved.is_empty();
+            unsaved.insert(ieee_address);
+            scheduled
+        };
+        if scheduled {
+            return;
+        }
+        let client = self.clone();
+        let save = Delay::new(Instant::now() + ATTRIBUTE_SAVE_DELAY)
+            .map_err(|err| error!("Device save timer failed: {}", err))
+            .map(move |_| {
+                let unsaved = mem::take(
+                    &mut *client
+                        .unsaved_devices
+                        .lock()
+                        .expect("Cannot obtain lock on unsaved devices"),
+                );
+                for ieee_address in unsaved {
+                    client.persist(ieee_address);
+                }
+            });
+        tokio::spawn(save);
+    }
+
+    /// Removes the device from the storage, if any.
+    pub(super) fn forget(&self, ieee_address: u64) {
+        if let Some(storage) = &self.storage {
+            let mut storage = storage.lock().expect("Cannot obtain lock on storage");
+            if let Err(err) = storage.remove_device(ieee_address) {
+                warn!("Cannot remove device {:#018x}: {}", ieee_address, err);
+            }
+        }
+    }
+
+    pub(super) fn update_device_by_nwk(
+        &self,
+        nwk_address: u16,
+        update: impl FnOnce(&mut DeviceRegistry) -> bool,
+    ) {
+        if let Some(ieee_address) =
+            self.read_registry(|registry| registry.ieee_address(nwk_address))
+        {
+            self.update_device(ieee_address, update);
+        }
+    }
+
+    /// Records the reception of an indication from the source. The IEEE address
+    /// of unknown devices is looked up, so they get into the registry, unless
+    /// `lookup` is false, e.g. for a Device_annce which carries it.
+    pub(super) fn track_source(&self, source: &Address, lqi: u8, rssi: i8, lookup: bool) {
+        let nwk_address = match *source {
+            Address::NWK(nwk_address, _) => nwk_address,
+            Address::IEEE(ieee_address, _) => {
+                match self.read_registry(|registry| registry.nwk_address(ieee_address)) {
+                    Some(nwk_address) => nwk_address,
+                    None => return,
+                }
+            }
+            Address::Group(_) => return,
+        };
+        let (lookup, retry) = self.write_registry(|registry| {
+            if registry.seen(nwk_address, lqi, rssi, SystemTime::now()) {
+                // Sleepy devices are interviewed again when they wake up
+                let retry = registry
+                    .get_by_nwk(nwk_address)
+                    .filter(|device| device.interview == InterviewState::Failed)
+                    .map(|device| device.ieee_address);
+                (false, retry)
+            } else {
+                let lookup =
+                    lookup && !is_broadcast(nwk_address) && registry.start_lookup(nwk_address);
+                (lookup, None)
+            }
+        });
+        self.release_held_requests(nwk_address);
+        if let Some(ieee_address) = retry {
+            self.start_interview(ieee_address);
+        }
+        if lookup {
+            debug!("Looking up IEEE address of {:#06x}", nwk_address);
+            let client = self.clone();
+            // The response is recorded when dispatched, see `track_devices`
+            let lookup = self
+                .ieee_address(nwk_address, AddressRequestType::Single, 0)
+                .then(move |result| {
+                    if let Err(err) = result {
+                        debug!(
+                            "Cannot look up IEEE address of {:#06x}: {}",
+                            nwk_address, err
+                        );
+                    }
+                    client.write_registry(|registry| registry.end_lookup(nwk_address));
+                    Ok(())
+                });
+            tokio::spawn(lookup);
+        }
+    }
+
+    /// Records the attributes read from, or reported by, the device in the
+    /// attribute cache, and in the device identity for the Basic cluster.
+    pub(super) fn track_attributes(&self, source: &Address, cluster_id: u16, frame: &ZclFrame) {
+        let (nwk_address, endpoint) = match source {
+            Address::NWK(nwk_address, endpoint) => (*nwk_address, *endpoint),
+            _ => return,
+        };
+        let attributes: Vec<_> = match frame.global_command() {
+            Some(GlobalCommand::ReadAttributesResponse) => {
+                match ReadAttributeRecord::read_all(&frame.payload) {
+                    Ok(records) => records
+                        .into_iter()
+                        .filter_map(|record| {
+                            let attribute_id = record.attribute_id;
+                            record.value.map(|value| (attribute_id, value))
+                        })
+                        .collect(),
+                    Err(_) => return,
+                }
+            }
+            Some(GlobalCommand::ReportAttributes) => {
+                match AttributeReport::read_all(&frame.payload) {
+                    Ok(reports) => reports
+                        .into_iter()
+                        .map(|report| (report.attribute_id, report.value))
+                        .collect(),
+                    Err(_) => return,
+                }
+            }
+            _ => return,
+        };
+        let now = SystemTime::now();
+        let events: Vec<_> = self.write_registry(|registry| {
+            attributes
+                .iter()
+                .filter_map(|(attribute_id, value)| {
+                    registry.set_attribute(
+                        nwk_address,
+                        endpoint,
+                        cluster_id,
+                        *attribute_id,
+                        value.clone(),
+                        now,
+                    )
+                })
+                .collect()
+        });
+        if let Some(Event::AttributeChanged { ieee, .. }) = events.first() {
+            self.persist_later(*ieee);
+        }
+        for event in events {
+            self.emit(event);
+        }
+        if cluster_id != basic::CLUSTER_ID {
+            return;
+        }
+        self.update_device_by_nwk(nwk_address, |registry| {
+            attributes
+                .iter()
+                .fold(false, |changed, (attribute_id, value)| {
+                    registry.set_basic_attribute(nwk_address, *attribute_id, value) || changed
+                })
+        });
+    }
+}
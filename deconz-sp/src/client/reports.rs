@@ -0,0 +1,50 @@
+use log::*;
+
+use super::Client;
+use crate::protocol::types::Address;
+use crate::reports::{AttributeReports, Report};
+use crate::zcl::{AttributeReport, GlobalCommand, ZclFrame};
+
+impl Client {
+    /// Returns a stream of the attribute reports sent by the devices, which
+    /// can be narrowed with filters, e.g. `.cluster(0x0402)`. The stream keeps
+    /// `ClientOptions::event_capacity` reports.
+    pub fn attribute_reports(&self) -> AttributeReports {
+        AttributeReports::new(self.reports.receiver())
+    }
+
+    /// Hands the reports of a Report Attributes frame to the subscribers.
+    pub(super) fn publish_reports(&self, source: &Address, cluster_id: u16, frame: &ZclFrame) {
+        if frame.global_command() != Some(GlobalCommand::ReportAttributes) {
+            return;
+        }
+        let (ieee_address, endpoint) = match source {
+            Address::IEEE(ieee_address, endpoint) => (*ieee_address, *endpoint),
+            Address::NWK(nwk_address, endpoint) => match self.device_by_nwk(*nwk_address) {
+                Some(device) => (device.ieee_address, *endpoint),
+                None => {
+                    debug!("Report from unknown device {:#06x}", nwk_address);
+                    return;
+                }
+            },
+            Address::Group(_) => return,
+        };
+        let reports = match AttributeReport::read_all(&frame.payload) {
+            Ok(reports) => reports,
+            Err(err) => {
+                debug!("Invalid attribute report: {}", err);
+                return;
+            }
+        };
+        for report in reports {
+            let report = Report {
+                ieee_address,
+                endpoint,
+                cluster_id,
+                attribute_id: report.attribute_id,
+                value: report.value,
+            };
+            self.reports.publish(report);
+        }
+    }
+}
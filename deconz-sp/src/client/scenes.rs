@@ -0,0 +1,151 @@
+use futures::future::{self, Either};
+use futures::Future;
+
+use super::zcl::{expect_cluster_command, expect_default_response};
+use super::Client;
+use crate::protocol::types::Address;
+use crate::zcl::scenes::{self, Scene, SceneMembership, SceneResponse};
+use crate::zcl::{ZclFrame, ZclStatus, HA_PROFILE_ID};
+use crate::Error;
+
+impl Client {
+    /// Adds the scene to the endpoint, with the state given by its extension
+    /// field sets.
+    pub fn add_scene(
+        &self,
+        destination: Address,
+        scene: &Scene,
+    ) -> impl Future<Item = (), Error = Error> {
+        self.scenes_command(destination, scenes::add_scene(scene), scenes::ADD_SCENE)
+    }
+
+    /// Stores the current state of the endpoint in the scene.
+    pub fn store_scene(
+        &self,
+        destination: Address,
+        group_id: u16,
+        scene_id: u8,
+    ) -> impl Future<Item = (), Error = Error> {
+        self.scenes_command(
+            destination,
+            scenes::store_scene(group_id, scene_id),
+            scenes::STORE_SCENE,
+        )
+    }
+
+    /// Moves the endpoint to the state stored in the scene. Recalling a scene
+    /// through a group switches all its members at once.
+    pub fn recall_scene(
+        &self,
+        destination: Address,
+        group_id: u16,
+        scene_id: u8,
+    ) -> impl Future<Item = (), Error = Error> {
+        let frame = scenes::recall_scene(group_id, scene_id);
+        match destination {
+            Address::Group(_) => {
+                Either::A(self.zcl_send(destination, HA_PROFILE_ID, scenes::CLUSTER_ID, frame))
+            }
+            _ => Either::B(
+                self.zcl_request(destination, HA_PROFILE_ID, scenes::CLUSTER_ID, frame)
+                    .and_then(|frame| expect_default_response(frame, "RecallScene")),
+            ),
+        }
+    }
+
+    pub fn remove_scene(
+        &self,
+        destination: Address,
+        group_id: u16,
+        scene_id: u8,
+    ) -> impl Future<Item = (), Error = Error> {
+        self.scenes_command(
+            destination,
+            scenes::remove_scene(group_id, scene_id),
+            scenes::REMOVE_SCENE,
+        )
+    }
+
+    pub fn remove_all_scenes(
+        &self,
+        destination: Address,
+        group_id: u16,
+    ) -> impl Future<Item = (), Error = Error> {
+        self.scenes_command(
+            destination,
+            scenes::remove_all_scenes(group_id),
+            scenes::REMOVE_ALL_SCENES,
+        )
+    }
+
+    /// Returns the scene as stored by the endpoint. Needs a device destination.
+    pub fn view_scene(
+        &self,
+        destination: Address,
+        group_id: u16,
+        scene_id: u8,
+    ) -> impl Future<Item = Scene, Error = Error> {
+        self.scenes_request(
+            destination,
+            scenes::view_scene(group_id, scene_id),
+            scenes::VIEW_SCENE,
+        )
+        .and_then(|frame| scenes::read_view_scene_response(&frame.payload))
+        .and_then(|response| response.map_err(Error::ZclStatus))
+    }
+
+    /// Returns the scenes of the group stored by the endpoint. Needs a device
+    /// destination.
+    pub fn get_scene_membership(
+        &self,
+        destination: Address,
+        group_id: u16,
+    ) -> impl Future<Item = SceneMembership, Error = Error> {
+        self.scenes_request(
+            destination,
+            scenes::get_scene_membership(group_id),
+            scenes::GET_SCENE_MEMBERSHIP,
+        )
+        .and_then(|frame| SceneMembership::read(&frame.payload))
+    }
+
+    /// Sends a command answered with a status, group and scene. Groups do not
+    /// answer: the command completes once sent.
+    fn scenes_command(
+        &self,
+        destination: Address,
+        frame: ZclFrame,
+        command_id: u8,
+    ) -> impl Future<Item = (), Error = Error> {
+        match destination {
+            Address::Group(_) => {
+                Either::A(self.zcl_send(destination, HA_PROFILE_ID, scenes::CLUSTER_ID, frame))
+            }
+            _ => Either::B(
+                self.scenes_request(destination, frame, command_id)
+                    .and_then(|frame| SceneResponse::read(&frame.payload))
+                    .and_then(|response| match response.status {
+                        ZclStatus::Success => Ok(()),
+                        status => Err(Error::ZclStatus(status)),
+                    }),
+            ),
+        }
+    }
+
+    fn scenes_request(
+        &self,
+        destination: Address,
+        frame: ZclFrame,
+        command_id: u8,
+    ) -> impl Future<Item = ZclFrame, Error = Error> {
+        if let Address::Group(_) = destination {
+            return Either::A(future::err(Error::Encoding(
+                "Scenes request needs a device destination",
+            )));
+        }
+        let request = self
+            .zcl_request(destination, HA_PROFILE_ID, scenes::CLUSTER_ID, frame)
+            .and_then(move |frame| expect_cluster_command(frame, command_id, "Scenes"));
+        Either::B(request)
+    }
+}
@@ -0,0 +1,111 @@
+use futures::future::{self, Either};
+use futures::sync::oneshot::{channel, Sender};
+use futures::Future;
+use log::*;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use super::Client;
+use crate::call::{with_timeout, Call};
+use crate::protocol::types::{Address, SourceAddress};
+use crate::{Error, Event};
+
+/// A sleepy device heard from this recently is still awake, e.g. while it is
+/// interviewed after joining.
+const AWAKE_TIME: Duration = Duration::from_secs(5);
+
+/// Requests waiting for sleepy devices to wake up, by IEEE address. They fail
+/// with `Error::Disconnected` if the link goes down.
+pub(super) type HeldRequests = HashMap<u64, Vec<Sender<Result<(), Error>>>>;
+
+impl Client {
+    /// Completes once the destination can receive a request: at once, unless
+    /// it is a sleepy device not heard from lately. The request is then held
+    /// until the device polls or sends a frame, for at most
+    /// `ClientOptions::sleepy_hold_time`.
+    pub(super) fn when_awake(
+        &self,
+        destination: &Address,
+    ) -> impl Future<Item = (), Error = Error> {
+        let hold_time = match self.sleepy_hold_time {
+            Some(hold_time) => hold_time,
+            None => return Either::A(future::ok(())),
+        };
+        let now = SystemTime::now();
+        let asleep = self.read_registry(|registry| {
+            let device = match *destination {
+                Address::NWK(nwk_address, _) => registry.get_by_nwk(nwk_address),
+                Address::IEEE(ieee_address, _) => registry.get(ieee_address),
+                Address::Group(_) => None,
+            }?;
+            let awake = device
+                .last_seen
+                .and_then(|seen| now.duration_since(seen).ok())
+                .map(|elapsed| elapsed < AWAKE_TIME)
+                .unwrap_or(false);
+            if device.is_sleepy() && !awake {
+                Some(device.ieee_address)
+            } else {
+                None
+            }
+        });
+        let ieee_address = match asleep {
+            Some(ieee_address) => ieee_address,
+            None => return Either::A(future::ok(())),
+        };
+        debug!("Request held until {:#018x} wakes up", ieee_address);
+        let (sender, receiver) = channel();
+        {
+            let mut held_requests = self
+                .held_requests
+                .lock()
+                .expect("Cannot obtain lock on held requests");
+            let requests = held_requests.entry(ieee_address).or_default();
+            requests.retain(|request| !request.is_canceled());
+            requests.push(sender);
+        }
+        Either::B(with_timeout(
+            Call::new(receiver).and_then(|released| released),
+            hold_time,
+        ))
+    }
+
+    /// Sends the requests held for the device, which is awake.
+    pub(super) fn release_held_requests(&self, nwk_address: u16) {
+        let ieee_address = match self.read_registry(|registry| registry.ieee_address(nwk_address)) {
+            Some(ieee_address) => ieee_address,
+            None => return,
+        };
+        let requests = self
+            .held_requests
+            .lock()
+            .expect("Cannot obtain lock on held requests")
+            .remove(&ieee_address);
+        if let Some(requests) = requests {
+            debug!(
+                "Sending {} requests held for {:#018x}",
+                requests.len(),
+                ieee_address
+            );
+            for request in requests {
+                let _ = request.send(Ok(()));
+            }
+        }
+    }
+
+    pub(super) fn mac_poll(&self, address: SourceAddress, lqi: u8, rssi: i8) {
+        let nwk_address = match address {
+            SourceAddress::NWK(nwk_address) => Some(nwk_address),
+            SourceAddress::IEEE(ieee_address) => {
+                self.read_registry(|registry| registry.nwk_address(ieee_address))
+            }
+        };
+        if let Some(nwk_address) = nwk_address {
+            self.write_registry(|registry| {
+                registry.seen(nwk_address, lqi, rssi, SystemTime::now())
+            });
+            self.release_held_requests(nwk_address);
+        }
+        self.emit(Event::MacPoll { address, lqi, rssi });
+    }
+}
@@ -0,0 +1,113 @@
+use super::*;
+
+fn registry() -> DeviceRegistry {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    registry
+}
+
+#[test]
+fn response_from_destination() {
+    let registry = registry();
+    let nwk = Address::NWK(0x1234, 1);
+    let ieee = Address::IEEE(0x0011_2233_4455_6677, 1);
+    assert!(is_from(&nwk, &Address::NWK(0x1234, 1), &registry));
+    assert!(!is_from(&nwk, &Address::NWK(0x5678, 1), &registry));
+    assert!(is_from(&ieee, &ieee, &registry));
+    // Requests to IEEE addresses are answered from the NWK address
+    assert!(is_from(&ieee, &Address::NWK(0x1234, 1), &registry));
+    assert!(!is_from(&ieee, &Address::NWK(0x5678, 1), &registry));
+    assert!(is_from(&nwk, &ieee, &registry));
+    assert!(!is_from(
+        &Address::IEEE(0x0011_2233_4455_0000, 1),
+        &Address::NWK(0x1234, 1),
+        &registry
+    ));
+}
+
+#[test]
+fn response_to_broadcast_or_group() {
+    let registry = registry();
+    assert!(is_from(
+        &Address::NWK(0xfffd, 0),
+        &Address::NWK(0x5678, 0),
+        &registry
+    ));
+    assert!(is_from(
+        &Address::Group(0x0001),
+        &Address::NWK(0x5678, 1),
+        &registry
+    ));
+}
+
+#[test]
+fn allocate_group_table_entries() {
+    let mut table = GroupTable {
+        size: 2,
+        groups: vec![(0x0001, 1)].into_iter().collect(),
+    };
+    // Entries written before the client started are kept
+    assert_eq!(table.allocate(0x0001).unwrap(), None);
+    assert_eq!(table.allocate(0x0002).unwrap(), Some(0));
+    match table.allocate(0x0003) {
+        Err(Error::GroupTableFull) => {}
+        other => panic!("Unexpected entry: {:?}", other),
+    }
+    table.groups.remove(&0x0001);
+    assert_eq!(table.allocate(0x0003).unwrap(), Some(1));
+}
+
+#[test]
+fn fail_requests_on_link_down() {
+    let subscriptions = RwLock::new(BTreeMap::new());
+    let zcl_subscriptions = RwLock::new(Vec::new());
+    let zdo_subscriptions = RwLock::new(Vec::new());
+    let held_requests = Mutex::new(HeldRequests::new());
+    let (sender, request) = channel();
+    subscriptions.write().unwrap().insert((1, 0x0a), sender);
+    let (sender, zcl_request) = channel();
+    zcl_subscriptions.write().unwrap().push(ZclSubscription {
+        destination: Address::NWK(0x1234, 1),
+        cluster_id: 0x0006,
+        transaction_sequence: 1,
+        sender,
+    });
+    let (sender, zdo_request) = channel();
+    zdo_subscriptions.write().unwrap().push(ZdoSubscription {
+        destination: Address::NWK(0x1234, 0),
+        cluster_id: 0x8005,
+        transaction_sequence: 1,
+        sender,
+    });
+    let (sender, held_request) = channel();
+    held_requests
+        .lock()
+        .unwrap()
+        .insert(0x0011_2233_4455_6677, vec![sender]);
+    link::fail_pending_requests(
+        &subscriptions,
+        &zcl_subscriptions,
+        &zdo_subscriptions,
+        &held_requests,
+    );
+    assert!(subscriptions.read().unwrap().is_empty());
+    assert!(zcl_subscriptions.read().unwrap().is_empty());
+    assert!(zdo_subscriptions.read().unwrap().is_empty());
+    assert!(held_requests.lock().unwrap().is_empty());
+    match request.wait() {
+        Ok(Err(Error::Disconnected)) => {}
+        other => panic!("Unexpected response: {:?}", other),
+    }
+    match zcl_request.wait() {
+        Ok(Err(Error::Disconnected)) => {}
+        other => panic!("Unexpected response: {:?}", other),
+    }
+    match zdo_request.wait() {
+        Ok(Err(Error::Disconnected)) => {}
+        other => panic!("Unexpected response: {:?}", other),
+    }
+    match held_request.wait() {
+        Ok(Err(Error::Disconnected)) => {}
+        other => panic!("Unexpected response: {:?}", other),
+    }
+}
@@ -0,0 +1,76 @@
+use futures::future::{loop_fn, Either, Loop};
+use futures::Future;
+use log::*;
+use std::collections::VecDeque;
+
+use super::Client;
+use crate::topology::Topology;
+use crate::zdo::TablePage;
+use crate::Error;
+
+impl Client {
+    /// Maps the network: starting from the coordinator, reads the neighbor and
+    /// routing tables of each router found.
+    pub fn topology(&self) -> impl Future<Item = Topology, Error = Error> {
+        let client = self.clone();
+        let pending: VecDeque<u16> = vec![0x0000].into_iter().collect();
+        loop_fn(
+            (Topology::new(), pending),
+            move |(mut topology, mut pending)| {
+                let nwk_address = match pending.pop_front() {
+                    Some(nwk_address) => nwk_address,
+                    None => return Either::A(futures::future::ok(Loop::Break(topology))),
+                };
+                debug!("Reading neighbor table of {:#06x}", nwk_address);
+                let neighbors = {
+                    let client = client.clone();
+                    read_table(move |start_index| client.mgmt_lqi(nwk_address, start_index))
+                };
+                let routes = {
+                    let client = client.clone();
+                    read_table(move |start_index| client.mgmt_rtg(nwk_address, start_index))
+                };
+                let crawl = neighbors.then(move |result| match result {
+                    Ok(neighbors) => {
+                        pending.extend(topology.add_neighbors(nwk_address, &neighbors));
+                        // Not all routers implement Mgmt_Rtg_req
+                        Either::A(routes.then(move |result| {
+                            match result {
+                                Ok(routes) => topology.add_routes(nwk_address, &routes),
+                                Err(err) => {
+                                    debug!("Cannot read routes of {:#06x}: {}", nwk_address, err)
+                                }
+                            }
+                            Ok(Loop::Continue((topology, pending)))
+                        }))
+                    }
+                    Err(err) => {
+                        debug!("Cannot read neighbors of {:#06x}: {}", nwk_address, err);
+                        topology.failed.push(nwk_address);
+                        Either::B(futures::future::ok(Loop::Continue((topology, pending))))
+                    }
+                });
+                Either::B(crawl)
+            },
+        )
+    }
+}
+
+/// Reads all the pages of a remote table.
+pub(super) fn read_table<T, F, R>(read_page: F) -> impl Future<Item = Vec<T>, Error = Error>
+where
+    F: Fn(u8) -> R,
+    R: Future<Item = TablePage<T>, Error = Error>,
+{
+    loop_fn(Vec::new(), move |mut entries: Vec<T>| {
+        read_page(entries.len() as u8).map(move |page| {
+            let more = page.has_more();
+            entries.extend(page.entries);
+            if more {
+                Loop::Continue(entries)
+            } else {
+                Loop::Break(entries)
+            }
+        })
+    })
+}
@@ -0,0 +1,398 @@
+use futures::sync::oneshot::{channel, Sender};
+use futures::Future;
+use log::*;
+
+use super::{is_from, Client, CLIENT_ENDPOINT, RESPONSE_TIMEOUT};
+use crate::call::{with_timeout, Call};
+use crate::protocol::types::Address;
+use crate::registry::DeviceRegistry;
+use crate::zcl::*;
+use crate::Error;
+
+/// A ZCL request waiting for its response, matched on the transaction
+/// sequence number, the cluster and the source of the response.
+pub(super) struct ZclSubscription {
+    pub(super) destination: Address,
+    pub(super) cluster_id: u16,
+    pub(super) transaction_sequence: u8,
+    /// Fails with `Error::Disconnected` if the link goes down.
+    pub(super) sender: Sender<Result<ZclFrame, Error>>,
+}
+
+impl ZclSubscription {
+    fn matches(
+        &self,
+        source: &Address,
+        cluster_id: u16,
+        header: &ZclHeader,
+        registry: &DeviceRegistry,
+    ) -> bool {
+        is_from(&self.destination, source, registry)
+            && self.cluster_id == cluster_id
+            && self.transaction_sequence == header.transaction_sequence
+            && header.direction == Direction::ServerToClient
+    }
+}
+
+fn expect_command(
+    frame: ZclFrame,
+    expected: GlobalCommand,
+    name: &'static str,
+) -> Result<ZclFrame, Error> {
+    match frame.global_command() {
+        Some(command) if command == expected => Ok(frame),
+        Some(GlobalCommand::DefaultResponse) => {
+            let response = DefaultResponse::read(&frame.payload)?;
+            Err(Error::ZclStatus(response.status))
+        }
+        _ => Err(Error::UnexpectedZclResponse(name, frame.header.command_id)),
+    }
+}
+
+/// Checks the frame is the cluster specific response to a request, or a
+/// default response reporting an error.
+pub(super) fn expect_cluster_command(
+    frame: ZclFrame,
+    command_id: u8,
+    name: &'static str,
+) -> Result<ZclFrame, Error> {
+    match frame.header.frame_type {
+        FrameType::ClusterSpecific if frame.header.command_id == command_id => Ok(frame),
+        _ => expect_default_response(frame, name).and_then(|_| {
+            Err(Error::UnexpectedZclResponse(
+                name,
+                GlobalCommand::DefaultResponse.code(),
+            ))
+        }),
+    }
+}
+
+/// Checks the frame is a successful default response.
+pub(super) fn expect_default_response(frame: ZclFrame, name: &'static str) -> Result<(), Error> {
+    let frame = expect_command(frame, GlobalCommand::DefaultResponse, name)?;
+    match DefaultResponse::read(&frame.payload)?.status {
+        ZclStatus::Success => Ok(()),
+        status => Err(Error::ZclStatus(status)),
+    }
+}
+
+impl Client {
+    /// Hands a received ZCL frame to the request waiting for it. Returns `false`
+    /// if the frame is not a response to a pending request.
+    pub(super) fn dispatch_zcl(&self, source: Address, cluster_id: u16, asdu: &[u8]) -> bool {
+        let frame = match ZclFrame::read(asdu) {
+            Ok(frame) => frame,
+            Err(err) => {
+                debug!("Indication is not a valid ZCL frame: {}", err);
+                return false;
+            }
+        };
+        self.track_attributes(&source, cluster_id, &frame);
+        self.publish_reports(&source, cluster_id, &frame);
+        let registry = self
+            .registry
+            .read()
+            .expect("Cannot obtain read-lock on registry");
+        let mut subscriptions = self
+            .zcl_subscriptions
+            .write()
+            .expect("Cannot obtain write-lock on ZCL subscriptions");
+        match subscriptions.iter().position(|subscription| {
+            subscription.matches(&source, cluster_id, &frame.header, &registry)
+        }) {
+            Some(index) => {
+                trace!("ZCL subscription exists!");
+                if subscriptions.remove(index).sender.send(Ok(frame)).is_err() {
+                    debug!("ZCL response received after the request was abandoned");
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sends a ZCL frame from the coordinator endpoint, and waits for the
+    /// response carrying the same transaction sequence number. Requests to a
+    /// sleepy device are held until it wakes up.
+    pub fn zcl_request(
+        &self,
+        destination: Address,
+        profile_id: u16,
+        cluster_id: u16,
+        mut frame: ZclFrame,
+    ) -> impl Future<Item = ZclFrame, Error = Error> {
+        let transaction_sequence = self.next_transaction_sequence();
+        frame.header.transaction_sequence = transaction_sequence;
+        let (sender, receiver) = channel();
+        {
+            let mut subscriptions = self
+                .zcl_subscriptions
+                .write()
+                .expect("Cannot obtain write-lock on ZCL subscriptions");
+            subscriptions.retain(|subscription| !subscription.sender.is_canceled());
+            subscriptions.push(ZclSubscription {
+                destination,
+                cluster_id,
+                transaction_sequence,
+                sender,
+            });
+        }
+        let mut asdu = Vec::new();
+        frame.write(&mut asdu);
+        let client = self.clone();
+        self.when_awake(&destination).and_then(move |_| {
+            let request = client
+                .aps_data_request(destination, profile_id, cluster_id, CLIENT_ENDPOINT, asdu)
+                .and_then(|_| Call::new(receiver))
+                .and_then(|response| response);
+            with_timeout(request, RESPONSE_TIMEOUT)
+        })
+    }
+
+    /// Sends a ZCL frame from the coordinator endpoint without waiting for a
+    /// response, e.g. to a group, whose members do not answer.
+    pub fn zcl_send(
+        &self,
+        destination: Address,
+        profile_id: u16,
+        cluster_id: u16,
+        mut frame: ZclFrame,
+    ) -> impl Future<Item = (), Error = Error> {
+        frame.header.transaction_sequence = self.next_transaction_sequence();
+        frame.header.disable_default_response = true;
+        let mut asdu = Vec::new();
+        frame.write(&mut asdu);
+        let client = self.clone();
+        self.when_awake(&destination).and_then(move |_| {
+            client
+                .aps_data_request(destination, profile_id, cluster_id, CLIENT_ENDPOINT, asdu)
+                .map(|_| ())
+        })
+    }
+
+    fn global_request(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        command: GlobalCommand,
+        payload: Vec<u8>,
+    ) -> impl Future<Item = ZclFrame, Error = Error> {
+        self.zcl_request(
+            destination,
+            HA_PROFILE_ID,
+            cluster_id,
+            ZclFrame::new_global(command, payload),
+        )
+    }
+
+    pub fn read_attributes(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        attributes: &[u16],
+    ) -> impl Future<Item = Vec<ReadAttributeRecord>, Error = Error> {
+        let mut payload = Vec::new();
+        write_read_attributes(attributes, &mut payload);
+        self.global_request(
+            destination,
+            cluster_id,
+            GlobalCommand::ReadAttributes,
+            payload,
+        )
+        .and_then(|frame| {
+            let frame = expect_command(
+                frame,
+                GlobalCommand::ReadAttributesResponse,
+                "ReadAttributesResponse",
+            )?;
+            ReadAttributeRecord::read_all(&frame.payload)
+        })
+    }
+
+    pub fn write_attributes(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        records: &[WriteAttributeRecord],
+    ) -> impl Future<Item = Vec<WriteAttributeStatus>, Error = Error> {
+        self.write_attributes_with(
+            destination,
+            cluster_id,
+            GlobalCommand::WriteAttributes,
+            records,
+        )
+    }
+
+    /// Writes all the attributes, or none of them if any write fails.
+    pub fn write_attributes_undivided(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        records: &[WriteAttributeRecord],
+    ) -> impl Future<Item = Vec<WriteAttributeStatus>, Error = Error> {
+        self.write_attributes_with(
+            destination,
+            cluster_id,
+            GlobalCommand::WriteAttributesUndivided,
+            records,
+        )
+    }
+
+    fn write_attributes_with(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        command: GlobalCommand,
+        records: &[WriteAttributeRecord],
+    ) -> impl Future<Item = Vec<WriteAttributeStatus>, Error = Error> {
+        let mut payload = Vec::new();
+        for record in records {
+            record.write(&mut payload);
+        }
+        self.global_request(destination, cluster_id, command, payload)
+            .and_then(|frame| {
+                let frame = expect_command(
+                    frame,
+                    GlobalCommand::WriteAttributesResponse,
+                    "WriteAttributesResponse",
+                )?;
+                WriteAttributeStatus::read_all(&frame.payload)
+            })
+    }
+
+    pub fn configure_reporting(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        configurations: &[ReportingConfiguration],
+    ) -> impl Future<Item = Vec<ConfigureReportingStatus>, Error = Error> {
+        let mut payload = Vec::new();
+        for configuration in configurations {
+            configuration.write(&mut payload);
+        }
+        self.global_request(
+            destination,
+            cluster_id,
+            GlobalCommand::ConfigureReporting,
+            payload,
+        )
+        .and_then(|frame| {
+            let frame = expect_command(
+                frame,
+                GlobalCommand::ConfigureReportingResponse,
+                "ConfigureReportingResponse",
+            )?;
+            ConfigureReportingStatus::read_all(&frame.payload)
+        })
+    }
+
+    pub fn read_reporting_configuration(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        attributes: &[(ReportingDirection, u16)],
+    ) -> impl Future<Item = Vec<ReportingConfigurationRecord>, Error = Error> {
+        let mut payload = Vec::new();
+        write_read_reporting_configuration(attributes, &mut payload);
+        self.global_request(
+            destination,
+            cluster_id,
+            GlobalCommand::ReadReportingConfiguration,
+            payload,
+        )
+        .and_then(|frame| {
+            let frame = expect_command(
+                frame,
+                GlobalCommand::ReadReportingConfigurationResponse,
+                "ReadReportingConfigurationResponse",
+            )?;
+            ReportingConfigurationRecord::read_all(&frame.payload)
+        })
+    }
+
+    pub fn discover_attributes(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        start_attribute_id: u16,
+        max_attributes: u8,
+    ) -> impl Future<Item = DiscoverAttributesResponse, Error = Error> {
+        let mut payload = Vec::new();
+        write_discover_attributes(start_attribute_id, max_attributes, &mut payload);
+        self.global_request(
+            destination,
+            cluster_id,
+            GlobalCommand::DiscoverAttributes,
+            payload,
+        )
+        .and_then(|frame| {
+            let frame = expect_command(
+                frame,
+                GlobalCommand::DiscoverAttributesResponse,
+                "DiscoverAttributesResponse",
+            )?;
+            DiscoverAttributesResponse::read(&frame.payload)
+        })
+    }
+
+    /// Lists the cluster-specific commands the device accepts.
+    pub fn discover_commands_received(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        start_command_id: u8,
+        max_commands: u8,
+    ) -> impl Future<Item = DiscoverCommandsResponse, Error = Error> {
+        self.discover_commands(
+            destination,
+            cluster_id,
+            GlobalCommand::DiscoverCommandsReceived,
+            start_command_id,
+            max_commands,
+        )
+    }
+
+    /// Lists the cluster-specific commands the device may send.
+    pub fn discover_commands_generated(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        start_command_id: u8,
+        max_commands: u8,
+    ) -> impl Future<Item = DiscoverCommandsResponse, Error = Error> {
+        self.discover_commands(
+            destination,
+            cluster_id,
+            GlobalCommand::DiscoverCommandsGenerated,
+            start_command_id,
+            max_commands,
+        )
+    }
+
+    fn discover_commands(
+        &self,
+        destination: Address,
+        cluster_id: u16,
+        command: GlobalCommand,
+        start_command_id: u8,
+        max_commands: u8,
+    ) -> impl Future<Item = DiscoverCommandsResponse, Error = Error> {
+        let (expected, name) = match command {
+            GlobalCommand::DiscoverCommandsReceived => (
+                GlobalCommand::DiscoverCommandsReceivedResponse,
+                "DiscoverCommandsReceivedResponse",
+            ),
+            _ => (
+                GlobalCommand::DiscoverCommandsGeneratedResponse,
+                "DiscoverCommandsGeneratedResponse",
+            ),
+        };
+        let mut payload = Vec::new();
+        write_discover_commands(start_command_id, max_commands, &mut payload);
+        self.global_request(destination, cluster_id, command, payload)
+            .and_then(move |frame| {
+                let frame = expect_command(frame, expected, name)?;
+                DiscoverCommandsResponse::read(&frame.payload)
+            })
+    }
+}
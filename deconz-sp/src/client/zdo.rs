@@ -0,0 +1,445 @@
+use futures::future::Either;
+use futures::sync::oneshot::{channel, Sender};
+use futures::Future;
+use log::*;
+
+use super::{is_from, Client, RESPONSE_TIMEOUT};
+use crate::call::{with_timeout, Call};
+use crate::protocol::types::Address;
+use crate::zdo::*;
+use crate::{Error, Event};
+
+/// A ZDO request waiting for its response, matched on the transaction
+/// sequence number, the response cluster and the source of the response.
+pub(super) struct ZdoSubscription {
+    pub(super) destination: Address,
+    pub(super) cluster_id: u16,
+    pub(super) transaction_sequence: u8,
+    /// Fails with `Error::Disconnected` if the link goes down.
+    pub(super) sender: Sender<Result<ZdoFrame, Error>>,
+}
+
+impl Client {
+    /// Hands a received ZDO frame to the request waiting for it. Returns `false`
+    /// if the frame is not a response to a pending request.
+    pub(super) fn dispatch_zdo(&self, source: Address, cluster_id: u16, asdu: &[u8]) -> bool {
+        let frame = match ZdoFrame::read(asdu) {
+            Ok(frame) => frame,
+            Err(err) => {
+                debug!("Indication is not a valid ZDO frame: {}", err);
+                return false;
+            }
+        };
+        let announced = self.track_devices(&source, cluster_id, &frame);
+        let registry = self
+            .registry
+            .read()
+            .expect("Cannot obtain read-lock on registry");
+        let mut subscriptions = self
+            .zdo_subscriptions
+            .write()
+            .expect("Cannot obtain write-lock on ZDO subscriptions");
+        match subscriptions.iter().position(|subscription| {
+            is_from(&subscription.destination, &source, &registry)
+                && subscription.cluster_id == cluster_id
+                && subscription.transaction_sequence == frame.transaction_sequence
+        }) {
+            Some(index) => {
+                trace!("ZDO subscription exists!");
+                if subscriptions.remove(index).sender.send(Ok(frame)).is_err() {
+                    debug!("ZDO response received after the request was abandoned");
+                }
+                true
+            }
+            None => announced,
+        }
+    }
+
+    /// Emits the events related to device addresses: Device_annce, address
+    /// responses and leave responses. Returns true for Device_annce, whose
+    /// indication is replaced by the `DeviceJoined` event.
+    fn track_devices(&self, source: &Address, cluster_id: u16, frame: &ZdoFrame) -> bool {
+        let response = cluster_id & RESPONSE_CLUSTER_FLAG != 0;
+        match (cluster_id & !RESPONSE_CLUSTER_FLAG, response) {
+            (DEVICE_ANNOUNCE, false) => match DeviceAnnounce::read(&frame.payload) {
+                Ok(announce) => {
+                    self.update_address(announce.ieee_address, announce.nwk_address);
+                    self.update_device(announce.ieee_address, |registry| {
+                        registry.set_capabilities(announce.ieee_address, announce.capabilities)
+                    });
+                    self.emit(Event::DeviceJoined {
+                        ieee: announce.ieee_address,
+                        nwk: announce.nwk_address,
+                        capabilities: announce.capabilities,
+                    });
+                    self.start_interview(announce.ieee_address);
+                    true
+                }
+                Err(err) => {
+                    debug!("Invalid Device_annce: {}", err);
+                    false
+                }
+            },
+            (NWK_ADDRESS_REQUEST, true) | (IEEE_ADDRESS_REQUEST, true) => {
+                if let Ok(response) = AddressResponse::read(&frame.payload) {
+                    self.update_address(response.ieee_address, response.nwk_address);
+                }
+                false
+            }
+            (NODE_DESCRIPTOR_REQUEST, true) => {
+                if let Ok((nwk, descriptor)) = NodeDescriptor::read_response(&frame.payload) {
+                    self.update_device_by_nwk(nwk, |registry| {
+                        registry.set_node_descriptor(nwk, descriptor)
+                    });
+                }
+                false
+            }
+            (SIMPLE_DESCRIPTOR_REQUEST, true) => {
+                if let Ok((nwk, descriptor)) = SimpleDescriptor::read_response(&frame.payload) {
+                    self.update_device_by_nwk(nwk, |registry| {
+                        registry.set_simple_descriptor(nwk, descriptor)
+                    });
+                }
+                false
+            }
+            (MGMT_LEAVE_REQUEST, true) => {
+                let target = self
+                    .pending_leaves
+                    .lock()
+                    .expect("Cannot obtain lock on pending leaves")
+                    .remove(&frame.transaction_sequence);
+                if read_status_response(&frame.payload).is_ok() {
+                    match (target, source) {
+                        // The responder may be the parent of the device
+                        (Some(ieee), _) if ieee != 0 => self.device_left(ieee),
+                        // The device itself leaves, on our request or another's
+                        (_, Address::IEEE(ieee, _)) => self.device_left(*ieee),
+                        (_, Address::NWK(nwk, _)) => {
+                            let ieee = self
+                                .write_registry(|registry| registry.remove_by_nwk(*nwk))
+                                .map(|device| device.ieee_address);
+                            if let Some(ieee) = ieee {
+                                self.forget(ieee);
+                            }
+                            self.emit(Event::DeviceLeft { ieee, nwk: *nwk });
+                        }
+                        (_, Address::Group(_)) => (),
+                    }
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes a device which left the network, and emits `DeviceLeft`.
+    fn device_left(&self, ieee_address: u64) {
+        match self.write_registry(|registry| registry.remove(ieee_address)) {
+            Some(device) => {
+                self.forget(ieee_address);
+                self.emit(Event::DeviceLeft {
+                    ieee: Some(ieee_address),
+                    nwk: device.nwk_address,
+                });
+            }
+            None => debug!("Unknown device {:#018x} left", ieee_address),
+        }
+    }
+
+    fn zdo_frame(&self, request: &ZdoRequest) -> (u8, Vec<u8>) {
+        let transaction_sequence = self.next_transaction_sequence();
+        if let ZdoRequest::MgmtLeave { ieee_address, .. } = request {
+            self.pending_leaves
+                .lock()
+                .expect("Cannot obtain lock on pending leaves")
+                .insert(transaction_sequence, *ieee_address);
+        }
+        let mut payload = Vec::new();
+        request.write(&mut payload);
+        let mut asdu = Vec::new();
+        ZdoFrame {
+            transaction_sequence,
+            payload,
+        }
+        .write(&mut asdu);
+        (transaction_sequence, asdu)
+    }
+
+    /// Sends a ZDO request to the device, and waits for the response on the
+    /// matching response cluster (request cluster | 0x8000).
+    pub fn zdo_request(
+        &self,
+        nwk_address: u16,
+        request: ZdoRequest,
+    ) -> impl Future<Item = ZdoFrame, Error = Error> {
+        let destination = Address::NWK(nwk_address, ZDO_ENDPOINT);
+        let cluster_id = request.cluster_id();
+        let (transaction_sequence, asdu) = self.zdo_frame(&request);
+        let (sender, receiver) = channel();
+        {
+            let mut subscriptions = self
+                .zdo_subscriptions
+                .write()
+                .expect("Cannot obtain write-lock on ZDO subscriptions");
+            subscriptions.retain(|subscription| !subscription.sender.is_canceled());
+            subscriptions.push(ZdoSubscription {
+                destination,
+                cluster_id: cluster_id | RESPONSE_CLUSTER_FLAG,
+                transaction_sequence,
+                sender,
+            });
+        }
+        let request = self
+            .aps_data_request(destination, ZDO_PROFILE_ID, cluster_id, ZDO_ENDPOINT, asdu)
+            .and_then(|_| Call::new(receiver))
+            .and_then(|response| response);
+        with_timeout(request, RESPONSE_TIMEOUT)
+    }
+
+    /// Sends a ZDO request without waiting for any response, e.g. broadcasts.
+    pub fn zdo_send(
+        &self,
+        nwk_address: u16,
+        request: ZdoRequest,
+    ) -> impl Future<Item = (), Error = Error> {
+        let cluster_id = request.cluster_id();
+        let (_, asdu) = self.zdo_frame(&request);
+        self.aps_data_request(
+            Address::NWK(nwk_address, ZDO_ENDPOINT),
+            ZDO_PROFILE_ID,
+            cluster_id,
+            ZDO_ENDPOINT,
+            asdu,
+        )
+        .map(|_| ())
+    }
+
+    /// Looks up the NWK address of a device by broadcasting a NWK_addr_req.
+    pub fn nwk_address(
+        &self,
+        ieee_address: u64,
+        request_type: AddressRequestType,
+        start_index: u8,
+    ) -> impl Future<Item = AddressResponse, Error = Error> {
+        self.zdo_request(
+            BROADCAST_RX_ON_WHEN_IDLE,
+            ZdoRequest::NwkAddress {
+                ieee_address,
+                request_type,
+                start_index,
+            },
+        )
+        .and_then(|frame| AddressResponse::read(&frame.payload))
+    }
+
+    pub fn ieee_address(
+        &self,
+        nwk_address: u16,
+        request_type: AddressRequestType,
+        start_index: u8,
+    ) -> impl Future<Item = AddressResponse, Error = Error> {
+        self.zdo_request(
+            nwk_address,
+            ZdoRequest::IeeeAddress {
+                nwk_address,
+                request_type,
+                start_index,
+            },
+        )
+        .and_then(|frame| AddressResponse::read(&frame.payload))
+    }
+
+    pub fn node_descriptor(
+        &self,
+        nwk_address: u16,
+    ) -> impl Future<Item = NodeDescriptor, Error = Error> {
+        self.zdo_request(nwk_address, ZdoRequest::NodeDescriptor { nwk_address })
+            .and_then(|frame| NodeDescriptor::read_response(&frame.payload))
+            .map(|(_, descriptor)| descriptor)
+    }
+
+    pub fn power_descriptor(
+        &self,
+        nwk_address: u16,
+    ) -> impl Future<Item = PowerDescriptor, Error = Error> {
+        self.zdo_request(nwk_address, ZdoRequest::PowerDescriptor { nwk_address })
+            .and_then(|frame| PowerDescriptor::read_response(&frame.payload))
+            .map(|(_, descriptor)| descriptor)
+    }
+
+    pub fn simple_descriptor(
+        &self,
+        nwk_address: u16,
+        endpoint: u8,
+    ) -> impl Future<Item = SimpleDescriptor, Error = Error> {
+        self.zdo_request(
+            nwk_address,
+            ZdoRequest::SimpleDescriptor {
+                nwk_address,
+                endpoint,
+            },
+        )
+        .and_then(|frame| SimpleDescriptor::read_response(&frame.payload))
+        .map(|(_, descriptor)| descriptor)
+    }
+
+    pub fn active_endpoints(&self, nwk_address: u16) -> impl Future<Item = Vec<u8>, Error = Error> {
+        self.zdo_request(nwk_address, ZdoRequest::ActiveEndpoints { nwk_address })
+            .and_then(|frame| read_endpoints_response(&frame.payload))
+            .map(|(_, endpoints)| endpoints)
+    }
+
+    /// Returns the endpoints of the device matching the profile and one of the
+    /// clusters.
+    pub fn match_descriptor(
+        &self,
+        nwk_address: u16,
+        profile_id: u16,
+        input_clusters: Vec<u16>,
+        output_clusters: Vec<u16>,
+    ) -> impl Future<Item = Vec<u8>, Error = Error> {
+        self.zdo_request(
+            nwk_address,
+            ZdoRequest::MatchDescriptor {
+                nwk_address,
+                profile_id,
+                input_clusters,
+                output_clusters,
+            },
+        )
+        .and_then(|frame| read_endpoints_response(&frame.payload))
+        .map(|(_, endpoints)| endpoints)
+    }
+
+    /// Broadcasts a Device_annce, which is not answered.
+    pub fn device_announce(
+        &self,
+        announce: DeviceAnnounce,
+    ) -> impl Future<Item = (), Error = Error> {
+        self.zdo_send(
+            BROADCAST_RX_ON_WHEN_IDLE,
+            ZdoRequest::DeviceAnnounce(announce),
+        )
+    }
+
+    /// Creates a binding on the source device of the entry.
+    pub fn bind(
+        &self,
+        nwk_address: u16,
+        entry: BindingEntry,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.zdo_request(nwk_address, ZdoRequest::Bind(entry))
+            .and_then(|frame| read_status_response(&frame.payload))
+            .map(move |_| {
+                client.update_device(entry.source_address, |registry| registry.add_binding(entry))
+            })
+    }
+
+    /// Removes a binding from the source device of the entry.
+    pub fn unbind(
+        &self,
+        nwk_address: u16,
+        entry: BindingEntry,
+    ) -> impl Future<Item = (), Error = Error> {
+        let client = self.clone();
+        self.zdo_request(nwk_address, ZdoRequest::Unbind(entry))
+            .and_then(|frame| read_status_response(&frame.payload))
+            .map(move |_| {
+                client.update_device(entry.source_address, |registry| {
+                    registry.remove_binding(&entry)
+                })
+            })
+    }
+
+    /// Reads a page of the neighbor table of a router.
+    pub fn mgmt_lqi(
+        &self,
+        nwk_address: u16,
+        start_index: u8,
+    ) -> impl Future<Item = TablePage<Neighbor>, Error = Error> {
+        self.zdo_request(nwk_address, ZdoRequest::MgmtLqi { start_index })
+            .and_then(|frame| TablePage::<Neighbor>::read(&frame.payload))
+    }
+
+    /// Reads a page of the routing table of a router.
+    pub fn mgmt_rtg(
+        &self,
+        nwk_address: u16,
+        start_index: u8,
+    ) -> impl Future<Item = TablePage<Route>, Error = Error> {
+        self.zdo_request(nwk_address, ZdoRequest::MgmtRtg { start_index })
+            .and_then(|frame| TablePage::<Route>::read(&frame.payload))
+    }
+
+    /// Reads a page of the binding table of a device.
+    pub fn mgmt_bind(
+        &self,
+        nwk_address: u16,
+        start_index: u8,
+    ) -> impl Future<Item = TablePage<BindingEntry>, Error = Error> {
+        self.zdo_request(nwk_address, ZdoRequest::MgmtBind { start_index })
+            .and_then(|frame| TablePage::<BindingEntry>::read(&frame.payload))
+    }
+
+    /// Asks the device to leave the network, or to make one of its children
+    /// leave.
+    pub fn mgmt_leave(
+        &self,
+        nwk_address: u16,
+        ieee_address: u64,
+        remove_children: bool,
+        rejoin: bool,
+    ) -> impl Future<Item = (), Error = Error> {
+        self.zdo_request(
+            nwk_address,
+            ZdoRequest::MgmtLeave {
+                ieee_address,
+                remove_children,
+                rejoin,
+            },
+        )
+        .and_then(|frame| read_status_response(&frame.payload))
+    }
+
+    /// Opens the network for joining through the device for `duration` seconds
+    /// (0 closes it, 0xff leaves it open). Broadcast requests are not answered.
+    pub fn mgmt_permit_joining(
+        &self,
+        nwk_address: u16,
+        duration: u8,
+        trust_center_significance: bool,
+    ) -> impl Future<Item = (), Error = Error> {
+        let request = ZdoRequest::MgmtPermitJoining {
+            duration,
+            trust_center_significance,
+        };
+        if is_broadcast(nwk_address) {
+            Either::A(self.zdo_send(nwk_address, request))
+        } else {
+            Either::B(
+                self.zdo_request(nwk_address, request)
+                    .and_then(|frame| read_status_response(&frame.payload)),
+            )
+        }
+    }
+
+    /// Sends a Mgmt_NWK_Update_req. Only energy scans sent to a single device
+    /// are answered, with the energy measured on each channel.
+    pub fn mgmt_nwk_update(
+        &self,
+        nwk_address: u16,
+        update: NwkUpdate,
+    ) -> impl Future<Item = Option<NwkUpdateNotify>, Error = Error> {
+        let request = ZdoRequest::MgmtNwkUpdate(update);
+        if is_broadcast(nwk_address) || !request.has_response() {
+            Either::A(self.zdo_send(nwk_address, request).map(|_| None))
+        } else {
+            Either::B(
+                self.zdo_request(nwk_address, request)
+                    .and_then(|frame| NwkUpdateNotify::read(&frame.payload))
+                    .map(Some),
+            )
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Discovery of the serial adapters on Linux, through `/sys/class/tty` and
+//! `/dev/serial/by-id`.
+
+use futures::future::{self, Either};
+use futures::{Future, Sink, Stream};
+use log::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::call::with_timeout;
+use crate::protocol::{IncomingPayload, OutgoingMessage};
+use crate::serial::{self, DEFAULT_BAUD_RATE};
+use crate::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// USB identifiers of the ConBee II.
+const DRESDEN_ELEKTRONIK_VENDOR_ID: u16 = 0x1cf1;
+const CONBEE_II_PRODUCT_ID: u16 = 0x0030;
+/// USB identifiers of the FTDI FT230X bridge of the ConBee.
+const FTDI_VENDOR_ID: u16 = 0x0403;
+const FT230X_PRODUCT_ID: u16 = 0x6015;
+
+const CONBEE_II_BAUD_RATE: u32 = 115_200;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AdapterKind {
+    ConBeeII,
+    ConBee,
+    RaspBee,
+    /// ttyACM or ttyUSB device which may be an adapter.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adapter {
+    /// Path of the device in `/dev`.
+    pub path: PathBuf,
+    /// Stable path of the device in `/dev/serial/by-id`, if any.
+    pub by_id: Option<PathBuf>,
+    pub kind: AdapterKind,
+    pub baud_rate: u32,
+}
+
+/// USB attributes of a tty device.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct UsbInfo {
+    vendor_id: Option<u16>,
+    product_id: Option<u16>,
+    product: Option<String>,
+}
+
+/// Returns the kind of adapter behind the tty device, or `None` if it cannot
+/// be one.
+fn classify(name: &str, usb: &UsbInfo, by_id: Option<&str>) -> Option<AdapterKind> {
+    let named = |pattern: &str| {
+        usb.product
+            .iter()
+            .map(String::as_str)
+            .chain(by_id)
+            .any(|name| name.to_lowercase().contains(pattern))
+    };
+    match (usb.vendor_id, usb.product_id) {
+        (Some(DRESDEN_ELEKTRONIK_VENDOR_ID), Some(CONBEE_II_PRODUCT_ID)) => {
+            return Some(AdapterKind::ConBeeII)
+        }
+        (Some(FTDI_VENDOR_ID), Some(FT230X_PRODUCT_ID)) => return Some(AdapterKind::ConBee),
+        _ => (),
+    }
+    if named("conbee ii") || named("conbee_ii") {
+        Some(AdapterKind::ConBeeII)
+    } else if named("conbee") {
+        Some(AdapterKind::ConBee)
+    } else if named("raspbee") || name.starts_with("ttyAMA") {
+        Some(AdapterKind::RaspBee)
+    } else if name.starts_with("ttyACM") || name.starts_with("ttyUSB") {
+        Some(AdapterKind::Unknown)
+    } else {
+        None
+    }
+}
+
+/// The ConBee II, a CDC ACM device, talks faster than the adapters behind a
+/// UART.
+fn baud_rate(name: &str, kind: AdapterKind) -> u32 {
+    match kind {
+        AdapterKind::ConBeeII => CONBEE_II_BAUD_RATE,
+        AdapterKind::Unknown if name.starts_with("ttyACM") => CONBEE_II_BAUD_RATE,
+        _ => DEFAULT_BAUD_RATE,
+    }
+}
+
+/// Lists the candidate adapters, most likely first.
+pub fn discover() -> Result<Vec<Adapter>, Error> {
+    discover_in(Path::new("/"))
+}
+
+/// Lists the candidate adapters of the system mounted under `root`.
+pub fn discover_in(root: &Path) -> Result<Vec<Adapter>, Error> {
+    let by_id = serial_by_id(root);
+    let mut adapters = Vec::new();
+    let entries = match fs::read_dir(root.join("sys/class/tty")) {
+        Ok(entries) => entries,
+        Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(adapters),
+        Err(err) => return Err(err.into()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        // Virtual terminals have no device
+        let device = match fs::canonicalize(entry.path().join("device")) {
+            Ok(device) => device,
+            Err(_) => continue,
+        };
+        let usb = usb_info(root, &device);
+        let link = by_id
+            .iter()
+            .find(|(target, _)| *target == name)
+            .map(|(_, link)| link);
+        let link_name = link
+            .and_then(|link| link.file_name())
+            .and_then(|name| name.to_str());
+        if let Some(kind) = classify(&name, &usb, link_name) {
+            debug!("Found {:?} adapter at {}", kind, name);
+            adapters.push(Adapter {
+                path: root.join("dev").join(&name),
+                by_id: link.cloned(),
+                kind,
+                baud_rate: baud_rate(&name, kind),
+            });
+        }
+    }
+    adapters.sort_by(|a, b| (a.kind, &a.path).cmp(&(b.kind, &b.path)));
+    Ok(adapters)
+}
+
+/// Returns the names of the devices in `/dev` and their by-id links.
+fn serial_by_id(root: &Path) -> Vec<(String, PathBuf)> {
+    let entries = match fs::read_dir(root.join("dev/serial/by-id")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let target = fs::read_link(entry.path()).ok()?;
+            let name = target.file_name()?.to_string_lossy().into_owned();
+            Some((name, entry.path()))
+        })
+        .collect()
+}
+
+/// Reads the USB attributes of the closest USB device above the tty device.
+fn usb_info(root: &Path, device: &Path) -> UsbInfo {
+    let read = |dir: &Path, name: &str| {
+        fs::read_to_string(dir.join(name))
+            .ok()
+            .map(|value| value.trim().to_owned())
+    };
+    let id = |dir: &Path, name: &str| {
+        read(dir, name).and_then(|value| u16::from_str_radix(&value, 16).ok())
+    };
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_owned());
+    device
+        .ancestors()
+        .take_while(|dir| dir.starts_with(&root) && *dir != root)
+        .find(|dir| dir.join("idVendor").is_file())
+        .map(|dir| UsbInfo {
+            vendor_id: id(dir, "idVendor"),
+            product_id: id(dir, "idProduct"),
+            product: read(dir, "product"),
+        })
+        .unwrap_or_default()
+}
+
+/// Opens the adapter and returns its firmware version.
+pub fn probe(adapter: &Adapter) -> impl Future<Item = u32, Error = Error> {
+    let frames = match serial::open(&adapter.path, adapter.baud_rate) {
+        Ok(frames) => frames,
+        Err(err) => return Either::A(future::err(err)),
+    };
+    let version = frames
+        .send(OutgoingMessage::new_version(0))
+        .and_then(|frames| {
+            frames
+                .filter_map(|message| match message.payload {
+                    IncomingPayload::Version { version } => Some(version),
+                    _ => None,
+                })
+                .into_future()
+                .map_err(|(err, _)| err)
+        })
+        .and_then(|(version, _)| version.ok_or(Error::Disconnected));
+    Either::B(with_timeout(version, PROBE_TIMEOUT))
+}
+
+/// Probes the adapters one after the other, and returns those which answer
+/// with their firmware version.
+pub fn probe_all(adapters: Vec<Adapter>) -> impl Future<Item = Vec<(Adapter, u32)>, Error = Error> {
+    futures::stream::iter_ok(adapters)
+        .and_then(|adapter| {
+            probe(&adapter).then(move |result| match result {
+                Ok(version) => Ok(Some((adapter, version))),
+                Err(err) => {
+                    debug!("No answer from {}: {}", adapter.path.display(), err);
+                    Ok(None)
+                }
+            })
+        })
+        .filter_map(|adapter| adapter)
+        .collect()
+}
@@ -0,0 +1,122 @@
+use super::*;
+use std::env;
+use std::os::unix::fs::symlink;
+use std::process;
+
+fn usb(vendor_id: u16, product_id: u16, product: &str) -> UsbInfo {
+    UsbInfo {
+        vendor_id: Some(vendor_id),
+        product_id: Some(product_id),
+        product: Some(String::from(product)),
+    }
+}
+
+#[test]
+fn classify_usb_ids() {
+    assert_eq!(
+        classify("ttyACM0", &usb(0x1cf1, 0x0030, "ConBee II"), None),
+        Some(AdapterKind::ConBeeII)
+    );
+    assert_eq!(
+        classify("ttyUSB0", &usb(0x0403, 0x6015, "FT230X Basic UART"), None),
+        Some(AdapterKind::ConBee)
+    );
+}
+
+#[test]
+fn classify_names() {
+    let by_id = "usb-dresden_elektronik_ingenieurtechnik_GmbH_ConBee_II_DE1234-if00";
+    assert_eq!(
+        classify("ttyACM1", &UsbInfo::default(), Some(by_id)),
+        Some(AdapterKind::ConBeeII)
+    );
+    assert_eq!(
+        classify("ttyAMA0", &UsbInfo::default(), None),
+        Some(AdapterKind::RaspBee)
+    );
+    assert_eq!(
+        classify("ttyUSB1", &usb(0x10c4, 0xea60, "CP2102"), None),
+        Some(AdapterKind::Unknown)
+    );
+    assert_eq!(classify("ttyS0", &UsbInfo::default(), None), None);
+}
+
+#[test]
+fn suggested_baud_rates() {
+    assert_eq!(baud_rate("ttyACM0", AdapterKind::ConBeeII), 115_200);
+    assert_eq!(baud_rate("ttyUSB0", AdapterKind::ConBee), 38400);
+    assert_eq!(baud_rate("ttyAMA0", AdapterKind::RaspBee), 38400);
+    assert_eq!(baud_rate("ttyACM1", AdapterKind::Unknown), 115_200);
+    assert_eq!(baud_rate("ttyUSB1", AdapterKind::Unknown), 38400);
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+/// Adds a tty device below the USB device, as in sysfs.
+fn add_tty(root: &Path, name: &str, usb_device: &str) {
+    let device = root
+        .join("sys/devices/pci0000:00/usb1")
+        .join(usb_device)
+        .join(format!("{}:1.0", usb_device))
+        .join("tty")
+        .join(name);
+    fs::create_dir_all(&device).unwrap();
+    let class = root.join("sys/class/tty").join(name);
+    fs::create_dir_all(&class).unwrap();
+    symlink(&device, class.join("device")).unwrap();
+}
+
+#[test]
+fn discover_fake_tree() {
+    let root = env::temp_dir().join(format!("deconz-sp-discover-{}", process::id()));
+    let usb = root.join("sys/devices/pci0000:00/usb1");
+    write(&usb.join("1-1/idVendor"), "1cf1\n");
+    write(&usb.join("1-1/idProduct"), "0030\n");
+    write(&usb.join("1-1/product"), "ConBee II\n");
+    add_tty(&root, "ttyACM0", "1-1");
+    write(&usb.join("1-2/idVendor"), "0403\n");
+    write(&usb.join("1-2/idProduct"), "6015\n");
+    add_tty(&root, "ttyUSB0", "1-2");
+    // Serial port and virtual terminal
+    fs::create_dir_all(root.join("sys/devices/platform/serial8250/tty/ttyS0")).unwrap();
+    fs::create_dir_all(root.join("sys/class/tty/ttyS0")).unwrap();
+    symlink(
+        root.join("sys/devices/platform/serial8250/tty/ttyS0"),
+        root.join("sys/class/tty/ttyS0/device"),
+    )
+    .unwrap();
+    fs::create_dir_all(root.join("sys/class/tty/tty0")).unwrap();
+    let by_id = root.join("dev/serial/by-id");
+    fs::create_dir_all(&by_id).unwrap();
+    let conbee_ii = by_id.join("usb-dresden_elektronik_ingenieurtechnik_GmbH_ConBee_II-if00");
+    symlink("../../ttyACM0", &conbee_ii).unwrap();
+
+    let adapters = discover_in(&root).unwrap();
+    assert_eq!(
+        adapters,
+        vec![
+            Adapter {
+                path: root.join("dev/ttyACM0"),
+                by_id: Some(conbee_ii),
+                kind: AdapterKind::ConBeeII,
+                baud_rate: 115_200,
+            },
+            Adapter {
+                path: root.join("dev/ttyUSB0"),
+                by_id: None,
+                kind: AdapterKind::ConBee,
+                baud_rate: 38400,
+            },
+        ]
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn discover_without_sysfs() {
+    let root = env::temp_dir().join(format!("deconz-sp-no-sysfs-{}", process::id()));
+    assert!(discover_in(&root).unwrap().is_empty());
+}
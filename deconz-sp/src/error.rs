@@ -1,8 +1,12 @@
+#![allow(non_local_definitions)] // emitted by the failure derive
+
 use failure::Fail;
 use std::convert::From;
 
-use crate::protocol::constants::StatusCode;
+use crate::protocol::constants::{ParameterCode, StatusCode};
 use crate::protocol::IncomingPayload;
+use crate::zcl::ZclStatus;
+use crate::zdo::ZdoStatus;
 
 #[derive(Fail, Debug)]
 pub enum Error {
@@ -23,6 +27,43 @@ pub enum Error {
         _0, _1
     )]
     UnexpectedResponsePayload(&'static str, IncomingPayload),
+    #[fail(display = "The link to the device is down")]
+    Disconnected,
+    #[fail(display = "No response received before timeout")]
+    Timeout,
+    #[fail(display = "Remote device returns ZCL status: {:?}", _0)]
+    ZclStatus(ZclStatus),
+    #[fail(
+        display = "The ZCL response was not expected: expected: {} received command: {:#x}",
+        _0, _1
+    )]
+    UnexpectedZclResponse(&'static str, u8),
+    #[fail(display = "Remote device returns ZDO status: {:?}", _0)]
+    ZdoStatus(ZdoStatus),
+    #[fail(display = "Unknown device: {:#018x}", _0)]
+    UnknownDevice(u64),
+    #[fail(display = "Parameter {:?} differs from the written value", _0)]
+    ParameterMismatch(ParameterCode),
+    #[fail(display = "Invalid install code: {}", _0)]
+    InvalidInstallCode(&'static str),
+    #[fail(display = "The group table of the coordinator is full")]
+    GroupTableFull,
+    #[fail(display = "Invalid firmware file: {}", _0)]
+    InvalidFirmware(&'static str),
+    #[fail(display = "Bootloader returns status: {:#04x}", _0)]
+    BootloaderStatus(u8),
+    #[fail(
+        display = "Firmware CRC mismatch: expected: {:#010x} written: {:#010x}",
+        _0, _1
+    )]
+    FirmwareCrcMismatch(u32, u32),
+    #[fail(display = "JSON error")]
+    Json(#[fail(cause)] serde_json::Error),
+    #[fail(display = "TOML error")]
+    Toml(#[fail(cause)] toml::de::Error),
+    #[cfg(feature = "sqlite")]
+    #[fail(display = "SQLite error")]
+    Sqlite(#[fail(cause)] rusqlite::Error),
 }
 
 impl From<std::io::Error> for Error {
@@ -31,6 +72,25 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(json: serde_json::Error) -> Self {
+        Error::Json(json)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(toml: toml::de::Error) -> Self {
+        Error::Toml(toml)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(sqlite: rusqlite::Error) -> Self {
+        Error::Sqlite(sqlite)
+    }
+}
+
 impl From<serial_line_ip::Error> for Error {
     fn from(slip: serial_line_ip::Error) -> Self {
         Error::SLIP(slip)
@@ -0,0 +1,79 @@
+use crate::network::PermitJoinTarget;
+use crate::protocol::types::SourceAddress;
+use crate::protocol::IncomingPayload;
+use crate::zcl::AttributeValue;
+use crate::zdo::MacCapabilities;
+
+/// Items of the event streams returned by `Client::new` and
+/// `Client::subscribe`.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// Unsolicited message from the device, not handled by the client.
+    Notification(IncomingPayload),
+    /// A device joined, or rejoined, the network and sent a Device_annce.
+    DeviceJoined {
+        ieee: u64,
+        nwk: u16,
+        capabilities: MacCapabilities,
+    },
+    /// A device left the network: a Mgmt_Leave_rsp from the device or from its
+    /// parent confirmed it, or its NWK address was given to another device.
+    /// Devices which leave on their own send no Mgmt_Leave_rsp, and the serial
+    /// protocol does not report their NWK leave command: they are only
+    /// reported once their address is reused. The IEEE address is only known
+    /// if the device was seen before.
+    DeviceLeft { ieee: Option<u64>, nwk: u16 },
+    /// A device was added to the registry.
+    DeviceAdded { ieee: u64, nwk: u16 },
+    /// The descriptors, capabilities or identity of a device changed in the
+    /// registry.
+    DeviceUpdated { ieee: u64 },
+    /// The descriptors and the Basic cluster attributes of the device were
+    /// read after it joined.
+    InterviewCompleted { ieee: u64 },
+    /// The interview stopped on an error. It is retried when the device is heard
+    /// from again.
+    InterviewFailed { ieee: u64, reason: String },
+    /// A known device is now reachable on another NWK address.
+    NwkAddressChanged {
+        ieee: u64,
+        old_nwk: u16,
+        new_nwk: u16,
+    },
+    /// The network was opened for joining through the target for `duration`
+    /// seconds, or closed when `duration` is 0. A duration of 0xff leaves the
+    /// network open until it is closed explicitly.
+    PermitJoinChanged {
+        target: PermitJoinTarget,
+        duration: u8,
+    },
+    /// An attribute read from, or reported by, a device has a new value.
+    /// Reports repeating the cached value are not signaled.
+    AttributeChanged {
+        ieee: u64,
+        endpoint: u8,
+        cluster_id: u16,
+        attribute_id: u16,
+        value: AttributeValue,
+    },
+    /// A sleepy end device polled the coordinator, its parent. The requests
+    /// held for it are sent.
+    MacPoll {
+        address: SourceAddress,
+        lqi: u8,
+        rssi: i8,
+    },
+    /// The subscriber did not keep up, and missed this many events.
+    Lagged { missed: u64 },
+    /// The serial link reported an error.
+    LinkError { reason: String },
+    /// The serial device disappeared. Pending requests failed with
+    /// `Error::Disconnected`, and the device is reopened unless
+    /// `ClientOptions::reconnect` is disabled.
+    LinkDown,
+    /// The serial device was reopened after the link went down.
+    LinkUp,
+    /// The serial link is closed for good, as reconnection is disabled. It is
+    /// the last event of the streams.
+    LinkClosed,
+}
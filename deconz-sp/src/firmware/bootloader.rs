@@ -0,0 +1,195 @@
+//! Frames of the bootloader protocol. They start with `0x81` and the
+//! command, and are SLIP encoded with the checksum of the serial protocol.
+//!
+//! Once the update is requested, the bootloader asks for the image chunk by
+//! chunk, then reports the CRC-32 of what it wrote.
+
+use bytes::{BufMut, BytesMut};
+use log::*;
+use serial_line_ip::{Decoder as SLIPDecoder, Encoder as SLIPEncoder};
+use tokio::codec::{Decoder, Encoder};
+
+use crate::protocol::compute_crc;
+use crate::reader::Reader;
+use crate::zcl::write_uint;
+use crate::Error;
+
+const MAGIC: u8 = 0x81;
+
+const ID_REQUEST: u8 = 0x02;
+const ID_RESPONSE: u8 = 0x82;
+const UPDATE_REQUEST: u8 = 0x03;
+const UPDATE_RESPONSE: u8 = 0x83;
+const DATA_REQUEST: u8 = 0x04;
+const DATA_RESPONSE: u8 = 0x84;
+const UPDATE_DONE: u8 = 0x85;
+
+pub const STATUS_SUCCESS: u8 = 0x00;
+pub const STATUS_INVALID_OFFSET: u8 = 0x01;
+
+/// Frames sent to the bootloader.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostFrame {
+    IdRequest,
+    UpdateRequest {
+        size: u32,
+        target_address: u32,
+        file_type: u8,
+        crc32: u32,
+    },
+    DataResponse {
+        status: u8,
+        offset: u32,
+        data: Vec<u8>,
+    },
+}
+
+impl HostFrame {
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.push(MAGIC);
+        match self {
+            HostFrame::IdRequest => out.push(ID_REQUEST),
+            HostFrame::UpdateRequest {
+                size,
+                target_address,
+                file_type,
+                crc32,
+            } => {
+                out.push(UPDATE_REQUEST);
+                write_uint(out, *size as u64, 4);
+                write_uint(out, *target_address as u64, 4);
+                out.push(*file_type);
+                write_uint(out, *crc32 as u64, 4);
+            }
+            HostFrame::DataResponse {
+                status,
+                offset,
+                data,
+            } => {
+                out.push(DATA_RESPONSE);
+                out.push(*status);
+                write_uint(out, *offset as u64, 4);
+                write_uint(out, data.len() as u64, 2);
+                out.extend_from_slice(data);
+            }
+        }
+    }
+}
+
+/// Frames sent by the bootloader.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BootloaderFrame {
+    IdResponse {
+        version: u32,
+        /// CRC-32 of the application currently flashed.
+        app_crc32: u32,
+    },
+    UpdateResponse {
+        status: u8,
+    },
+    DataRequest {
+        offset: u32,
+        length: u16,
+    },
+    UpdateDone {
+        status: u8,
+        /// CRC-32 of the image written to the flash.
+        crc32: u32,
+    },
+}
+
+impl BootloaderFrame {
+    pub fn read(input: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(input);
+        if reader.u8()? != MAGIC {
+            return Err(Error::Decoding("Not a bootloader frame"));
+        }
+        Ok(match reader.u8()? {
+            ID_RESPONSE => BootloaderFrame::IdResponse {
+                version: reader.u32()?,
+                app_crc32: reader.u32()?,
+            },
+            UPDATE_RESPONSE => BootloaderFrame::UpdateResponse {
+                status: reader.u8()?,
+            },
+            DATA_REQUEST => BootloaderFrame::DataRequest {
+                offset: reader.u32()?,
+                length: reader.u16()?,
+            },
+            UPDATE_DONE => BootloaderFrame::UpdateDone {
+                status: reader.u8()?,
+                crc32: reader.u32()?,
+            },
+            _ => return Err(Error::Decoding("Unknown bootloader command")),
+        })
+    }
+}
+
+/// Codec of the serial device while it runs its bootloader.
+pub struct BootloaderCodec {}
+
+impl BootloaderCodec {
+    pub fn new() -> Self {
+        BootloaderCodec {}
+    }
+}
+
+impl Default for BootloaderCodec {
+    fn default() -> Self {
+        BootloaderCodec::new()
+    }
+}
+
+impl Decoder for BootloaderCodec {
+    type Item = BootloaderFrame;
+    type Error = Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<BootloaderFrame>, Error> {
+        // Skips the frames which cannot be read, up to the next valid one
+        while !buf.is_empty() {
+            let mut output = vec![0; buf.len()];
+            let (read, frame, is_end) = SLIPDecoder::new().decode(buf, &mut output)?;
+            if !is_end {
+                return Ok(None);
+            }
+            let frame = frame.to_vec();
+            buf.split_to(read);
+            if frame.len() < 2 {
+                continue;
+            }
+            let (body, crc) = frame.split_at(frame.len() - 2);
+            if crc != compute_crc(body) {
+                warn!("Bootloader frame with invalid checksum: {:x?}", frame);
+                continue;
+            }
+            match BootloaderFrame::read(body) {
+                Ok(frame) => {
+                    trace!("Decoded bootloader frame: {:?}", frame);
+                    return Ok(Some(frame));
+                }
+                Err(err) => warn!("Receive invalid bootloader frame: {:?}", err),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Encoder for BootloaderCodec {
+    type Item = HostFrame;
+    type Error = Error;
+
+    fn encode(&mut self, frame: HostFrame, buf: &mut BytesMut) -> Result<(), Error> {
+        let mut data = Vec::new();
+        frame.write(&mut data);
+        let crc = compute_crc(&data);
+        // Each byte takes two bytes at most once escaped, plus the delimiters
+        let mut output = vec![0; 2 * (data.len() + crc.len()) + 2];
+        let mut encoder = SLIPEncoder::new();
+        let mut result = encoder.encode(&data, &mut output)?;
+        result += encoder.encode(&crc, &mut output[result.1..])?;
+        result += encoder.finish(&mut output[result.1..])?;
+        buf.reserve(result.1);
+        buf.put(&output[0..result.1]);
+        Ok(())
+    }
+}
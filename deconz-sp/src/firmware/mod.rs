@@ -0,0 +1,242 @@
+//! Firmware update of the ConBee II through its bootloader, from `.GCF`
+//! files.
+//!
+//! The bootloader runs for a few seconds after the stick is powered up, before
+//! it starts the firmware: the stick is unplugged and plugged in again, then
+//! flashed within that window. The crate does not reset the stick into its
+//! bootloader. The original ConBee and the RaspBee use another bootloader
+//! protocol, which is not supported.
+
+use futures::future::{self, loop_fn, Either, Loop};
+use futures::{Future, Sink, Stream};
+use log::*;
+use std::path::Path;
+use std::time::Duration;
+use tokio::codec::Framed;
+
+use crate::call::with_timeout;
+use crate::reader::Reader;
+use crate::Error;
+
+pub mod bootloader;
+
+use bootloader::{BootloaderCodec, BootloaderFrame, HostFrame};
+
+#[cfg(test)]
+mod tests;
+
+const GCF_MAGIC: u32 = 0xcafe_feed;
+const GCF_HEADER_SIZE: usize = 14;
+
+/// Time allowed to write a whole image, a few seconds for the ConBee II.
+const FLASH_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Firmware image read from a `.GCF` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gcf {
+    pub file_type: u8,
+    /// Address of the image in the flash of the device.
+    pub target_address: u32,
+    /// Checksum byte of the header, as written by the firmware build.
+    pub checksum: u8,
+    pub data: Vec<u8>,
+}
+
+impl Gcf {
+    pub fn read(input: &[u8]) -> Result<Self, Error> {
+        if input.len() < GCF_HEADER_SIZE {
+            return Err(Error::InvalidFirmware("File shorter than the header"));
+        }
+        let mut reader = Reader::new(input);
+        if reader.u32()? != GCF_MAGIC {
+            return Err(Error::InvalidFirmware("Not a GCF file"));
+        }
+        let file_type = reader.u8()?;
+        let target_address = reader.u32()?;
+        let size = reader.u32()? as usize;
+        let checksum = reader.u8()?;
+        if reader.remaining() != size {
+            return Err(Error::InvalidFirmware("Size differs from the header"));
+        }
+        Ok(Gcf {
+            file_type,
+            target_address,
+            checksum,
+            data: Vec::from(reader.rest()),
+        })
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Gcf::read(&std::fs::read(path)?)
+    }
+
+    /// CRC-32 of the image, compared with the one computed by the bootloader.
+    pub fn crc32(&self) -> u32 {
+        crc32(&self.data)
+    }
+}
+
+/// CRC-32 (IEEE 802.3).
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(0xffff_ffff, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// Bytes of the image sent to the bootloader.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub sent: usize,
+    pub total: usize,
+}
+
+/// Sends the frame and waits for the answer.
+fn exchange<T>(
+    transport: T,
+    frame: HostFrame,
+) -> impl Future<Item = (BootloaderFrame, T), Error = Error>
+where
+    T: Stream<Item = BootloaderFrame, Error = Error>
+        + Sink<SinkItem = HostFrame, SinkError = Error>,
+{
+    transport.send(frame).and_then(receive)
+}
+
+fn receive<T>(transport: T) -> impl Future<Item = (BootloaderFrame, T), Error = Error>
+where
+    T: Stream<Item = BootloaderFrame, Error = Error>,
+{
+    transport
+        .into_future()
+        .map_err(|(err, _)| err)
+        .and_then(|(frame, transport)| match frame {
+            Some(frame) => Ok((frame, transport)),
+            None => Err(Error::Disconnected),
+        })
+}
+
+/// Writes the image through the bootloader talking on the transport, and
+/// checks the CRC-32 of the written image. Returns the version of the
+/// bootloader.
+pub fn flash<T, F>(
+    transport: T,
+    firmware: Gcf,
+    progress: F,
+) -> impl Future<Item = u32, Error = Error>
+where
+    T: Stream<Item = BootloaderFrame, Error = Error>
+        + Sink<SinkItem = HostFrame, SinkError = Error>,
+    F: FnMut(Progress),
+{
+    exchange(transport, HostFrame::IdRequest)
+        .and_then(|(frame, transport)| match frame {
+            BootloaderFrame::IdResponse { version, .. } => {
+                debug!("Bootloader version {:#010x}", version);
+                Ok((version, transport))
+            }
+            _ => Err(Error::Decoding("Expected bootloader id response")),
+        })
+        .and_then(move |(version, transport)| {
+            let request = HostFrame::UpdateRequest {
+                size: firmware.data.len() as u32,
+                target_address: firmware.target_address,
+                file_type: firmware.file_type,
+                crc32: firmware.crc32(),
+            };
+            exchange(transport, request).and_then(move |(frame, transport)| match frame {
+                BootloaderFrame::UpdateResponse {
+                    status: bootloader::STATUS_SUCCESS,
+                } => Ok((version, transport, firmware)),
+                BootloaderFrame::UpdateResponse { status } => Err(Error::BootloaderStatus(status)),
+                _ => Err(Error::Decoding("Expected bootloader update response")),
+            })
+        })
+        .and_then(move |(version, transport, firmware)| {
+            loop_fn((transport, firmware, progress), write_chunk).map(move |_| version)
+        })
+}
+
+/// Answers the next request of the bootloader, until it is done.
+fn write_chunk<T, F>(
+    (transport, firmware, mut progress): (T, Gcf, F),
+) -> impl Future<Item = Loop<(), (T, Gcf, F)>, Error = Error>
+where
+    T: Stream<Item = BootloaderFrame, Error = Error>
+        + Sink<SinkItem = HostFrame, SinkError = Error>,
+    F: FnMut(Progress),
+{
+    receive(transport).and_then(move |(frame, transport)| match frame {
+        BootloaderFrame::DataRequest { offset, length } => {
+            let start = offset as usize;
+            let response = if start < firmware.data.len() {
+                let end = (start + length as usize).min(firmware.data.len());
+                progress(Progress {
+                    sent: end,
+                    total: firmware.data.len(),
+                });
+                HostFrame::DataResponse {
+                    status: bootloader::STATUS_SUCCESS,
+                    offset,
+                    data: Vec::from(&firmware.data[start..end]),
+                }
+            } else {
+                warn!("Bootloader asks for data past the image: {}", offset);
+                HostFrame::DataResponse {
+                    status: bootloader::STATUS_INVALID_OFFSET,
+                    offset,
+                    data: Vec::new(),
+                }
+            };
+            Either::A(
+                transport
+                    .send(response)
+                    .map(|transport| Loop::Continue((transport, firmware, progress))),
+            )
+        }
+        BootloaderFrame::UpdateDone {
+            status: bootloader::STATUS_SUCCESS,
+            crc32,
+        } => {
+            let expected = firmware.crc32();
+            Either::B(if crc32 == expected {
+                future::ok(Loop::Break(()))
+            } else {
+                future::err(Error::FirmwareCrcMismatch(expected, crc32))
+            })
+        }
+        BootloaderFrame::UpdateDone { status, .. } => {
+            Either::B(future::err(Error::BootloaderStatus(status)))
+        }
+        _ => Either::B(future::err(Error::Decoding(
+            "Expected bootloader data request",
+        ))),
+    })
+}
+
+/// Flashes the ConBee II at `path`, which must be running its bootloader,
+/// i.e. right after being plugged in. See the module documentation.
+pub fn flash_device<F>(
+    path: &Path,
+    baud_rate: u32,
+    firmware: Gcf,
+    progress: F,
+) -> impl Future<Item = u32, Error = Error>
+where
+    F: FnMut(Progress),
+{
+    let serial = match crate::serial::open_port(path, baud_rate) {
+        Ok(serial) => serial,
+        Err(err) => return Either::A(future::err(err)),
+    };
+    let transport = Framed::new(serial, BootloaderCodec::new());
+    Either::B(with_timeout(
+        flash(transport, firmware, progress),
+        FLASH_TIMEOUT,
+    ))
+}
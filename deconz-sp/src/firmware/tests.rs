@@ -0,0 +1,203 @@
+use super::bootloader::*;
+use super::*;
+use bytes::BytesMut;
+use futures::{Async, AsyncSink, Poll, StartSend};
+use std::collections::VecDeque;
+use tokio::codec::{Decoder, Encoder};
+
+fn gcf_file(data: &[u8]) -> Vec<u8> {
+    let mut file = vec![0xed, 0xfe, 0xfe, 0xca, 0x03, 0x00, 0x50, 0x00, 0x00];
+    file.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    file.push(0xaa);
+    file.extend_from_slice(data);
+    file
+}
+
+fn image(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7) as u8).collect()
+}
+
+#[test]
+fn read_gcf() {
+    let data = image(300);
+    assert_eq!(
+        Gcf::read(&gcf_file(&data)).unwrap(),
+        Gcf {
+            file_type: 0x03,
+            target_address: 0x5000,
+            checksum: 0xaa,
+            data,
+        }
+    );
+}
+
+#[test]
+fn read_invalid_gcf() {
+    let mut file = gcf_file(&image(16));
+    file.pop();
+    assert!(Gcf::read(&file).is_err());
+    let mut file = gcf_file(&image(16));
+    file[0] = 0;
+    assert!(Gcf::read(&file).is_err());
+    assert!(Gcf::read(&[0xed, 0xfe, 0xfe, 0xca]).is_err());
+}
+
+#[test]
+fn compute_crc32() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    assert_eq!(crc32(&[]), 0);
+}
+
+#[test]
+fn encode_host_frames() {
+    let mut buf = BytesMut::new();
+    BootloaderCodec::new()
+        .encode(HostFrame::IdRequest, &mut buf)
+        .unwrap();
+    assert_eq!(&buf[..], &[0xc0, 0x81, 0x02, 0x7d, 0xff, 0xc0]);
+    let mut out = Vec::new();
+    HostFrame::DataResponse {
+        status: STATUS_SUCCESS,
+        offset: 0x100,
+        data: vec![0xc0, 0x01],
+    }
+    .write(&mut out);
+    assert_eq!(
+        out,
+        vec![0x81, 0x84, 0x00, 0x00, 0x01, 0x00, 0x00, 0x02, 0x00, 0xc0, 0x01]
+    );
+}
+
+#[test]
+fn decode_bootloader_frames() {
+    let mut codec = BootloaderCodec::new();
+    // Data request, preceded by a frame with an invalid checksum
+    let mut buf = BytesMut::from(
+        &[
+            0xc0, 0x81, 0x83, 0x00, 0x00, 0x00, 0xc0, 0xc0, 0x81, 0x04, 0x00, 0x01, 0x00, 0x00,
+            0x80, 0x00, 0xfa, 0xfe, 0xc0, 0xc0, 0x81,
+        ][..],
+    );
+    assert_eq!(
+        codec.decode(&mut buf).unwrap(),
+        Some(BootloaderFrame::DataRequest {
+            offset: 0x100,
+            length: 0x80,
+        })
+    );
+    // The next frame is not complete
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    assert_eq!(&buf[..], &[0xc0, 0x81]);
+}
+
+/// Bootloader writing the image to memory, `chunk_size` bytes at a time.
+struct SimulatedBootloader {
+    chunk_size: u16,
+    flash: Vec<u8>,
+    size: usize,
+    /// Flips a bit of the written image, as a faulty flash would.
+    corrupt: bool,
+    responses: VecDeque<BootloaderFrame>,
+}
+
+impl SimulatedBootloader {
+    fn new(chunk_size: u16) -> Self {
+        SimulatedBootloader {
+            chunk_size,
+            flash: Vec::new(),
+            size: 0,
+            corrupt: false,
+            responses: VecDeque::new(),
+        }
+    }
+
+    fn request_data(&mut self, offset: usize) {
+        let frame = if offset < self.size {
+            BootloaderFrame::DataRequest {
+                offset: offset as u32,
+                length: self.chunk_size,
+            }
+        } else {
+            if self.corrupt {
+                self.flash[0] ^= 1;
+            }
+            BootloaderFrame::UpdateDone {
+                status: STATUS_SUCCESS,
+                crc32: crc32(&self.flash),
+            }
+        };
+        self.responses.push_back(frame);
+    }
+}
+
+impl Sink for SimulatedBootloader {
+    type SinkItem = HostFrame;
+    type SinkError = Error;
+
+    fn start_send(&mut self, frame: HostFrame) -> StartSend<HostFrame, Error> {
+        match frame {
+            HostFrame::IdRequest => self.responses.push_back(BootloaderFrame::IdResponse {
+                version: 0x0300_0000,
+                app_crc32: 0,
+            }),
+            HostFrame::UpdateRequest { size, .. } => {
+                self.size = size as usize;
+                self.flash = vec![0xff; self.size];
+                self.responses.push_back(BootloaderFrame::UpdateResponse {
+                    status: STATUS_SUCCESS,
+                });
+                self.request_data(0);
+            }
+            HostFrame::DataResponse { offset, data, .. } => {
+                let offset = offset as usize;
+                self.flash[offset..offset + data.len()].copy_from_slice(&data);
+                self.request_data(offset + data.len());
+            }
+        }
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Error> {
+        Ok(Async::Ready(()))
+    }
+}
+
+impl Stream for SimulatedBootloader {
+    type Item = BootloaderFrame;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<BootloaderFrame>, Error> {
+        Ok(Async::Ready(self.responses.pop_front()))
+    }
+}
+
+#[test]
+fn flash_simulated_bootloader() {
+    let firmware = Gcf::read(&gcf_file(&image(1000))).unwrap();
+    let mut updates = Vec::new();
+    let version = flash(SimulatedBootloader::new(256), firmware, |progress| {
+        updates.push(progress)
+    })
+    .wait()
+    .unwrap();
+    assert_eq!(version, 0x0300_0000);
+    assert_eq!(
+        updates
+            .iter()
+            .map(|progress| progress.sent)
+            .collect::<Vec<_>>(),
+        vec![256, 512, 768, 1000]
+    );
+    assert!(updates.iter().all(|progress| progress.total == 1000));
+}
+
+#[test]
+fn flash_detects_crc_mismatch() {
+    let firmware = Gcf::read(&gcf_file(&image(100))).unwrap();
+    let mut bootloader = SimulatedBootloader::new(64);
+    bootloader.corrupt = true;
+    match flash(bootloader, firmware, |_| ()).wait() {
+        Err(Error::FirmwareCrcMismatch(expected, _)) => assert_eq!(expected, crc32(&image(100))),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,75 @@
+//! Install codes of Zigbee 3.0 devices, and the link keys derived from them.
+//!
+//! An install code is 6, 8, 12 or 16 random bytes followed by their CRC16. The
+//! device only joins with the link key derived from its install code, which
+//! must be given to the coordinator first, see `Client::add_install_code`.
+
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::Aes128;
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::Error;
+
+#[cfg(test)]
+mod tests;
+
+const BLOCK_SIZE: usize = 16;
+
+/// Checks the length and the CRC of the install code, given with its CRC.
+pub fn validate(install_code: &[u8]) -> Result<(), Error> {
+    match install_code.len() {
+        8 | 10 | 14 | 18 => (),
+        _ => return Err(Error::InvalidInstallCode("Invalid length")),
+    }
+    let (code, crc) = install_code.split_at(install_code.len() - 2);
+    if crc16(code) != LittleEndian::read_u16(crc) {
+        return Err(Error::InvalidInstallCode("Invalid CRC"));
+    }
+    Ok(())
+}
+
+/// Validates the install code, given with its CRC, and returns the link key
+/// derived from it.
+pub fn link_key(install_code: &[u8]) -> Result<[u8; 16], Error> {
+    validate(install_code)?;
+    Ok(aes_mmo_hash(install_code))
+}
+
+/// CRC-16/X-25 of the install code, as appended to it (little-endian).
+pub fn crc16(data: &[u8]) -> u16 {
+    let crc = data.iter().fold(0xffff, |crc: u16, byte| {
+        (0..8).fold(crc ^ u16::from(*byte), |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0x8408
+            } else {
+                crc >> 1
+            }
+        })
+    });
+    !crc
+}
+
+/// Matyas-Meyer-Oseas hash, based on AES-128, of the Zigbee specification
+/// (annex B.6). Messages must be shorter than 8 kB.
+pub fn aes_mmo_hash(data: &[u8]) -> [u8; 16] {
+    // The message is padded with a 1 bit, then 0 bits, then its length in
+    // bits on the last 2 bytes of the block
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % BLOCK_SIZE != BLOCK_SIZE - 2 {
+        message.push(0);
+    }
+    let bits = (data.len() * 8) as u16;
+    message.extend_from_slice(&bits.to_be_bytes());
+
+    let mut hash = [0; BLOCK_SIZE];
+    for block in message.chunks(BLOCK_SIZE) {
+        let cipher = Aes128::new(&hash.into());
+        let mut encrypted = aes::Block::clone_from_slice(block);
+        cipher.encrypt_block(&mut encrypted);
+        for (i, byte) in hash.iter_mut().enumerate() {
+            *byte = encrypted[i] ^ block[i];
+        }
+    }
+    hash
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+/// Example of the Zigbee specification.
+const INSTALL_CODE: [u8; 18] = [
+    0x83, 0xfe, 0xd3, 0x40, 0x7a, 0x93, 0x97, 0x23, 0xa5, 0xc6, 0x39, 0xb2, 0x69, 0x16, 0xd5, 0x05,
+    0xc3, 0xb5,
+];
+
+#[test]
+fn install_code_crc() {
+    assert_eq!(crc16(&INSTALL_CODE[..16]), 0xb5c3);
+    assert!(validate(&INSTALL_CODE).is_ok());
+}
+
+#[test]
+fn invalid_install_code() {
+    let mut install_code = INSTALL_CODE;
+    install_code[0] ^= 0xff;
+    assert!(validate(&install_code).is_err());
+    assert!(validate(&INSTALL_CODE[..16]).is_err());
+    assert!(validate(&[]).is_err());
+}
+
+#[test]
+fn derive_link_key() {
+    assert_eq!(
+        link_key(&INSTALL_CODE).unwrap(),
+        [
+            0x66, 0xb6, 0x90, 0x09, 0x81, 0xe1, 0xee, 0x3c, 0xa4, 0x20, 0x6b, 0x6b, 0x86, 0x1c,
+            0x02, 0xbb
+        ]
+    );
+}
+
+#[test]
+fn hash_multiple_blocks() {
+    // 14 bytes leave no room for the padding in the first block
+    assert_ne!(aes_mmo_hash(&[0; 14]), aes_mmo_hash(&[0; 13]));
+    assert_ne!(aes_mmo_hash(&[0; 32]), aes_mmo_hash(&[0; 16]));
+}
@@ -1,10 +1,29 @@
+pub mod backup;
+mod bus;
 mod call;
 mod client;
+pub mod discover;
 mod error;
+mod event;
+pub mod firmware;
+pub mod install_code;
+pub mod network;
 mod protocol;
+mod reader;
+pub mod registry;
+pub mod reports;
+mod serde_hex;
+mod serial;
+pub mod storage;
+pub mod topology;
+pub mod zcl;
+pub mod zdo;
 
-pub use client::Client;
+pub use bus::Subscription;
+pub use client::{Client, ClientOptions, CLIENT_ENDPOINT};
+pub use discover::discover;
 pub use error::Error;
+pub use event::Event;
 pub use protocol::constants;
 pub use protocol::types;
-pub use protocol::{IncomingMessage, OutgoingMessage, IncomingPayload};
+pub use protocol::{IncomingMessage, IncomingPayload, OutgoingMessage};
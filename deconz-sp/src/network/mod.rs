@@ -0,0 +1,257 @@
+//! Configuration of the network formed by the coordinator.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::protocol::constants::ParameterCode;
+use crate::protocol::types::ParameterValue;
+use crate::serde_hex::{hex, hex_option};
+use crate::zdo::NwkUpdateNotify;
+use crate::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Identity and security of the network. See `Client::form_network` and
+/// `Client::reconcile`.
+///
+/// In TOML, the extended PAN ID, the key and the trust center address are
+/// hexadecimal strings:
+///
+/// ```toml
+/// channel_mask = 0x00008000
+/// pan_id = 0x1a62
+/// extended_pan_id = "00dddddddddddddd"
+/// network_key = "01030507090b0d0f00020406080a0c0d"
+/// security_mode = 3
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Bit `n` allows channel `n` (11 to 26).
+    pub channel_mask: u32,
+    pub pan_id: u16,
+    #[serde(with = "hex")]
+    pub extended_pan_id: u64,
+    #[serde(with = "hex")]
+    pub network_key: [u8; 16],
+    /// 3 (no master key, trust center link key) unless the devices need
+    /// something else.
+    pub security_mode: u8,
+    /// Defaults to the MAC address of the coordinator.
+    #[serde(default, with = "hex_option")]
+    pub trust_center_address: Option<u64>,
+}
+
+impl NetworkConfig {
+    pub fn new(channel: u8, pan_id: u16, extended_pan_id: u64, network_key: [u8; 16]) -> Self {
+        NetworkConfig {
+            channel_mask: channel_mask(&[channel]),
+            pan_id,
+            extended_pan_id,
+            network_key,
+            security_mode: 3,
+            trust_center_address: None,
+        }
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Returns the parameters to write, in the order expected by the firmware.
+    pub fn parameters(&self, trust_center_address: u64) -> Vec<(ParameterCode, ParameterValue)> {
+        vec![
+            (ParameterCode::ApsDesignedCoordinator, ParameterValue::U8(1)),
+            (
+                ParameterCode::ChannelMask,
+                ParameterValue::U32(self.channel_mask),
+            ),
+            (ParameterCode::NwkPanId, ParameterValue::U16(self.pan_id)),
+            (
+                ParameterCode::ApsExtendedPanId,
+                ParameterValue::U64(self.extended_pan_id),
+            ),
+            (
+                ParameterCode::SecurityMode,
+                ParameterValue::U8(self.security_mode),
+            ),
+            (
+                ParameterCode::NetworkKey,
+                ParameterValue::NetworkKey {
+                    index: 0,
+                    key: self.network_key,
+                },
+            ),
+            (
+                ParameterCode::TrustCenterAddress,
+                ParameterValue::U64(trust_center_address),
+            ),
+        ]
+    }
+}
+
+/// Devices through which new devices may join. See `Client::permit_join`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PermitJoinTarget {
+    /// The coordinator and all the routers.
+    All,
+    Coordinator,
+    /// A single router, by NWK address.
+    Router(u16),
+}
+
+/// Counts the join windows opened through each target, so the end of a
+/// previous window does not close the current one of the same target.
+#[derive(Debug, Default)]
+pub(crate) struct PermitJoinWindows(HashMap<PermitJoinTarget, u32>);
+
+impl PermitJoinWindows {
+    /// Records a new window through the target, and returns its number.
+    pub fn open(&mut self, target: PermitJoinTarget) -> u32 {
+        let window = self.0.entry(target).or_insert(0);
+        *window = window.wrapping_add(1);
+        *window
+    }
+
+    /// Whether the window is the last one opened through the target.
+    pub fn is_current(&self, target: PermitJoinTarget, window: u32) -> bool {
+        self.0.get(&target) == Some(&window)
+    }
+}
+
+/// A parameter of the device which differs from the configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterChange {
+    pub parameter: ParameterCode,
+    /// `None` if the parameter could not be read.
+    pub current: Option<ParameterValue>,
+    pub desired: ParameterValue,
+}
+
+impl fmt::Display for ParameterChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.current {
+            Some(current) => write!(
+                f,
+                "{:?}: {} -> {}",
+                self.parameter,
+                DisplayValue(current),
+                DisplayValue(&self.desired)
+            ),
+            None => write!(
+                f,
+                "{:?}: -> {}",
+                self.parameter,
+                DisplayValue(&self.desired)
+            ),
+        }
+    }
+}
+
+struct DisplayValue<'a>(&'a ParameterValue);
+
+impl<'a> fmt::Display for DisplayValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            // Keys are not printed
+            ParameterValue::NetworkKey { index, .. } => write!(f, "<key {}>", index),
+            ParameterValue::LinkKey { ieee_address, .. } => {
+                write!(f, "<link key of {:#018x}>", ieee_address)
+            }
+            ParameterValue::GroupAddress { index, group_id } => {
+                write!(f, "<group {:#06x} at {}>", group_id, index)
+            }
+            value => write!(f, "{:#x}", value.u64()),
+        }
+    }
+}
+
+/// Returns the changes turning the current values into the desired ones, in the
+/// order of `desired`. Parameters missing from `current` are changed.
+pub fn plan(
+    desired: &[(ParameterCode, ParameterValue)],
+    current: &[(ParameterCode, ParameterValue)],
+) -> Vec<ParameterChange> {
+    desired
+        .iter()
+        .filter_map(|(parameter, desired)| {
+            let current = current
+                .iter()
+                .find(|(code, _)| code == parameter)
+                .map(|(_, value)| value.clone());
+            if current.as_ref() == Some(desired) {
+                None
+            } else {
+                Some(ParameterChange {
+                    parameter: *parameter,
+                    current,
+                    desired: desired.clone(),
+                })
+            }
+        })
+        .collect()
+}
+
+/// Energy measured on each channel by the routers of the network. See
+/// `Client::energy_scan`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnergyScan {
+    /// Measures of each channel, one per router which answered.
+    pub energy: BTreeMap<u8, Vec<u8>>,
+    /// Routers which did not answer, by NWK address.
+    pub failed: Vec<u16>,
+}
+
+impl EnergyScan {
+    /// Records the energy measured by a router.
+    pub fn add(&mut self, notify: &NwkUpdateNotify) {
+        for (channel, energy) in channels(notify.scanned_channels)
+            .into_iter()
+            .zip(notify.energy_values.iter())
+        {
+            self.energy.entry(channel).or_default().push(*energy);
+        }
+    }
+
+    /// Highest energy measured on the channel.
+    pub fn max(&self, channel: u8) -> Option<u8> {
+        self.energy
+            .get(&channel)
+            .and_then(|measures| measures.iter().max().cloned())
+    }
+
+    pub fn average(&self, channel: u8) -> Option<u8> {
+        self.energy
+            .get(&channel)
+            .filter(|measures| !measures.is_empty())
+            .map(|measures| {
+                let sum: usize = measures.iter().map(|energy| *energy as usize).sum();
+                (sum / measures.len()) as u8
+            })
+    }
+
+    /// Returns the channel with the lowest highest energy, i.e. the one where
+    /// the noisiest router is the least disturbed.
+    pub fn quietest(&self) -> Option<u8> {
+        self.energy
+            .keys()
+            .min_by_key(|channel| self.max(**channel))
+            .cloned()
+    }
+}
+
+/// Returns the mask allowing the channels.
+pub fn channel_mask(channels: &[u8]) -> u32 {
+    channels
+        .iter()
+        .filter(|channel| (11..=26).contains(*channel))
+        .fold(0, |mask, channel| mask | (1 << channel))
+}
+
+/// Returns the channels allowed by the mask.
+pub fn channels(mask: u32) -> Vec<u8> {
+    (11..=26)
+        .filter(|channel| mask & (1 << channel) != 0)
+        .collect()
+}
@@ -0,0 +1,143 @@
+use super::*;
+
+#[test]
+fn channel_mask_of_channels() {
+    assert_eq!(channel_mask(&[11]), 0x0000_0800);
+    assert_eq!(channel_mask(&[15, 20, 25]), 0x0210_8000);
+    // Channels outside of the 2.4 GHz band are ignored
+    assert_eq!(channel_mask(&[0, 27]), 0);
+}
+
+#[test]
+fn channels_of_channel_mask() {
+    assert_eq!(channels(0x0210_8000), vec![15, 20, 25]);
+    assert_eq!(channels(0x07ff_f800).len(), 16);
+    assert!(channels(0x0000_07ff).is_empty());
+}
+
+#[test]
+fn default_network_config() {
+    let config = NetworkConfig::new(15, 0x1a62, 0x00dd_dddd_dddd_dddd, [0x01; 16]);
+    assert_eq!(channels(config.channel_mask), vec![15]);
+    assert_eq!(config.security_mode, 3);
+    assert_eq!(config.trust_center_address, None);
+}
+
+const TOML: &str = r#"
+channel_mask = 0x00008000
+pan_id = 0x1a62
+extended_pan_id = "00dddddddddddddd"
+network_key = "01030507090b0d0f00020406080a0c0d"
+security_mode = 3
+"#;
+
+#[test]
+fn network_config_from_toml() {
+    let config = NetworkConfig::from_toml(TOML).unwrap();
+    assert_eq!(channels(config.channel_mask), vec![15]);
+    assert_eq!(config.pan_id, 0x1a62);
+    assert_eq!(config.extended_pan_id, 0x00dd_dddd_dddd_dddd);
+    assert_eq!(config.network_key[..4], [0x01, 0x03, 0x05, 0x07]);
+    assert_eq!(config.trust_center_address, None);
+    assert!(NetworkConfig::from_toml("pan_id = 0x1a62").is_err());
+}
+
+#[test]
+fn plan_changes() {
+    let config = NetworkConfig::from_toml(TOML).unwrap();
+    let desired = config.parameters(0x0021_2eff_ff01_2345);
+    let mut current = desired.clone();
+    assert!(plan(&desired, &current).is_empty());
+
+    current[2] = (ParameterCode::NwkPanId, ParameterValue::U16(0x1234));
+    current.retain(|(parameter, _)| *parameter != ParameterCode::SecurityMode);
+    let changes = plan(&desired, &current);
+    assert_eq!(
+        changes,
+        vec![
+            ParameterChange {
+                parameter: ParameterCode::NwkPanId,
+                current: Some(ParameterValue::U16(0x1234)),
+                desired: ParameterValue::U16(0x1a62),
+            },
+            ParameterChange {
+                parameter: ParameterCode::SecurityMode,
+                current: None,
+                desired: ParameterValue::U8(3),
+            },
+        ]
+    );
+    assert_eq!(changes[0].to_string(), "NwkPanId: 0x1234 -> 0x1a62");
+}
+
+#[test]
+fn hide_keys_in_changes() {
+    let change = ParameterChange {
+        parameter: ParameterCode::NetworkKey,
+        current: Some(ParameterValue::NetworkKey {
+            index: 0,
+            key: [0; 16],
+        }),
+        desired: ParameterValue::NetworkKey {
+            index: 0,
+            key: [0xab; 16],
+        },
+    };
+    assert_eq!(change.to_string(), "NetworkKey: <key 0> -> <key 0>");
+}
+
+#[test]
+fn show_group_entries_in_changes() {
+    let change = ParameterChange {
+        parameter: ParameterCode::GroupAddress,
+        current: Some(ParameterValue::GroupAddress {
+            index: 2,
+            group_id: 0xffff,
+        }),
+        desired: ParameterValue::GroupAddress {
+            index: 2,
+            group_id: 0x0001,
+        },
+    };
+    assert_eq!(
+        change.to_string(),
+        "GroupAddress: <group 0xffff at 2> -> <group 0x0001 at 2>"
+    );
+}
+
+#[test]
+fn aggregate_energy_scans() {
+    let mut scan = EnergyScan::default();
+    scan.add(&NwkUpdateNotify {
+        scanned_channels: channel_mask(&[11, 15, 25]),
+        total_transmissions: 10,
+        transmission_failures: 0,
+        energy_values: vec![0xc0, 0x20, 0x40],
+    });
+    scan.add(&NwkUpdateNotify {
+        scanned_channels: channel_mask(&[11, 15, 25]),
+        total_transmissions: 10,
+        transmission_failures: 1,
+        energy_values: vec![0xe0, 0x60, 0x30],
+    });
+    assert_eq!(scan.energy[&11], vec![0xc0, 0xe0]);
+    assert_eq!(scan.max(15), Some(0x60));
+    assert_eq!(scan.average(25), Some(0x38));
+    assert_eq!(scan.max(20), None);
+    assert_eq!(scan.quietest(), Some(25));
+}
+
+#[test]
+fn permit_join_windows_per_target() {
+    let mut windows = PermitJoinWindows::default();
+    let coordinator = windows.open(PermitJoinTarget::Coordinator);
+    let router = windows.open(PermitJoinTarget::Router(0x1234));
+    // Both windows close, the one of the router does not replace the other
+    assert!(windows.is_current(PermitJoinTarget::Coordinator, coordinator));
+    assert!(windows.is_current(PermitJoinTarget::Router(0x1234), router));
+    let reopened = windows.open(PermitJoinTarget::Coordinator);
+    assert!(!windows.is_current(PermitJoinTarget::Coordinator, coordinator));
+    assert!(windows.is_current(PermitJoinTarget::Coordinator, reopened));
+    assert!(windows.is_current(PermitJoinTarget::Router(0x1234), router));
+    assert!(!windows.is_current(PermitJoinTarget::Router(0x5678), router));
+}
@@ -21,10 +21,10 @@ impl Decoder for Codec {
 
     fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<IncomingMessage>, Error> {
         // TODO: validate CRC
-        if buf.len() < 1 {
+        if buf.is_empty() {
             return Ok(None);
         }
-        trace!("Decode incoming frame: {:x?}", &buf as &[u8]);
+        trace!("Decode incoming frame: {:x?}", &buf[..]);
         let mut decoder = SLIPDecoder::new();
         let mut output = [0; 300]; // TODO: 32 is suffisent?
         let (readed, _, is_end) = decoder.decode(buf, &mut output)?;
@@ -73,9 +73,12 @@ impl Encoder for Codec {
     }
 }
 
-fn compute_crc(data: &[u8]) -> [u8; 2] {
-    let crc = data.iter().fold(0 as u16, |acc, value| acc + *value as u16);
-    let crc = !(crc as u16) + 1;
+/// Checksum closing each frame: two's complement of the sum of the bytes.
+pub(crate) fn compute_crc(data: &[u8]) -> [u8; 2] {
+    let crc = data
+        .iter()
+        .fold(0u16, |acc, value| acc.wrapping_add(*value as u16));
+    let crc = (!crc).wrapping_add(1);
     let mut buf = [0; 2];
     LittleEndian::write_u16(&mut buf, crc);
     buf
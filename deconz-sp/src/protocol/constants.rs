@@ -8,6 +8,10 @@ pub enum CommandCode {
     ApsDataRequest,
     ApsDataConfirm,
     ApsDataIndication,
+    Version,
+    MacPoll,
+    /// Command the crate does not model, sent and received as raw frames.
+    Unknown(u8),
 }
 
 impl CommandCode {
@@ -21,6 +25,9 @@ impl CommandCode {
             CommandCode::ApsDataRequest => 0x12,
             CommandCode::ApsDataConfirm => 0x04,
             CommandCode::ApsDataIndication => 0x17,
+            CommandCode::Version => 0x0d,
+            CommandCode::MacPoll => 0x1c,
+            CommandCode::Unknown(code) => *code,
         }
     }
     pub fn from_code(code: u8) -> Option<Self> {
@@ -33,12 +40,14 @@ impl CommandCode {
             0x12 => Some(CommandCode::ApsDataRequest),
             0x04 => Some(CommandCode::ApsDataConfirm),
             0x17 => Some(CommandCode::ApsDataIndication),
+            0x0d => Some(CommandCode::Version),
+            0x1c => Some(CommandCode::MacPoll),
             _ => None,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ParameterCode {
     MacAddress,
     NwkPanId,
@@ -50,9 +59,15 @@ pub enum ParameterCode {
     TrustCenterAddress,
     SecurityMode,
     NetworkKey,
+    LinkKey,
     CurrentChannel,
+    /// Entry of the APS group table: the coordinator receives the frames sent
+    /// to the groups of the table.
+    GroupAddress,
+    PermitJoin,
     ProtocolVersion,
     NwkUpdateId,
+    NwkFrameCounter,
 }
 
 impl ParameterCode {
@@ -68,9 +83,13 @@ impl ParameterCode {
             ParameterCode::TrustCenterAddress => 0x0e,
             ParameterCode::SecurityMode => 0x10,
             ParameterCode::NetworkKey => 0x18,
+            ParameterCode::LinkKey => 0x19,
             ParameterCode::CurrentChannel => 0x1c,
+            ParameterCode::GroupAddress => 0x20,
+            ParameterCode::PermitJoin => 0x21,
             ParameterCode::ProtocolVersion => 0x22,
             ParameterCode::NwkUpdateId => 0x24,
+            ParameterCode::NwkFrameCounter => 0x27,
         }
     }
     pub fn from_code(code: u8) -> Option<Self> {
@@ -85,12 +104,17 @@ impl ParameterCode {
             0x0e => Some(ParameterCode::TrustCenterAddress),
             0x10 => Some(ParameterCode::SecurityMode),
             0x18 => Some(ParameterCode::NetworkKey),
+            0x19 => Some(ParameterCode::LinkKey),
             0x1c => Some(ParameterCode::CurrentChannel),
+            0x20 => Some(ParameterCode::GroupAddress),
+            0x21 => Some(ParameterCode::PermitJoin),
             0x22 => Some(ParameterCode::ProtocolVersion),
             0x24 => Some(ParameterCode::NwkUpdateId),
+            0x27 => Some(ParameterCode::NwkFrameCounter),
             _ => None,
         }
     }
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> u8 {
         match self {
             ParameterCode::MacAddress => 8,
@@ -102,10 +126,14 @@ impl ParameterCode {
             ParameterCode::ApsExtendedPanId => 8,
             ParameterCode::TrustCenterAddress => 8,
             ParameterCode::SecurityMode => 1,
-            ParameterCode::NetworkKey => 1,
+            ParameterCode::NetworkKey => 17,
+            ParameterCode::LinkKey => 24,
             ParameterCode::CurrentChannel => 1,
+            ParameterCode::GroupAddress => 3,
+            ParameterCode::PermitJoin => 1,
             ParameterCode::ProtocolVersion => 2,
             ParameterCode::NwkUpdateId => 1,
+            ParameterCode::NwkFrameCounter => 4,
         }
     }
 }
@@ -156,7 +184,7 @@ impl StatusCode {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NetworkStateCode {
     Offline,
     Joining,
@@ -1,5 +1,6 @@
 use super::constants::{CommandCode, NetworkStateCode, ParameterCode, StatusCode};
-use super::types::{Address, ParameterValue};
+use super::types::{Address, ParameterValue, SourceAddress};
+use crate::reader::Reader;
 use crate::Error;
 use byteorder::{ByteOrder, LittleEndian};
 use log::*;
@@ -9,7 +10,7 @@ mod tests;
 
 const FRAME_MIN_LEN: usize = 5;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum IncomingPayload {
     ReadParameter {
         parameter: ParameterCode,
@@ -37,6 +38,33 @@ pub enum IncomingPayload {
         lqi: u8,
         rssi: i8,
     },
+    ApsDataRequest {
+        request_id: u8,
+    },
+    ApsDataConfirm {
+        request_id: u8,
+        destination: Address,
+        source_endpoint: u8,
+        status: u8,
+    },
+    /// Firmware version: major and minor version in the two high bytes, then
+    /// the platform (0x05 ConBee and RaspBee, 0x07 ConBee II and RaspBee II).
+    Version {
+        version: u32,
+    },
+    /// A sleepy end device polled the coordinator, its parent, for the frames
+    /// held for it.
+    MacPoll {
+        address: SourceAddress,
+        lqi: u8,
+        rssi: i8,
+    },
+    /// Payload of a command the crate does not model, e.g. sent by a newer
+    /// firmware.
+    Unknown {
+        command: u8,
+        bytes: Vec<u8>,
+    },
 }
 
 fn decode_device_state(state: u8) -> Option<(NetworkStateCode, bool, bool, bool, bool)> {
@@ -62,22 +90,49 @@ impl IncomingPayload {
                     return Err(Error::Decoding("Too short payload for ReadParameter"));
                 }
                 let payload_len = LittleEndian::read_u16(&input[0..2]) as usize;
-                if input.len() < payload_len {
+                if payload_len < 2 || input.len() < 2 + payload_len {
                     return Err(Error::Decoding(
                         "Too short payload: incorrect payload length",
                     ));
                 }
                 let parameter_len = payload_len - 1;
-                let parameter = ParameterCode::from_code(input[2]);
-                let parameter_value =
-                    LittleEndian::read_uint(&input[3..3 + parameter_len], parameter_len);
-                match parameter {
-                    None => Err(Error::Decoding("Unknown parameter id")),
-                    Some(parameter) => Ok(IncomingPayload::ReadParameter {
-                        parameter,
-                        value: ParameterValue::from_value_and_len(parameter_value, parameter_len),
-                    }),
-                }
+                let parameter = match ParameterCode::from_code(input[2]) {
+                    None => return Err(Error::Decoding("Unknown parameter id")),
+                    Some(parameter) => parameter,
+                };
+                let input = &input[3..3 + parameter_len];
+                let value = match parameter {
+                    ParameterCode::NetworkKey if parameter_len == 17 => {
+                        let mut key = [0; 16];
+                        key.clone_from_slice(&input[1..]);
+                        ParameterValue::NetworkKey {
+                            index: input[0],
+                            key,
+                        }
+                    }
+                    ParameterCode::LinkKey if parameter_len == 24 => {
+                        let mut key = [0; 16];
+                        key.clone_from_slice(&input[8..]);
+                        ParameterValue::LinkKey {
+                            ieee_address: LittleEndian::read_u64(input),
+                            key,
+                        }
+                    }
+                    ParameterCode::GroupAddress if parameter_len == 3 => {
+                        ParameterValue::GroupAddress {
+                            index: input[0],
+                            group_id: LittleEndian::read_u16(&input[1..]),
+                        }
+                    }
+                    _ if parameter_len > 8 => {
+                        return Err(Error::Decoding("Unexpected parameter length"))
+                    }
+                    _ => ParameterValue::from_value_and_len(
+                        LittleEndian::read_uint(input, parameter_len),
+                        parameter_len,
+                    ),
+                };
+                Ok(IncomingPayload::ReadParameter { parameter, value })
             }
             CommandCode::WriteParameter => {
                 if input.len() < 3 {
@@ -93,7 +148,7 @@ impl IncomingPayload {
                 }
             }
             CommandCode::DeviceState => {
-                if input.len() < 1 {
+                if input.is_empty() {
                     return Err(Error::Decoding("Too short payload for DeviceState"));
                 }
                 match decode_device_state(input[0]) {
@@ -114,7 +169,7 @@ impl IncomingPayload {
                 }
             }
             CommandCode::ChangeNetworkState => {
-                if input.len() < 1 {
+                if input.is_empty() {
                     return Err(Error::Decoding("Too short payload for DeviceState"));
                 }
                 match NetworkStateCode::from_code(input[0]) {
@@ -126,7 +181,7 @@ impl IncomingPayload {
                 if input.len() < 2 {
                     return Err(Error::Decoding("Too short payload for ApsDataIndication"));
                 }
-                let payload_length = LittleEndian::read_u16(&input) as usize;
+                let payload_length = LittleEndian::read_u16(input) as usize;
                 if input.len() < (2 + payload_length) {
                     return Err(Error::Decoding(
                         "Too short payload for ApsDataIndication: invalid payload_length",
@@ -191,6 +246,60 @@ impl IncomingPayload {
                     rssi,
                 })
             }
+            CommandCode::ApsDataRequest => {
+                if input.len() < 4 {
+                    return Err(Error::Decoding("Too short payload for ApsDataRequest"));
+                }
+                Ok(IncomingPayload::ApsDataRequest {
+                    request_id: input[3],
+                })
+            }
+            CommandCode::ApsDataConfirm => {
+                let mut reader = Reader::new(input);
+                let payload_length = reader.u16()? as usize;
+                if input.len() < (2 + payload_length) {
+                    return Err(Error::Decoding(
+                        "Too short payload for ApsDataConfirm: invalid payload_length",
+                    ));
+                }
+                let _device_state = reader.u8()?;
+                let request_id = reader.u8()?;
+                let destination = match reader.u8()? {
+                    0x1 => Address::Group(reader.u16()?),
+                    0x2 => Address::NWK(reader.u16()?, reader.u8()?),
+                    0x3 => Address::IEEE(reader.u64()?, reader.u8()?),
+                    _ => return Err(Error::Decoding("Unknown address mode for destination")),
+                };
+                let source_endpoint = reader.u8()?;
+                let status = reader.u8()?;
+                Ok(IncomingPayload::ApsDataConfirm {
+                    request_id,
+                    destination,
+                    source_endpoint,
+                    status,
+                })
+            }
+            CommandCode::Version => Ok(IncomingPayload::Version {
+                version: Reader::new(input).u32()?,
+            }),
+            CommandCode::MacPoll => {
+                let mut reader = Reader::new(input);
+                reader.u16()?;
+                let address = match reader.u8()? {
+                    0x2 => SourceAddress::NWK(reader.u16()?),
+                    0x3 => SourceAddress::IEEE(reader.u64()?),
+                    _ => return Err(Error::Decoding("Unknown address mode for MacPoll")),
+                };
+                Ok(IncomingPayload::MacPoll {
+                    address,
+                    lqi: reader.u8()?,
+                    rssi: reader.i8()?,
+                })
+            }
+            CommandCode::Unknown(command) => Ok(IncomingPayload::Unknown {
+                command: *command,
+                bytes: Vec::from(input),
+            }),
             CommandCode::DeviceStateChanged => match decode_device_state(input[0]) {
                 None => Err(Error::Decoding("Cannot decode device state")),
                 Some((
@@ -207,9 +316,6 @@ impl IncomingPayload {
                     apsde_data_request,
                 }),
             },
-            _ => Err(Error::Decoding(
-                "This command decoder is not yet implemented",
-            )),
         }
     }
 }
@@ -220,6 +326,8 @@ pub struct IncomingMessage {
     pub seq: u8,
     pub status: StatusCode,
     pub payload: IncomingPayload,
+    /// The frame as received, without its checksum.
+    pub frame: Vec<u8>,
 }
 
 impl IncomingMessage {
@@ -227,7 +335,7 @@ impl IncomingMessage {
         if input.len() < FRAME_MIN_LEN {
             return Err(Error::Decoding("Frame is too short: cannot read header"));
         }
-        let command = CommandCode::from_code(input[0]);
+        let command = CommandCode::from_code(input[0]).unwrap_or(CommandCode::Unknown(input[0]));
         let seq = input[1];
         let status = StatusCode::from_code(input[2]);
         let frame_len = LittleEndian::read_u16(&input[3..5]) as usize;
@@ -236,16 +344,16 @@ impl IncomingMessage {
                 "Frame is too short: invalid Frame length value",
             ));
         }
-        match (command, status) {
-            (None, _) => Err(Error::Decoding("Invalid commande code")),
-            (_, None) => Err(Error::Decoding("Invalid status code")),
-            (Some(command), Some(status)) => {
+        match status {
+            None => Err(Error::Decoding("Invalid status code")),
+            Some(status) => {
                 let payload = IncomingPayload::read(&command, &input[5..frame_len])?;
                 Ok(IncomingMessage {
                     command,
                     seq,
                     status,
                     payload,
+                    frame: Vec::from(&input[..frame_len]),
                 })
             }
         }
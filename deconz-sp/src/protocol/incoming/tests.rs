@@ -10,8 +10,8 @@ fn decode_invalid_frame() {
     assert!(IncomingMessage::read(&[0x07, 0x1, 0x1, 0xff, 0x0, 0x0]).is_err());
     // Invalid status code
     assert!(IncomingMessage::read(&[0x07, 0x1, 0xff, 0x5, 0x0]).is_err());
-    // Invalid command code
-    assert!(IncomingMessage::read(&[0xff, 0x1, 0x1, 0x5, 0x0]).is_err());
+    // Unknown command codes are passed through
+    assert!(IncomingMessage::read(&[0xff, 0x1, 0x0, 0x5, 0x0]).is_ok());
 }
 
 #[test]
@@ -222,3 +222,166 @@ fn decode_valid_aps_data_indication() {
         _ => panic!("Invalid response payload"),
     };
 }
+
+#[test]
+fn decode_valid_aps_data_request() {
+    let frame = [0x12, 0xa, 0x0, 0x9, 0x0, 0x2, 0x0, 0x22, 0x7];
+    let response = IncomingMessage::read(&frame);
+    assert!(response.is_ok());
+    let response = response.unwrap();
+    assert_eq!(
+        response.command.code(),
+        CommandCode::ApsDataRequest.code(),
+        "Invalid command in response"
+    );
+    match response.payload {
+        IncomingPayload::ApsDataRequest { request_id } => {
+            assert_eq!(request_id, 7, "Invalid request_id");
+        }
+        _ => panic!("Invalid response payload"),
+    };
+}
+
+#[test]
+fn decode_valid_aps_data_confirm() {
+    let frame = [
+        0x04, 0xa, 0x0, 0x13, 0x0, 0xc, 0x0, 0x26, 0x7, 0x2, 0x34, 0x12, 0x1, 0x1, 0xe1, 0x0, 0x0,
+        0x0, 0x0,
+    ];
+    let response = IncomingMessage::read(&frame);
+    assert!(response.is_ok());
+    let response = response.unwrap();
+    assert_eq!(
+        response.command.code(),
+        CommandCode::ApsDataConfirm.code(),
+        "Invalid command in response"
+    );
+    match response.payload {
+        IncomingPayload::ApsDataConfirm {
+            request_id,
+            destination,
+            source_endpoint,
+            status,
+        } => {
+            assert_eq!(request_id, 7, "Invalid request_id");
+            assert_eq!(destination, Address::NWK(0x1234, 1), "Invalid destination");
+            assert_eq!(source_endpoint, 1, "Invalid source_endpoint");
+            assert_eq!(status, 0xe1, "Invalid status");
+        }
+        _ => panic!("Invalid response payload"),
+    };
+}
+
+#[test]
+fn decode_valid_read_network_key() {
+    let mut frame = vec![0xa, 0xa, 0x0, 0x19, 0x0, 0x12, 0x0, 0x18, 0x0];
+    frame.extend(0..16);
+    match IncomingMessage::read(&frame).map(|response| response.payload) {
+        Ok(IncomingPayload::ReadParameter { parameter, value }) => {
+            assert_eq!(parameter, ParameterCode::NetworkKey);
+            let mut key = [0; 16];
+            for (i, byte) in key.iter_mut().enumerate() {
+                *byte = i as u8;
+            }
+            assert_eq!(value, ParameterValue::NetworkKey { index: 0, key });
+            assert_eq!(value.as_u64(), None);
+        }
+        other => panic!("Unexpected response: {:?}", other),
+    }
+}
+
+#[test]
+fn decode_valid_read_link_key() {
+    let mut frame = vec![
+        0xa, 0xa, 0x0, 0x20, 0x0, 0x19, 0x0, 0x19, 0x67, 0x45, 0x23, 0x01, 0x00, 0x8d, 0x15, 0x00,
+    ];
+    frame.extend(&[0x5a; 16]);
+    match IncomingMessage::read(&frame).map(|response| response.payload) {
+        Ok(IncomingPayload::ReadParameter { parameter, value }) => {
+            assert_eq!(parameter, ParameterCode::LinkKey);
+            assert_eq!(
+                value,
+                ParameterValue::LinkKey {
+                    ieee_address: 0x0015_8d00_0123_4567,
+                    key: [0x5a; 16],
+                }
+            );
+        }
+        other => panic!("Unexpected response: {:?}", other),
+    }
+}
+
+#[test]
+fn decode_valid_read_group_address() {
+    let frame = [0xa, 0xa, 0x0, 0xb, 0x0, 0x4, 0x0, 0x20, 0x02, 0x34, 0x12];
+    match IncomingMessage::read(&frame).map(|response| response.payload) {
+        Ok(IncomingPayload::ReadParameter { parameter, value }) => {
+            assert_eq!(parameter, ParameterCode::GroupAddress);
+            assert_eq!(
+                value,
+                ParameterValue::GroupAddress {
+                    index: 2,
+                    group_id: 0x1234
+                }
+            );
+        }
+        other => panic!("Unexpected response: {:?}", other),
+    }
+}
+
+#[test]
+fn decode_valid_version() {
+    let frame = [0xd, 0x5, 0x0, 0x9, 0x0, 0x0, 0x7, 0x72, 0x26];
+    let response = IncomingMessage::read(&frame).unwrap();
+    assert_eq!(response.command, CommandCode::Version);
+    match response.payload {
+        IncomingPayload::Version { version } => assert_eq!(version, 0x2672_0700),
+        _ => panic!("Invalid response payload"),
+    };
+}
+
+#[test]
+fn decode_unknown_command() {
+    // Followed by the crc bytes
+    let frame = [
+        0x2a, 0x3, 0x0, 0xa, 0x0, 0x2, 0x34, 0x12, 0xff, 0xc5, 0x6e, 0xfd,
+    ];
+    let response = IncomingMessage::read(&frame).unwrap();
+    assert_eq!(response.command, CommandCode::Unknown(0x2a));
+    assert_eq!(response.frame, &frame[..10]);
+    match response.payload {
+        IncomingPayload::Unknown { command, bytes } => {
+            assert_eq!(command, 0x2a);
+            assert_eq!(bytes, vec![0x2, 0x34, 0x12, 0xff, 0xc5]);
+        }
+        _ => panic!("Invalid response payload"),
+    };
+}
+
+#[test]
+fn decode_valid_mac_poll() {
+    let frame = [
+        0x1c, 0x3, 0x0, 0xc, 0x0, 0x5, 0x0, 0x2, 0x34, 0x12, 0xff, 0xc5,
+    ];
+    let response = IncomingMessage::read(&frame).unwrap();
+    assert_eq!(response.command, CommandCode::MacPoll);
+    match response.payload {
+        IncomingPayload::MacPoll { address, lqi, rssi } => {
+            assert_eq!(address, SourceAddress::NWK(0x1234));
+            assert_eq!(lqi, 0xff);
+            assert_eq!(rssi, -59);
+        }
+        _ => panic!("Invalid response payload"),
+    };
+    // Followed by the lifetime and timeout of the device in newer firmwares
+    let frame = [
+        0x1c, 0x4, 0x0, 0x1a, 0x0, 0x13, 0x0, 0x3, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x0,
+        0x80, 0xb0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ];
+    match IncomingMessage::read(&frame).unwrap().payload {
+        IncomingPayload::MacPoll { address, .. } => {
+            assert_eq!(address, SourceAddress::IEEE(0x0011_2233_4455_6677))
+        }
+        _ => panic!("Invalid response payload"),
+    };
+}
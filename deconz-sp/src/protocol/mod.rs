@@ -5,6 +5,7 @@ mod outgoing;
 pub mod constants;
 pub mod types;
 
+pub(crate) use codec::compute_crc;
 pub use codec::Codec;
 pub use incoming::{IncomingMessage, IncomingPayload};
 pub use outgoing::OutgoingMessage;
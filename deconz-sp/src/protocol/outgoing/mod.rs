@@ -14,12 +14,15 @@ enum OutgoingPayload {
     Empty,
     ReadParameter {
         parameter: ParameterCode,
+        /// Selects the value to read, e.g. the index of the network key.
+        argument: Vec<u8>,
     },
     WriteParameter {
         parameter: ParameterCode,
         value: ParameterValue,
     },
     DeviceState,
+    Version,
     ChangeNetworkState {
         state: NetworkStateCode,
     },
@@ -32,24 +35,27 @@ enum OutgoingPayload {
         asdu: Vec<u8>,
         radius: u8,
     },
+    /// Written as is, including its length if the command has one.
+    Raw(Vec<u8>),
 }
 
 impl OutgoingPayload {
     fn has_variable_length(&self) -> bool {
-        match self {
-            OutgoingPayload::Empty => true,
-            OutgoingPayload::ReadParameter { .. } => true,
-            OutgoingPayload::WriteParameter { .. } => true,
-            OutgoingPayload::ApsDataRequest { .. } => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            OutgoingPayload::Empty
+                | OutgoingPayload::ReadParameter { .. }
+                | OutgoingPayload::WriteParameter { .. }
+                | OutgoingPayload::ApsDataRequest { .. }
+        )
     }
     fn length(&self) -> usize {
         match self {
             OutgoingPayload::Empty => 0,
-            OutgoingPayload::ReadParameter { .. } => 1,
+            OutgoingPayload::ReadParameter { argument, .. } => 1 + argument.len(),
             OutgoingPayload::WriteParameter { value, .. } => 1 + value.length(),
             OutgoingPayload::DeviceState => 3,
+            OutgoingPayload::Version => 4,
             OutgoingPayload::ChangeNetworkState { .. } => 1,
             OutgoingPayload::ApsDataRequest {
                 destination, asdu, ..
@@ -61,24 +67,33 @@ impl OutgoingPayload {
                 };
                 12 + address_len + asdu.len()
             }
+            OutgoingPayload::Raw(payload) => payload.len(),
         }
     }
     fn write(&self, out: &mut [u8]) -> Result<(), Error> {
         match self {
             OutgoingPayload::Empty => Ok(()),
-            OutgoingPayload::ReadParameter { parameter } => {
+            OutgoingPayload::ReadParameter {
+                parameter,
+                argument,
+            } => {
                 out[0] = parameter.code();
+                out[1..1 + argument.len()].clone_from_slice(argument);
                 Ok(())
             }
             OutgoingPayload::WriteParameter { parameter, value } => {
                 out[0] = parameter.code();
-                LittleEndian::write_uint(&mut out[1..], value.u64(), value.length());
+                value.write(&mut out[1..]);
                 Ok(())
             }
             OutgoingPayload::DeviceState => {
                 out[0..3].clone_from_slice(&[0x0, 0x0, 0x0]);
                 Ok(())
             }
+            OutgoingPayload::Version => {
+                out[0..4].clone_from_slice(&[0x0, 0x0, 0x0, 0x0]);
+                Ok(())
+            }
             OutgoingPayload::ChangeNetworkState { state } => {
                 out[0] = state.code();
                 Ok(())
@@ -119,13 +134,17 @@ impl OutgoingPayload {
                 next_offset += 1;
                 LittleEndian::write_u16(&mut out[next_offset..next_offset + 2], asdu.len() as u16);
                 next_offset += 2;
-                out[next_offset..next_offset + asdu.len()].clone_from_slice(&asdu);
+                out[next_offset..next_offset + asdu.len()].clone_from_slice(asdu);
                 next_offset += asdu.len();
                 out[next_offset] = 0x4;
                 next_offset += 1;
                 out[next_offset] = *radius;
                 Ok(())
             }
+            OutgoingPayload::Raw(payload) => {
+                out[0..payload.len()].clone_from_slice(payload);
+                Ok(())
+            }
         }
     }
 }
@@ -138,11 +157,27 @@ pub struct OutgoingMessage {
 }
 
 impl OutgoingMessage {
+    /// Reads a parameter. The network key is read at index 0, the key in use.
     pub fn new_read_parameter(seq: u8, parameter: ParameterCode) -> Self {
+        let argument = match parameter {
+            ParameterCode::NetworkKey => vec![0],
+            _ => Vec::new(),
+        };
+        OutgoingMessage::new_read_parameter_with_argument(seq, parameter, argument)
+    }
+
+    pub fn new_read_parameter_with_argument(
+        seq: u8,
+        parameter: ParameterCode,
+        argument: Vec<u8>,
+    ) -> Self {
         OutgoingMessage {
             command: CommandCode::ReadParameter,
             seq,
-            payload: OutgoingPayload::ReadParameter { parameter },
+            payload: OutgoingPayload::ReadParameter {
+                parameter,
+                argument,
+            },
         }
     }
 
@@ -162,6 +197,25 @@ impl OutgoingMessage {
         }
     }
 
+    /// Sends any command with the given payload. Known command codes are
+    /// answered as usual.
+    pub fn new_raw(seq: u8, command: u8, payload: Vec<u8>) -> Self {
+        OutgoingMessage {
+            command: CommandCode::from_code(command).unwrap_or(CommandCode::Unknown(command)),
+            seq,
+            payload: OutgoingPayload::Raw(payload),
+        }
+    }
+
+    /// Reads the version of the firmware.
+    pub fn new_version(seq: u8) -> Self {
+        OutgoingMessage {
+            command: CommandCode::Version,
+            seq,
+            payload: OutgoingPayload::Version,
+        }
+    }
+
     pub fn new_change_network_state(seq: u8, state: NetworkStateCode) -> Self {
         OutgoingMessage {
             command: CommandCode::ChangeNetworkState,
@@ -178,6 +232,7 @@ impl OutgoingMessage {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new_aps_data_request(
         seq: u8,
         request_id: u8,
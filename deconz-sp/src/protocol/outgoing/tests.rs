@@ -327,3 +327,112 @@ fn encode_valid_aps_data_confirm() {
         }
     }
 }
+
+#[test]
+fn encode_valid_read_network_key() {
+    let request = OutgoingMessage::new_read_parameter(10, ParameterCode::NetworkKey);
+    let mut output = [0; 32];
+    let len = request.write(&mut output).expect("Cannot write request");
+    assert_eq!(len, 9, "Invalid frame len");
+    assert_eq!(
+        LittleEndian::read_u16(&output[5..7]),
+        2,
+        "Invalid payload len"
+    );
+    assert_eq!(&output[7..9], &[ParameterCode::NetworkKey.code(), 0]);
+}
+
+#[test]
+fn encode_valid_write_network_key() {
+    let request = OutgoingMessage::new_write_parameter(
+        10,
+        ParameterCode::NetworkKey,
+        ParameterValue::NetworkKey {
+            index: 0,
+            key: [0xab; 16],
+        },
+    );
+    let mut output = [0; 32];
+    let len = request.write(&mut output).expect("Cannot write request");
+    assert_eq!(len, 25, "Invalid frame len");
+    assert_eq!(
+        LittleEndian::read_u16(&output[5..7]),
+        18,
+        "Invalid payload len"
+    );
+    assert_eq!(output[7], ParameterCode::NetworkKey.code());
+    assert_eq!(output[8], 0, "Invalid key index");
+    assert_eq!(&output[9..25], &[0xab; 16]);
+}
+
+#[test]
+fn encode_valid_write_permit_join() {
+    let request =
+        OutgoingMessage::new_write_parameter(10, ParameterCode::PermitJoin, ParameterValue::U8(60));
+    let mut output = [0; 32];
+    let len = request.write(&mut output).expect("Cannot write request");
+    assert_eq!(len, 9, "Invalid frame len");
+    assert_eq!(&output[7..9], &[0x21, 60]);
+}
+
+#[test]
+fn encode_valid_write_link_key() {
+    let request = OutgoingMessage::new_write_parameter(
+        10,
+        ParameterCode::LinkKey,
+        ParameterValue::LinkKey {
+            ieee_address: 0x0015_8d00_0123_4567,
+            key: [0x5a; 16],
+        },
+    );
+    let mut output = [0; 40];
+    let len = request.write(&mut output).expect("Cannot write request");
+    assert_eq!(len, 32, "Invalid frame len");
+    assert_eq!(output[7], ParameterCode::LinkKey.code());
+    assert_eq!(
+        LittleEndian::read_u64(&output[8..16]),
+        0x0015_8d00_0123_4567
+    );
+    assert_eq!(&output[16..32], &[0x5a; 16]);
+}
+
+#[test]
+fn encode_valid_write_group_address() {
+    let request = OutgoingMessage::new_write_parameter(
+        10,
+        ParameterCode::GroupAddress,
+        ParameterValue::GroupAddress {
+            index: 2,
+            group_id: 0x1234,
+        },
+    );
+    let mut output = [0; 16];
+    let len = request.write(&mut output).expect("Cannot write request");
+    assert_eq!(len, 11, "Invalid frame len");
+    assert_eq!(&output[7..11], &[0x20, 0x02, 0x34, 0x12]);
+}
+
+#[test]
+fn encode_valid_version() {
+    let request = OutgoingMessage::new_version(5);
+    let mut output = [0; 32];
+    let len = request.write(&mut output).unwrap();
+    assert_eq!(
+        &output[..len],
+        &[0xd, 0x5, 0x0, 0x9, 0x0, 0x0, 0x0, 0x0, 0x0]
+    );
+}
+
+#[test]
+fn encode_valid_raw() {
+    let request = OutgoingMessage::new_raw(5, 0x2a, vec![0x2, 0x0, 0x1, 0x2]);
+    assert_eq!(request.command, CommandCode::Unknown(0x2a));
+    let mut output = [0; 32];
+    let len = request.write(&mut output).unwrap();
+    assert_eq!(
+        &output[..len],
+        &[0x2a, 0x5, 0x0, 0x9, 0x0, 0x2, 0x0, 0x1, 0x2]
+    );
+    let request = OutgoingMessage::new_raw(6, 0x7, vec![0x0, 0x0, 0x0]);
+    assert_eq!(request.command, CommandCode::DeviceState);
+}
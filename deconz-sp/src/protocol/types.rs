@@ -1,6 +1,8 @@
+use byteorder::{ByteOrder, LittleEndian};
+
 use crate::protocol::constants::DestinationMode;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Address {
     Group(u16),
     NWK(u16, u8),
@@ -17,14 +19,41 @@ impl Address {
     }
 }
 
-#[derive(Debug)]
+/// Address of a device at the MAC layer, which has no endpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceAddress {
+    NWK(u16),
+    IEEE(u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParameterValue {
     U8(u8),
     U16(u16),
     U32(u32),
     U64(u64),
+    /// Network key, with its key sequence number. The key is not sent to the
+    /// other devices of the network when it is written.
+    NetworkKey {
+        index: u8,
+        key: [u8; 16],
+    },
+    /// Trust center link key of a device.
+    LinkKey {
+        ieee_address: u64,
+        key: [u8; 16],
+    },
+    /// Entry of the APS group table of the coordinator. The entry is cleared
+    /// with the group `UNUSED_GROUP`.
+    GroupAddress {
+        index: u8,
+        group_id: u16,
+    },
 }
 
+/// Group of the unused entries of the APS group table.
+pub const UNUSED_GROUP: u16 = 0xffff;
+
 impl ParameterValue {
     pub fn length(&self) -> usize {
         match self {
@@ -32,15 +61,49 @@ impl ParameterValue {
             ParameterValue::U16(_) => 2,
             ParameterValue::U32(_) => 4,
             ParameterValue::U64(_) => 8,
+            ParameterValue::NetworkKey { .. } => 17,
+            ParameterValue::LinkKey { .. } => 24,
+            ParameterValue::GroupAddress { .. } => 3,
         }
     }
 
+    /// Returns the value of integer parameters, and 0 for the keys and the
+    /// group table entries. See `as_u64`.
     pub fn u64(&self) -> u64 {
+        self.as_u64().unwrap_or(0)
+    }
+
+    /// Returns the value of integer parameters, or `None` for the keys and the
+    /// group table entries.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            ParameterValue::U8(value) => Some(*value as u64),
+            ParameterValue::U16(value) => Some(*value as u64),
+            ParameterValue::U32(value) => Some(*value as u64),
+            ParameterValue::U64(value) => Some(*value),
+            ParameterValue::NetworkKey { .. }
+            | ParameterValue::LinkKey { .. }
+            | ParameterValue::GroupAddress { .. } => None,
+        }
+    }
+
+    pub fn write(&self, out: &mut [u8]) {
         match self {
-            ParameterValue::U8(value) => *value as u64,
-            ParameterValue::U16(value) => *value as u64,
-            ParameterValue::U32(value) => *value as u64,
-            ParameterValue::U64(value) => *value,
+            ParameterValue::NetworkKey { index, key } => {
+                out[0] = *index;
+                out[1..17].clone_from_slice(key);
+            }
+            ParameterValue::LinkKey { ieee_address, key } => {
+                LittleEndian::write_u64(out, *ieee_address);
+                out[8..24].clone_from_slice(key);
+            }
+            ParameterValue::GroupAddress { index, group_id } => {
+                out[0] = *index;
+                LittleEndian::write_u16(&mut out[1..3], *group_id);
+            }
+            value => {
+                LittleEndian::write_uint(out, value.u64(), value.length());
+            }
         }
     }
 
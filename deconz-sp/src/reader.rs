@@ -0,0 +1,66 @@
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::Error;
+
+/// Little-endian cursor over a received frame, used by the ZCL and ZDO decoders.
+pub(crate) struct Reader<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Reader { input, offset: 0 }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.input.len() - self.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.remaining() < len {
+            return Err(Error::Decoding("Unexpected end of frame"));
+        }
+        let bytes = &self.input[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    pub fn rest(&mut self) -> &'a [u8] {
+        let bytes = &self.input[self.offset..];
+        self.offset = self.input.len();
+        bytes
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn i8(&mut self) -> Result<i8, Error> {
+        Ok(self.u8()? as i8)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, Error> {
+        Ok(LittleEndian::read_u16(self.bytes(2)?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        Ok(LittleEndian::read_u32(self.bytes(4)?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, Error> {
+        Ok(LittleEndian::read_u64(self.bytes(8)?))
+    }
+
+    pub fn uint(&mut self, len: usize) -> Result<u64, Error> {
+        Ok(LittleEndian::read_uint(self.bytes(len)?, len))
+    }
+
+    pub fn int(&mut self, len: usize) -> Result<i64, Error> {
+        Ok(LittleEndian::read_int(self.bytes(len)?, len))
+    }
+}
@@ -0,0 +1,524 @@
+//! Devices of the network, as learnt from the traffic received by the client.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+use crate::zcl::{basic, AttributeValue};
+use crate::zdo::{BindingEntry, LogicalType, MacCapabilities, NodeDescriptor, SimpleDescriptor};
+use crate::Event;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum InterviewState {
+    NotStarted,
+    Running,
+    Completed,
+    /// Retried when the device is heard from again.
+    Failed,
+}
+
+/// Latest value received for an attribute of the device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedAttribute {
+    pub value: AttributeValue,
+    /// When the value was last read or reported, changed or not.
+    pub updated: SystemTime,
+    /// When the value last changed.
+    pub changed: SystemTime,
+}
+
+impl CachedAttribute {
+    /// Whether the value was received more than `max_age` before `now`.
+    pub fn is_older_than(&self, max_age: Duration, now: SystemTime) -> bool {
+        now.duration_since(self.updated)
+            .map(|age| age > max_age)
+            .unwrap_or(false)
+    }
+}
+
+/// Cached attribute values, by endpoint, cluster and attribute.
+pub type AttributeCache = BTreeMap<(u8, u16, u16), CachedAttribute>;
+
+/// Cached attributes are saved as a list, as JSON keys cannot be tuples.
+mod attribute_cache {
+    use super::{AttributeCache, CachedAttribute};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    type Entry = (u8, u16, u16, CachedAttribute);
+
+    pub fn serialize<S: Serializer>(
+        attributes: &AttributeCache,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let entries: Vec<_> = attributes
+            .iter()
+            .map(|(&(endpoint, cluster_id, attribute_id), cached)| {
+                (endpoint, cluster_id, attribute_id, cached)
+            })
+            .collect();
+        entries.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<AttributeCache, D::Error> {
+        let entries = Vec::<Entry>::deserialize(deserializer)?;
+        Ok(entries
+            .into_iter()
+            .map(|(endpoint, cluster_id, attribute_id, cached)| {
+                ((endpoint, cluster_id, attribute_id), cached)
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Device {
+    pub ieee_address: u64,
+    pub nwk_address: u16,
+    pub capabilities: Option<MacCapabilities>,
+    pub node_descriptor: Option<NodeDescriptor>,
+    /// Simple descriptors, by endpoint.
+    pub endpoints: BTreeMap<u8, SimpleDescriptor>,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    /// Power source attribute of the Basic cluster (0x01 mains, 0x03 battery...).
+    pub power_source: Option<u8>,
+    pub sw_build: Option<String>,
+    pub interview: InterviewState,
+    /// Bindings created on the device by the client.
+    pub bindings: Vec<BindingEntry>,
+    /// Groups the endpoints of the device are members of, as (endpoint,
+    /// group) pairs: the Groups cluster works per endpoint.
+    pub groups: BTreeSet<(u8, u16)>,
+    pub lqi: Option<u8>,
+    pub rssi: Option<i8>,
+    pub last_seen: Option<SystemTime>,
+    /// Latest attribute values, saved with the device when they change.
+    #[serde(default, with = "attribute_cache")]
+    pub attributes: AttributeCache,
+}
+
+impl Device {
+    pub fn new(ieee_address: u64, nwk_address: u16) -> Self {
+        Device {
+            ieee_address,
+            nwk_address,
+            capabilities: None,
+            node_descriptor: None,
+            endpoints: BTreeMap::new(),
+            manufacturer: None,
+            model: None,
+            power_source: None,
+            sw_build: None,
+            interview: InterviewState::NotStarted,
+            bindings: Vec::new(),
+            groups: BTreeSet::new(),
+            lqi: None,
+            rssi: None,
+            last_seen: None,
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Whether the node descriptor of the device tells it is a router.
+    pub fn is_router(&self) -> bool {
+        self.node_descriptor
+            .as_ref()
+            .map(|descriptor| descriptor.logical_type == LogicalType::Router)
+            .unwrap_or(false)
+    }
+
+    /// Whether the device turns its receiver off while idle. Frames sent to it
+    /// are held by its parent until it polls.
+    pub fn is_sleepy(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .or_else(|| {
+                self.node_descriptor
+                    .as_ref()
+                    .map(|descriptor| &descriptor.mac_capabilities)
+            })
+            .map(|capabilities| !capabilities.rx_on_when_idle)
+            .unwrap_or(false)
+    }
+
+    pub fn attribute(
+        &self,
+        endpoint: u8,
+        cluster_id: u16,
+        attribute_id: u16,
+    ) -> Option<&CachedAttribute> {
+        self.attributes.get(&(endpoint, cluster_id, attribute_id))
+    }
+
+    /// Returns the endpoints implementing the cluster as a server.
+    pub fn endpoints_with_cluster(&self, cluster_id: u16) -> Vec<u8> {
+        self.endpoints
+            .values()
+            .filter(|descriptor| descriptor.input_clusters.contains(&cluster_id))
+            .map(|descriptor| descriptor.endpoint)
+            .collect()
+    }
+}
+
+/// Devices by IEEE address, with the NWK address index.
+///
+/// The update methods return whether the known state of the device changed, so
+/// the caller can emit the matching events. Reception statistics (`lqi`, `rssi`,
+/// `last_seen`) are not reported as changes.
+#[derive(Debug, Default)]
+pub struct DeviceRegistry {
+    devices: HashMap<u64, Device>,
+    ieee_addresses: HashMap<u16, u64>,
+    /// NWK addresses of unknown devices whose IEEE address is being looked up.
+    lookups: HashSet<u16>,
+}
+
+impl DeviceRegistry {
+    pub fn new() -> Self {
+        DeviceRegistry::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    pub fn get(&self, ieee_address: u64) -> Option<&Device> {
+        self.devices.get(&ieee_address)
+    }
+
+    pub fn get_by_nwk(&self, nwk_address: u16) -> Option<&Device> {
+        self.ieee_address(nwk_address)
+            .and_then(|ieee_address| self.devices.get(&ieee_address))
+    }
+
+    pub fn ieee_address(&self, nwk_address: u16) -> Option<u64> {
+        self.ieee_addresses.get(&nwk_address).cloned()
+    }
+
+    pub fn nwk_address(&self, ieee_address: u64) -> Option<u16> {
+        self.devices
+            .get(&ieee_address)
+            .map(|device| device.nwk_address)
+    }
+
+    pub fn devices(&self) -> impl Iterator<Item = &Device> {
+        self.devices.values()
+    }
+
+    /// Returns the devices implementing the cluster as a server on at least one
+    /// endpoint.
+    pub fn devices_with_cluster(&self, cluster_id: u16) -> impl Iterator<Item = &Device> {
+        self.devices
+            .values()
+            .filter(move |device| !device.endpoints_with_cluster(cluster_id).is_empty())
+    }
+
+    /// Removes the device which had the NWK address now given to another
+    /// device, and returns it: it left the network.
+    pub fn release_address(&mut self, ieee_address: u64, nwk_address: u16) -> Option<Device> {
+        match self.ieee_addresses.get(&nwk_address) {
+            Some(previous) if *previous != ieee_address => {
+                let previous = *previous;
+                if self.devices.get(&previous)?.nwk_address == nwk_address {
+                    self.remove(previous)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Records the NWK address of a device. Returns `DeviceAdded` for a new
+    /// device, and `NwkAddressChanged` when a known device got a new address.
+    /// A device which had the address is removed, see `release_address`.
+    pub fn update_address(&mut self, ieee_address: u64, nwk_address: u16) -> Option<Event> {
+        self.lookups.remove(&nwk_address);
+        self.release_address(ieee_address, nwk_address);
+        self.ieee_addresses.insert(nwk_address, ieee_address);
+        match self.devices.get_mut(&ieee_address) {
+            Some(device) if device.nwk_address == nwk_address => None,
+            Some(device) => {
+                let old_nwk = device.nwk_address;
+                device.nwk_address = nwk_address;
+                if self.ieee_addresses.get(&old_nwk) == Some(&ieee_address) {
+                    self.ieee_addresses.remove(&old_nwk);
+                }
+                Some(Event::NwkAddressChanged {
+                    ieee: ieee_address,
+                    old_nwk,
+                    new_nwk: nwk_address,
+                })
+            }
+            None => {
+                self.devices
+                    .insert(ieee_address, Device::new(ieee_address, nwk_address));
+                Some(Event::DeviceAdded {
+                    ieee: ieee_address,
+                    nwk: nwk_address,
+                })
+            }
+        }
+    }
+
+    /// Adds a device loaded from storage. An interrupted interview is
+    /// considered failed, so it is retried.
+    pub fn insert(&mut self, mut device: Device) {
+        if device.interview == InterviewState::Running {
+            device.interview = InterviewState::Failed;
+        }
+        self.ieee_addresses
+            .insert(device.nwk_address, device.ieee_address);
+        self.devices.insert(device.ieee_address, device);
+    }
+
+    /// Removes the device, and returns it.
+    pub fn remove(&mut self, ieee_address: u64) -> Option<Device> {
+        let device = self.devices.remove(&ieee_address)?;
+        if self.ieee_addresses.get(&device.nwk_address) == Some(&ieee_address) {
+            self.ieee_addresses.remove(&device.nwk_address);
+        }
+        Some(device)
+    }
+
+    /// Removes the device reachable on the NWK address, and returns it.
+    pub fn remove_by_nwk(&mut self, nwk_address: u16) -> Option<Device> {
+        self.ieee_addresses
+            .remove(&nwk_address)
+            .and_then(|ieee_address| self.devices.remove(&ieee_address))
+    }
+
+    /// Records the reception of a frame from the device. Returns `false` if the
+    /// device is unknown.
+    pub fn seen(&mut self, nwk_address: u16, lqi: u8, rssi: i8, at: SystemTime) -> bool {
+        match self.device_by_nwk_mut(nwk_address) {
+            Some(device) => {
+                device.lqi = Some(lqi);
+                device.rssi = Some(rssi);
+                device.last_seen = Some(at);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if the IEEE address of this unknown NWK address must be
+    /// looked up, i.e. no lookup is already running.
+    pub(crate) fn start_lookup(&mut self, nwk_address: u16) -> bool {
+        !self.ieee_addresses.contains_key(&nwk_address) && self.lookups.insert(nwk_address)
+    }
+
+    pub(crate) fn end_lookup(&mut self, nwk_address: u16) {
+        self.lookups.remove(&nwk_address);
+    }
+
+    pub fn set_capabilities(&mut self, ieee_address: u64, capabilities: MacCapabilities) -> bool {
+        match self.devices.get_mut(&ieee_address) {
+            Some(device) => replace(&mut device.capabilities, Some(capabilities)),
+            None => false,
+        }
+    }
+
+    pub fn set_node_descriptor(&mut self, nwk_address: u16, descriptor: NodeDescriptor) -> bool {
+        match self.device_by_nwk_mut(nwk_address) {
+            Some(device) => {
+                let capabilities = descriptor.mac_capabilities;
+                replace(&mut device.node_descriptor, Some(descriptor))
+                    | replace(&mut device.capabilities, Some(capabilities))
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_simple_descriptor(
+        &mut self,
+        nwk_address: u16,
+        descriptor: SimpleDescriptor,
+    ) -> bool {
+        match self.device_by_nwk_mut(nwk_address) {
+            Some(device) => {
+                let changed = device.endpoints.get(&descriptor.endpoint) != Some(&descriptor);
+                device.endpoints.insert(descriptor.endpoint, descriptor);
+                changed
+            }
+            None => false,
+        }
+    }
+
+    /// Records an attribute of the Basic cluster read from, or reported by, the
+    /// device.
+    pub fn set_basic_attribute(
+        &mut self,
+        nwk_address: u16,
+        attribute_id: u16,
+        value: &AttributeValue,
+    ) -> bool {
+        let device = match self.device_by_nwk_mut(nwk_address) {
+            Some(device) => device,
+            None => return false,
+        };
+        let field = match attribute_id {
+            basic::MANUFACTURER_NAME => &mut device.manufacturer,
+            basic::MODEL_IDENTIFIER => &mut device.model,
+            basic::SW_BUILD_ID => &mut device.sw_build,
+            basic::POWER_SOURCE => {
+                return match value.as_u64() {
+                    Some(power_source) => {
+                        replace(&mut device.power_source, Some(power_source as u8))
+                    }
+                    None => false,
+                }
+            }
+            _ => return false,
+        };
+        match value.as_str() {
+            Some(value) => replace(field, Some(value.trim_end_matches('\0').to_string())),
+            None => false,
+        }
+    }
+
+    /// Records an attribute read from, or reported by, the device. Returns
+    /// `AttributeChanged` if the value differs from the cached one.
+    pub fn set_attribute(
+        &mut self,
+        nwk_address: u16,
+        endpoint: u8,
+        cluster_id: u16,
+        attribute_id: u16,
+        value: AttributeValue,
+        at: SystemTime,
+    ) -> Option<Event> {
+        let device = self.device_by_nwk_mut(nwk_address)?;
+        let key = (endpoint, cluster_id, attribute_id);
+        match device.attributes.get_mut(&key) {
+            Some(cached) if cached.value.same_as(&value) => {
+                cached.updated = at;
+                return None;
+            }
+            _ => {}
+        }
+        device.attributes.insert(
+            key,
+            CachedAttribute {
+                value: value.clone(),
+                updated: at,
+                changed: at,
+            },
+        );
+        Some(Event::AttributeChanged {
+            ieee: device.ieee_address,
+            endpoint,
+            cluster_id,
+            attribute_id,
+            value,
+        })
+    }
+
+    /// Sets the interview state of the device. Returns `false` if the device is
+    /// unknown.
+    pub fn set_interview(&mut self, ieee_address: u64, state: InterviewState) -> bool {
+        match self.devices.get_mut(&ieee_address) {
+            Some(device) => {
+                device.interview = state;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn add_binding(&mut self, entry: BindingEntry) -> bool {
+        match self.devices.get_mut(&entry.source_address) {
+            Some(device) if !device.bindings.contains(&entry) => {
+                device.bindings.push(entry);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn remove_binding(&mut self, entry: &BindingEntry) -> bool {
+        match self.devices.get_mut(&entry.source_address) {
+            Some(device) => {
+                let count = device.bindings.len();
+                device.bindings.retain(|binding| binding != entry);
+                device.bindings.len() != count
+            }
+            None => false,
+        }
+    }
+
+    /// Records the membership of the device in the group.
+    pub fn add_group(&mut self, nwk_address: u16, endpoint: u8, group_id: u16) -> bool {
+        match self.device_by_nwk_mut(nwk_address) {
+            Some(device) => device.groups.insert((endpoint, group_id)),
+            None => false,
+        }
+    }
+
+    pub fn remove_group(&mut self, nwk_address: u16, endpoint: u8, group_id: u16) -> bool {
+        match self.device_by_nwk_mut(nwk_address) {
+            Some(device) => device.groups.remove(&(endpoint, group_id)),
+            None => false,
+        }
+    }
+
+    /// Replaces the groups of the endpoint, as reported by the device itself.
+    pub fn set_groups(&mut self, nwk_address: u16, endpoint: u8, groups: BTreeSet<u16>) -> bool {
+        match self.device_by_nwk_mut(nwk_address) {
+            Some(device) => {
+                let groups: BTreeSet<_> = device
+                    .groups
+                    .iter()
+                    .filter(|(member, _)| *member != endpoint)
+                    .cloned()
+                    .chain(groups.into_iter().map(|group_id| (endpoint, group_id)))
+                    .collect();
+                replace(&mut device.groups, groups)
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the IEEE address and the endpoint of the members of the group.
+    pub fn group_members(&self, group_id: u16) -> Vec<(u64, u8)> {
+        let mut members: Vec<_> = self
+            .devices
+            .values()
+            .flat_map(|device| {
+                device
+                    .groups
+                    .iter()
+                    .filter(move |(_, member_of)| *member_of == group_id)
+                    .map(move |(endpoint, _)| (device.ieee_address, *endpoint))
+            })
+            .collect();
+        members.sort();
+        members
+    }
+
+    fn device_by_nwk_mut(&mut self, nwk_address: u16) -> Option<&mut Device> {
+        match self.ieee_addresses.get(&nwk_address) {
+            Some(ieee_address) => self.devices.get_mut(ieee_address),
+            None => None,
+        }
+    }
+}
+
+/// Replaces the value, and returns true if it changed.
+fn replace<T: PartialEq>(field: &mut T, value: T) -> bool {
+    if *field == value {
+        false
+    } else {
+        *field = value;
+        true
+    }
+}
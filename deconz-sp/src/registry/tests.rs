@@ -0,0 +1,338 @@
+use super::*;
+
+fn simple_descriptor(endpoint: u8, input_clusters: Vec<u16>) -> SimpleDescriptor {
+    SimpleDescriptor {
+        endpoint,
+        profile_id: 0x0104,
+        device_id: 0x0100,
+        device_version: 1,
+        input_clusters,
+        output_clusters: vec![],
+    }
+}
+
+#[test]
+fn add_device() {
+    let mut registry = DeviceRegistry::new();
+    match registry.update_address(0x0011_2233_4455_6677, 0x1234) {
+        Some(Event::DeviceAdded { ieee, nwk }) => {
+            assert_eq!(ieee, 0x0011_2233_4455_6677);
+            assert_eq!(nwk, 0x1234);
+        }
+        other => panic!("Unexpected event: {:?}", other),
+    }
+    assert!(registry
+        .update_address(0x0011_2233_4455_6677, 0x1234)
+        .is_none());
+    assert_eq!(registry.len(), 1);
+    assert_eq!(registry.ieee_address(0x1234), Some(0x0011_2233_4455_6677));
+    assert_eq!(registry.nwk_address(0x0011_2233_4455_6677), Some(0x1234));
+}
+
+#[test]
+fn change_nwk_address() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    match registry.update_address(0x0011_2233_4455_6677, 0x5678) {
+        Some(Event::NwkAddressChanged {
+            ieee,
+            old_nwk,
+            new_nwk,
+        }) => {
+            assert_eq!(ieee, 0x0011_2233_4455_6677);
+            assert_eq!(old_nwk, 0x1234);
+            assert_eq!(new_nwk, 0x5678);
+        }
+        other => panic!("Unexpected event: {:?}", other),
+    }
+    assert_eq!(registry.ieee_address(0x1234), None);
+    assert!(registry.get_by_nwk(0x5678).is_some());
+}
+
+#[test]
+fn reuse_nwk_address() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    registry.update_address(0x8899_aabb_ccdd_eeff, 0x1234);
+    assert_eq!(registry.len(), 1);
+    assert_eq!(registry.ieee_address(0x1234), Some(0x8899_aabb_ccdd_eeff));
+    assert!(registry
+        .release_address(0x8899_aabb_ccdd_eeff, 0x1234)
+        .is_none());
+    let device = registry.release_address(0x0011_2233_4455_6677, 0x1234);
+    assert_eq!(
+        device.map(|device| device.ieee_address),
+        Some(0x8899_aabb_ccdd_eeff)
+    );
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn remove_device() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    let device = registry.remove_by_nwk(0x1234);
+    assert_eq!(
+        device.map(|device| device.ieee_address),
+        Some(0x0011_2233_4455_6677)
+    );
+    assert!(registry.is_empty());
+    assert!(registry.remove_by_nwk(0x1234).is_none());
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    assert!(registry.remove(0x0011_2233_4455_6677).is_some());
+    assert_eq!(registry.ieee_address(0x1234), None);
+    assert!(registry.remove(0x0011_2233_4455_6677).is_none());
+}
+
+#[test]
+fn record_reception() {
+    let mut registry = DeviceRegistry::new();
+    assert!(!registry.seen(0x1234, 0xff, -40, SystemTime::now()));
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    assert!(registry.seen(0x1234, 0xff, -40, SystemTime::now()));
+    let device = registry.get(0x0011_2233_4455_6677).unwrap();
+    assert_eq!(device.lqi, Some(0xff));
+    assert_eq!(device.rssi, Some(-40));
+    assert!(device.last_seen.is_some());
+}
+
+#[test]
+fn sleepy_device() {
+    let mut device = Device::new(0x0011_2233_4455_6677, 0x1234);
+    assert!(!device.is_sleepy());
+    device.capabilities = Some(MacCapabilities::from_byte(0x80));
+    assert!(device.is_sleepy());
+    device.capabilities = Some(MacCapabilities::from_byte(0x8e));
+    assert!(!device.is_sleepy());
+}
+
+#[test]
+fn lookup_once() {
+    let mut registry = DeviceRegistry::new();
+    assert!(registry.start_lookup(0x1234));
+    assert!(!registry.start_lookup(0x1234));
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    assert!(!registry.start_lookup(0x1234));
+}
+
+#[test]
+fn update_endpoints() {
+    let mut registry = DeviceRegistry::new();
+    assert!(!registry.set_simple_descriptor(0x1234, simple_descriptor(1, vec![0x0006])));
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    assert!(registry.set_simple_descriptor(0x1234, simple_descriptor(1, vec![0x0006])));
+    assert!(!registry.set_simple_descriptor(0x1234, simple_descriptor(1, vec![0x0006])));
+    assert!(registry.set_simple_descriptor(0x1234, simple_descriptor(2, vec![0x0402])));
+    let device = registry.get(0x0011_2233_4455_6677).unwrap();
+    assert_eq!(device.endpoints_with_cluster(0x0402), vec![2]);
+    assert_eq!(registry.devices_with_cluster(0x0006).count(), 1);
+    assert_eq!(registry.devices_with_cluster(0x0300).count(), 0);
+}
+
+#[test]
+fn update_basic_attributes() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    let model = AttributeValue::CharacterString(String::from("lumi.weather\0"));
+    assert!(registry.set_basic_attribute(0x1234, basic::MODEL_IDENTIFIER, &model));
+    assert!(!registry.set_basic_attribute(0x1234, basic::MODEL_IDENTIFIER, &model));
+    assert!(!registry.set_basic_attribute(
+        0x1234,
+        basic::MANUFACTURER_NAME,
+        &AttributeValue::Uint8(1)
+    ));
+    let device = registry.get(0x0011_2233_4455_6677).unwrap();
+    assert_eq!(device.model, Some(String::from("lumi.weather")));
+    assert_eq!(device.manufacturer, None);
+}
+
+#[test]
+fn update_power_source() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    assert!(registry.set_basic_attribute(
+        0x1234,
+        basic::POWER_SOURCE,
+        &AttributeValue::Enum8(0x03)
+    ));
+    assert_eq!(
+        registry.get(0x0011_2233_4455_6677).unwrap().power_source,
+        Some(0x03)
+    );
+}
+
+#[test]
+fn update_interview_state() {
+    let mut registry = DeviceRegistry::new();
+    assert!(!registry.set_interview(0x0011_2233_4455_6677, InterviewState::Running));
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    assert_eq!(
+        registry.get(0x0011_2233_4455_6677).unwrap().interview,
+        InterviewState::NotStarted
+    );
+    assert!(registry.set_interview(0x0011_2233_4455_6677, InterviewState::Failed));
+    assert_eq!(
+        registry.get(0x0011_2233_4455_6677).unwrap().interview,
+        InterviewState::Failed
+    );
+}
+
+#[test]
+fn update_bindings() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    let entry = BindingEntry {
+        source_address: 0x0011_2233_4455_6677,
+        source_endpoint: 1,
+        cluster_id: 0x0006,
+        destination: crate::zdo::BindingTarget::Group(0x0001),
+    };
+    assert!(registry.add_binding(entry));
+    assert!(!registry.add_binding(entry));
+    assert_eq!(
+        registry.get(0x0011_2233_4455_6677).unwrap().bindings,
+        vec![entry]
+    );
+    assert!(registry.remove_binding(&entry));
+    assert!(!registry.remove_binding(&entry));
+}
+
+#[test]
+fn insert_stored_device() {
+    let mut registry = DeviceRegistry::new();
+    let mut device = Device::new(0x0011_2233_4455_6677, 0x1234);
+    device.interview = InterviewState::Running;
+    registry.insert(device);
+    assert_eq!(
+        registry.get_by_nwk(0x1234).map(|device| device.interview),
+        Some(InterviewState::Failed)
+    );
+}
+
+#[test]
+fn update_groups() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    registry.update_address(0x8899_aabb_ccdd_eeff, 0x5678);
+    assert!(registry.add_group(0x1234, 1, 0x0001));
+    assert!(!registry.add_group(0x1234, 1, 0x0001));
+    assert!(registry.add_group(0x1234, 2, 0x0001));
+    assert!(registry.add_group(0x5678, 1, 0x0001));
+    assert!(!registry.add_group(0x9abc, 1, 0x0001));
+    assert_eq!(
+        registry.group_members(0x0001),
+        vec![
+            (0x0011_2233_4455_6677, 1),
+            (0x0011_2233_4455_6677, 2),
+            (0x8899_aabb_ccdd_eeff, 1)
+        ]
+    );
+    // Only the endpoint leaves the group
+    assert!(registry.remove_group(0x1234, 1, 0x0001));
+    assert!(!registry.remove_group(0x1234, 1, 0x0001));
+    assert_eq!(
+        registry.group_members(0x0001),
+        vec![(0x0011_2233_4455_6677, 2), (0x8899_aabb_ccdd_eeff, 1)]
+    );
+    assert!(registry.set_groups(0x5678, 1, vec![0x0002, 0x0003].into_iter().collect()));
+    assert!(!registry.set_groups(0x5678, 1, vec![0x0002, 0x0003].into_iter().collect()));
+    assert_eq!(
+        registry.group_members(0x0001),
+        vec![(0x0011_2233_4455_6677, 2)]
+    );
+    assert_eq!(
+        registry.group_members(0x0003),
+        vec![(0x8899_aabb_ccdd_eeff, 1)]
+    );
+    assert!(registry.set_groups(0x1234, 2, BTreeSet::new()));
+    assert!(registry.group_members(0x0001).is_empty());
+}
+
+#[test]
+fn cache_attributes() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    let start = SystemTime::UNIX_EPOCH;
+    let later = start + Duration::from_secs(60);
+    match registry.set_attribute(
+        0x1234,
+        1,
+        0x0402,
+        0x0000,
+        AttributeValue::Int16(2150),
+        start,
+    ) {
+        Some(Event::AttributeChanged {
+            ieee, cluster_id, ..
+        }) => {
+            assert_eq!(ieee, 0x0011_2233_4455_6677);
+            assert_eq!(cluster_id, 0x0402);
+        }
+        other => panic!("Unexpected event: {:?}", other),
+    }
+    // Repeated values are only timestamped
+    assert!(registry
+        .set_attribute(
+            0x1234,
+            1,
+            0x0402,
+            0x0000,
+            AttributeValue::Int16(2150),
+            later
+        )
+        .is_none());
+    let device = registry.get(0x0011_2233_4455_6677).unwrap();
+    let cached = device.attribute(1, 0x0402, 0x0000).unwrap();
+    assert_eq!(cached.changed, start);
+    assert_eq!(cached.updated, later);
+    assert!(!cached.is_older_than(Duration::from_secs(30), later + Duration::from_secs(30)));
+    assert!(cached.is_older_than(Duration::from_secs(30), later + Duration::from_secs(31)));
+    assert!(registry
+        .set_attribute(
+            0x1234,
+            1,
+            0x0402,
+            0x0000,
+            AttributeValue::Int16(2200),
+            later
+        )
+        .is_some());
+    assert!(registry
+        .set_attribute(
+            0x9abc,
+            1,
+            0x0402,
+            0x0000,
+            AttributeValue::Int16(2200),
+            later
+        )
+        .is_none());
+}
+
+#[test]
+fn cache_invalid_float_attributes() {
+    let mut registry = DeviceRegistry::new();
+    registry.update_address(0x0011_2233_4455_6677, 0x1234);
+    let start = SystemTime::UNIX_EPOCH;
+    let later = start + Duration::from_secs(60);
+    let nan = AttributeValue::Single(f32::NAN);
+    assert!(registry
+        .set_attribute(0x1234, 2, 0x000c, 0x0055, nan.clone(), start)
+        .is_some());
+    // A repeated invalid reading is not a change
+    assert!(registry
+        .set_attribute(0x1234, 2, 0x000c, 0x0055, nan, later)
+        .is_none());
+    let device = registry.get(0x0011_2233_4455_6677).unwrap();
+    assert_eq!(device.attribute(2, 0x000c, 0x0055).unwrap().changed, start);
+    assert!(registry
+        .set_attribute(
+            0x1234,
+            2,
+            0x000c,
+            0x0055,
+            AttributeValue::Single(21.5),
+            later
+        )
+        .is_some());
+}
@@ -0,0 +1,101 @@
+//! Attribute reports received from the devices. See `Client::attribute_reports`.
+
+use futures::{try_ready, Async, Poll, Stream};
+use log::*;
+
+use crate::bus::{Received, Receiver};
+
+use crate::zcl::AttributeValue;
+
+#[cfg(test)]
+mod tests;
+
+/// An attribute value reported by a device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub ieee_address: u64,
+    pub endpoint: u8,
+    pub cluster_id: u16,
+    pub attribute_id: u16,
+    pub value: AttributeValue,
+}
+
+/// Selects reports. Fields left to `None` match any report.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportFilter {
+    pub ieee_address: Option<u64>,
+    pub endpoint: Option<u8>,
+    pub cluster_id: Option<u16>,
+    pub attribute_id: Option<u16>,
+}
+
+impl ReportFilter {
+    pub fn matches(&self, report: &Report) -> bool {
+        fn matches<T: PartialEq>(filter: Option<T>, value: T) -> bool {
+            filter.is_none() || filter == Some(value)
+        }
+        matches(self.ieee_address, report.ieee_address)
+            && matches(self.endpoint, report.endpoint)
+            && matches(self.cluster_id, report.cluster_id)
+            && matches(self.attribute_id, report.attribute_id)
+    }
+}
+
+/// Stream of the reports matching a filter. Each stream receives its own copy
+/// of the reports. The streams are bounded like the event streams: the oldest
+/// reports are dropped when the stream does not keep up.
+pub struct AttributeReports {
+    receiver: Receiver<Report>,
+    filter: ReportFilter,
+}
+
+impl AttributeReports {
+    pub(crate) fn new(receiver: Receiver<Report>) -> Self {
+        AttributeReports {
+            receiver,
+            filter: ReportFilter::default(),
+        }
+    }
+
+    /// Only keeps the reports of the device.
+    pub fn device(mut self, ieee_address: u64) -> Self {
+        self.filter.ieee_address = Some(ieee_address);
+        self
+    }
+
+    pub fn endpoint(mut self, endpoint: u8) -> Self {
+        self.filter.endpoint = Some(endpoint);
+        self
+    }
+
+    pub fn cluster(mut self, cluster_id: u16) -> Self {
+        self.filter.cluster_id = Some(cluster_id);
+        self
+    }
+
+    pub fn attribute(mut self, attribute_id: u16) -> Self {
+        self.filter.attribute_id = Some(attribute_id);
+        self
+    }
+}
+
+impl Stream for AttributeReports {
+    type Item = Report;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Report>, ()> {
+        loop {
+            match try_ready!(self.receiver.poll()) {
+                Some(Received::Item(report)) => {
+                    if self.filter.matches(&report) {
+                        return Ok(Async::Ready(Some(report)));
+                    }
+                }
+                Some(Received::Lagged(missed)) => {
+                    warn!("Attribute report stream lagged, {} reports dropped", missed)
+                }
+                None => return Ok(Async::Ready(None)),
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::*;
+use crate::bus::Bus;
+use futures::Future;
+
+fn report(ieee_address: u64, cluster_id: u16) -> Report {
+    Report {
+        ieee_address,
+        endpoint: 1,
+        cluster_id,
+        attribute_id: 0x0000,
+        value: AttributeValue::Int16(2150),
+    }
+}
+
+#[test]
+fn filter_reports() {
+    let filter = ReportFilter {
+        cluster_id: Some(0x0402),
+        ..Default::default()
+    };
+    assert!(filter.matches(&report(1, 0x0402)));
+    assert!(!filter.matches(&report(1, 0x0405)));
+    assert!(ReportFilter::default().matches(&report(1, 0x0405)));
+}
+
+#[test]
+fn stream_only_yields_matching_reports() {
+    let bus = Bus::new(4);
+    let receiver = bus.receiver();
+    for report in &[report(1, 0x0402), report(2, 0x0402), report(2, 0x0405)] {
+        bus.publish(report.clone());
+    }
+    bus.close();
+    let reports = AttributeReports::new(receiver)
+        .device(2)
+        .cluster(0x0402)
+        .collect()
+        .wait()
+        .unwrap();
+    assert_eq!(reports, vec![report(2, 0x0402)]);
+}
+
+#[test]
+fn slow_stream_drops_oldest_reports() {
+    let bus = Bus::new(2);
+    let receiver = bus.receiver();
+    for ieee_address in 1..=4 {
+        bus.publish(report(ieee_address, 0x0402));
+    }
+    bus.close();
+    let reports = AttributeReports::new(receiver).collect().wait().unwrap();
+    assert_eq!(reports, vec![report(3, 0x0402), report(4, 0x0402)]);
+}
@@ -0,0 +1,88 @@
+//! Serde helpers for values written as hexadecimal strings.
+
+/// Values written as big-endian hexadecimal strings.
+pub(crate) trait Hex: Sized {
+    fn to_hex(&self) -> String;
+    fn from_hex(hex: &str) -> Option<Self>;
+}
+
+impl Hex for u16 {
+    fn to_hex(&self) -> String {
+        format!("{:04x}", self)
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        u16::from_str_radix(hex, 16).ok()
+    }
+}
+
+impl Hex for u64 {
+    fn to_hex(&self) -> String {
+        format!("{:016x}", self)
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        // IEEE addresses are sometimes written with separators
+        u64::from_str_radix(&hex.replace(':', ""), 16).ok()
+    }
+}
+
+impl Hex for [u8; 16] {
+    fn to_hex(&self) -> String {
+        self.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 32 || !hex.is_ascii() {
+            return None;
+        }
+        let mut key = [0; 16];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+        }
+        Some(key)
+    }
+}
+
+pub(crate) mod hex {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Hex;
+
+    pub fn serialize<T: Hex, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_hex())
+    }
+
+    pub fn deserialize<'de, T: Hex, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        T::from_hex(&hex).ok_or_else(|| D::Error::custom(format!("Invalid hex value: {}", hex)))
+    }
+}
+
+pub(crate) mod hex_option {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Hex;
+
+    pub fn serialize<T: Hex, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::hex::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T: Hex, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(hex) => T::from_hex(&hex)
+                .map(Some)
+                .ok_or_else(|| serde::de::Error::custom(format!("Invalid hex value: {}", hex))),
+            None => Ok(None),
+        }
+    }
+}
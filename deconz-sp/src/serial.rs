@@ -0,0 +1,30 @@
+//! Serial device carrying the frames of the protocol.
+
+use log::*;
+use std::path::Path;
+use tokio::codec::Framed;
+
+use crate::protocol::Codec;
+use crate::Error;
+
+/// Baud rate of the ConBee and the RaspBee.
+pub const DEFAULT_BAUD_RATE: u32 = 38400;
+
+pub(crate) type SerialFrames = Framed<tokio_serial::Serial, Codec>;
+
+pub(crate) fn open(path: &Path, baud_rate: u32) -> Result<SerialFrames, Error> {
+    Ok(Framed::new(open_port(path, baud_rate)?, Codec::new()))
+}
+
+pub(crate) fn open_port(path: &Path, baud_rate: u32) -> Result<tokio_serial::Serial, Error> {
+    debug!("Connect to device {}...", path.display());
+    let serial = tokio_serial::Serial::from_path(
+        path,
+        &mio_serial::SerialPortSettings {
+            baud_rate,
+            ..mio_serial::SerialPortSettings::default()
+        },
+    )?;
+    debug!("Connected to device");
+    Ok(serial)
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::Storage;
+use crate::registry::Device;
+use crate::Error;
+
+#[derive(Serialize, Deserialize, Default)]
+struct Database {
+    devices: Vec<Device>,
+}
+
+/// Keeps the devices in a JSON file, rewritten on each change.
+pub struct JsonStorage {
+    path: PathBuf,
+    devices: BTreeMap<u64, Device>,
+}
+
+impl JsonStorage {
+    /// Opens the file, which is created on the first save if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let database = match fs::read(&path) {
+            Ok(content) => serde_json::from_slice(&content)?,
+            Err(ref err) if err.kind() == ErrorKind::NotFound => Database::default(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(JsonStorage {
+            path,
+            devices: database
+                .devices
+                .into_iter()
+                .map(|device| (device.ieee_address, device))
+                .collect(),
+        })
+    }
+
+    fn write(&self) -> Result<(), Error> {
+        let database = Database {
+            devices: self.devices.values().cloned().collect(),
+        };
+        // Written aside then renamed, so a crash never leaves a truncated file
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, serde_json::to_vec_pretty(&database)?)?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load_devices(&mut self) -> Result<Vec<Device>, Error> {
+        Ok(self.devices.values().cloned().collect())
+    }
+
+    fn save_device(&mut self, device: &Device) -> Result<(), Error> {
+        self.devices.insert(device.ieee_address, device.clone());
+        self.write()
+    }
+
+    fn remove_device(&mut self, ieee_address: u64) -> Result<(), Error> {
+        if self.devices.remove(&ieee_address).is_some() {
+            self.write()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+//! Persistence of the device registry across restarts.
+
+use crate::registry::Device;
+use crate::Error;
+
+mod json;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(test)]
+mod tests;
+
+pub use json::JsonStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+
+/// Backend saving the devices known by the client: addresses, descriptors,
+/// bindings, group memberships...
+///
+/// Devices are loaded when the client starts, then saved each time they change.
+pub trait Storage: Send {
+    fn load_devices(&mut self) -> Result<Vec<Device>, Error>;
+    /// Inserts or replaces the device.
+    fn save_device(&mut self, device: &Device) -> Result<(), Error>;
+    fn remove_device(&mut self, ieee_address: u64) -> Result<(), Error>;
+}
@@ -0,0 +1,76 @@
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+use super::Storage;
+use crate::registry::Device;
+use crate::Error;
+
+/// Keeps the devices in an SQLite database, one row per device.
+///
+/// The row holds the JSON record of the device, as written by `JsonStorage`,
+/// rather than tables for the endpoints, bindings, groups and attributes:
+/// the devices are always loaded and saved whole, and new fields of `Device`
+/// need no schema migration. The IEEE and NWK addresses have their own
+/// columns, so the database can be queried by other tools.
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    /// Opens the database, creating the schema if needed.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        SqliteStorage::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens a database living in memory, mostly useful for tests.
+    pub fn open_in_memory() -> Result<Self, Error> {
+        SqliteStorage::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, Error> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS devices (
+                ieee_address INTEGER PRIMARY KEY,
+                nwk_address INTEGER NOT NULL,
+                record TEXT NOT NULL
+            );",
+        )?;
+        Ok(SqliteStorage { connection })
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_devices(&mut self) -> Result<Vec<Device>, Error> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT record FROM devices ORDER BY ieee_address")?;
+        let records = statement.query_map([], |row| row.get::<_, String>(0))?;
+        let mut devices = Vec::new();
+        for record in records {
+            devices.push(serde_json::from_str(&record?)?);
+        }
+        Ok(devices)
+    }
+
+    fn save_device(&mut self, device: &Device) -> Result<(), Error> {
+        // IEEE addresses are stored as their signed bit pattern
+        self.connection.execute(
+            "INSERT OR REPLACE INTO devices (ieee_address, nwk_address, record)
+             VALUES (?1, ?2, ?3)",
+            params![
+                device.ieee_address as i64,
+                device.nwk_address,
+                serde_json::to_string(device)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn remove_device(&mut self, ieee_address: u64) -> Result<(), Error> {
+        self.connection.execute(
+            "DELETE FROM devices WHERE ieee_address = ?1",
+            params![ieee_address as i64],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use super::*;
+use crate::registry::{CachedAttribute, InterviewState};
+use crate::zcl::AttributeValue;
+use crate::zdo::{BindingEntry, BindingTarget, SimpleDescriptor};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, UNIX_EPOCH};
+
+fn device() -> Device {
+    let mut device = Device::new(0x0011_2233_4455_6677, 0x1234);
+    device.endpoints.insert(
+        1,
+        SimpleDescriptor {
+            endpoint: 1,
+            profile_id: 0x0104,
+            device_id: 0x0302,
+            device_version: 1,
+            input_clusters: vec![0x0000, 0x0402],
+            output_clusters: vec![],
+        },
+    );
+    device.bindings.push(BindingEntry {
+        source_address: 0x0011_2233_4455_6677,
+        source_endpoint: 1,
+        cluster_id: 0x0402,
+        destination: BindingTarget::Group(0x0001),
+    });
+    device.groups.insert((1, 0x0001));
+    device.model = Some(String::from("lumi.weather"));
+    device.interview = InterviewState::Completed;
+    device
+}
+
+/// Device with cached attribute values, including the NaN of an invalid
+/// float value.
+fn device_with_attributes() -> Device {
+    let mut device = device();
+    let at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    for (key, value) in [
+        ((1, 0x0402, 0x0000), AttributeValue::Int16(2150)),
+        (
+            (1, 0x0000, 0x0005),
+            AttributeValue::CharacterString(String::from("lumi.weather")),
+        ),
+        ((2, 0x000c, 0x0055), AttributeValue::Single(f32::NAN)),
+    ]
+    .iter()
+    {
+        device.attributes.insert(
+            *key,
+            CachedAttribute {
+                value: value.clone(),
+                updated: at,
+                changed: at,
+            },
+        );
+    }
+    device
+}
+
+fn attribute_cache_round_trip(storage: &mut dyn Storage) {
+    let device = device_with_attributes();
+    storage.save_device(&device).unwrap();
+    let loaded = storage.load_devices().unwrap().remove(0);
+    assert_eq!(loaded.attributes.len(), 3);
+    assert_eq!(
+        loaded.attribute(1, 0x0402, 0x0000),
+        device.attribute(1, 0x0402, 0x0000)
+    );
+    assert_eq!(
+        loaded.attribute(1, 0x0000, 0x0005),
+        device.attribute(1, 0x0000, 0x0005)
+    );
+    match loaded
+        .attribute(2, 0x000c, 0x0055)
+        .map(|cached| &cached.value)
+    {
+        Some(AttributeValue::Single(value)) => assert!(value.is_nan()),
+        other => panic!("Unexpected value: {:?}", other),
+    }
+}
+
+fn temporary_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("deconz-sp-{}-{}.json", name, process::id()))
+}
+
+fn round_trip(storage: &mut dyn Storage) {
+    assert!(storage.load_devices().unwrap().is_empty());
+    let mut device = device();
+    storage.save_device(&device).unwrap();
+    device.nwk_address = 0x5678;
+    storage.save_device(&device).unwrap();
+    assert_eq!(storage.load_devices().unwrap(), vec![device]);
+    storage.remove_device(0x0011_2233_4455_6677).unwrap();
+    assert!(storage.load_devices().unwrap().is_empty());
+}
+
+#[test]
+fn json_round_trip() {
+    let path = temporary_path("round-trip");
+    let mut storage = JsonStorage::open(&path).unwrap();
+    round_trip(&mut storage);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn json_reload() {
+    let path = temporary_path("reload");
+    JsonStorage::open(&path)
+        .unwrap()
+        .save_device(&device())
+        .unwrap();
+    let devices = JsonStorage::open(&path).unwrap().load_devices().unwrap();
+    assert_eq!(devices, vec![device()]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn json_attribute_cache() {
+    let path = temporary_path("attributes");
+    attribute_cache_round_trip(&mut JsonStorage::open(&path).unwrap());
+    // Reopened as after a restart
+    let devices = JsonStorage::open(&path).unwrap().load_devices().unwrap();
+    assert_eq!(devices[0].attributes.len(), 3);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn json_invalid_file() {
+    let path = temporary_path("invalid");
+    fs::write(&path, b"{").unwrap();
+    assert!(JsonStorage::open(&path).is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_round_trip() {
+    let mut storage = SqliteStorage::open_in_memory().unwrap();
+    round_trip(&mut storage);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_attribute_cache() {
+    let mut storage = SqliteStorage::open_in_memory().unwrap();
+    attribute_cache_round_trip(&mut storage);
+}
@@ -0,0 +1,179 @@
+//! Map of the network, built from the neighbor and routing tables of the
+//! routers. See `Client::topology`.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::serde_hex::hex_option;
+use crate::zdo::{LogicalType, Neighbor, Relationship, Route, RouteStatus};
+use crate::Error;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Node {
+    pub nwk_address: u16,
+    /// Unknown for the coordinator, until another router lists it.
+    #[serde(with = "hex_option")]
+    pub ieee_address: Option<u64>,
+    pub device_type: Option<LogicalType>,
+    /// Depth in the network tree, 0 for the coordinator.
+    pub depth: Option<u8>,
+    /// Whether the neighbor table of the node was read.
+    pub crawled: bool,
+}
+
+impl Node {
+    fn new(nwk_address: u16) -> Self {
+        Node {
+            nwk_address,
+            ieee_address: None,
+            device_type: None,
+            depth: None,
+            crawled: false,
+        }
+    }
+}
+
+/// Entry of the neighbor table of `source`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Link {
+    pub source: u16,
+    pub target: u16,
+    /// Link quality of the frames received from `target` by `source`.
+    pub lqi: u8,
+    /// Relationship of `target` to `source`.
+    pub relationship: Relationship,
+}
+
+/// Entry of the routing table of `source`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteLink {
+    pub source: u16,
+    pub destination: u16,
+    pub next_hop: u16,
+    pub status: RouteStatus,
+}
+
+/// Nodes by NWK address, with the links between them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Topology {
+    pub nodes: BTreeMap<u16, Node>,
+    pub links: Vec<Link>,
+    pub routes: Vec<RouteLink>,
+    /// Routers whose neighbor table could not be read.
+    pub failed: Vec<u16>,
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Topology::new()
+    }
+}
+
+impl Topology {
+    /// Returns a topology with the coordinator only.
+    pub fn new() -> Self {
+        let mut coordinator = Node::new(0x0000);
+        coordinator.device_type = Some(LogicalType::Coordinator);
+        coordinator.depth = Some(0);
+        let mut nodes = BTreeMap::new();
+        nodes.insert(0x0000, coordinator);
+        Topology {
+            nodes,
+            links: Vec::new(),
+            routes: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+
+    /// Records the neighbor table of the node. Returns the routers which were
+    /// not seen before, to be crawled next.
+    pub fn add_neighbors(&mut self, nwk_address: u16, neighbors: &[Neighbor]) -> Vec<u16> {
+        self.nodes
+            .entry(nwk_address)
+            .or_insert_with(|| Node::new(nwk_address))
+            .crawled = true;
+        let mut routers = Vec::new();
+        for neighbor in neighbors {
+            let node = self.nodes.entry(neighbor.nwk_address).or_insert_with(|| {
+                if neighbor.device_type == Some(LogicalType::Router) {
+                    routers.push(neighbor.nwk_address);
+                }
+                Node::new(neighbor.nwk_address)
+            });
+            node.ieee_address = Some(neighbor.ieee_address);
+            node.device_type = neighbor.device_type.or(node.device_type);
+            node.depth = Some(neighbor.depth);
+            self.links.push(Link {
+                source: nwk_address,
+                target: neighbor.nwk_address,
+                lqi: neighbor.lqi,
+                relationship: neighbor.relationship,
+            });
+        }
+        routers
+    }
+
+    /// Records the routing table of the router.
+    pub fn add_routes(&mut self, nwk_address: u16, routes: &[Route]) {
+        self.routes.extend(routes.iter().map(|route| RouteLink {
+            source: nwk_address,
+            destination: route.destination,
+            next_hop: route.next_hop,
+            status: route.status,
+        }));
+    }
+
+    /// Returns the Graphviz description of the topology. Links are labelled
+    /// with their LQI, and parent-child links are solid.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph topology {\n");
+        for node in self.nodes.values() {
+            let shape = match node.device_type {
+                Some(LogicalType::Coordinator) => "doublecircle",
+                Some(LogicalType::Router) => "box",
+                _ => "ellipse",
+            };
+            let label = match node.ieee_address {
+                Some(ieee_address) => format!("{:#06x}\\n{:016x}", node.nwk_address, ieee_address),
+                None => format!("{:#06x}", node.nwk_address),
+            };
+            let style = if node.crawled || node.device_type != Some(LogicalType::Router) {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            let _ = writeln!(
+                dot,
+                "  \"{:04x}\" [label=\"{}\", shape={}{}];",
+                node.nwk_address, label, shape, style
+            );
+        }
+        for link in &self.links {
+            let style = match link.relationship {
+                Relationship::Child | Relationship::Parent => "solid",
+                _ => "dotted",
+            };
+            let _ = writeln!(
+                dot,
+                "  \"{:04x}\" -> \"{:04x}\" [label=\"{}\", style={}];",
+                link.source, link.target, link.lqi, style
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Returns the JSON description of the topology, with the nodes as a list.
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(&serde_json::json!({
+            "nodes": self.nodes.values().collect::<Vec<_>>(),
+            "links": self.links,
+            "routes": self.routes,
+            "failed": self.failed,
+        }))?)
+    }
+}
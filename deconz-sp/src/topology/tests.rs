@@ -0,0 +1,105 @@
+use super::*;
+
+fn neighbor(nwk_address: u16, device_type: LogicalType, relationship: Relationship) -> Neighbor {
+    Neighbor {
+        extended_pan_id: 0x00dd_dddd_dddd_dddd,
+        ieee_address: 0x0015_8d00_0000_0000 | nwk_address as u64,
+        nwk_address,
+        device_type: Some(device_type),
+        rx_on_when_idle: Some(device_type == LogicalType::Router),
+        relationship,
+        permit_joining: Some(false),
+        depth: 1,
+        lqi: 200,
+    }
+}
+
+#[test]
+fn crawl_neighbors_once() {
+    let mut topology = Topology::new();
+    let routers = topology.add_neighbors(
+        0x0000,
+        &[
+            neighbor(0x1234, LogicalType::Router, Relationship::Child),
+            neighbor(0x5678, LogicalType::EndDevice, Relationship::Child),
+        ],
+    );
+    assert_eq!(routers, vec![0x1234]);
+    // The coordinator is already known
+    let mut coordinator = neighbor(0x0000, LogicalType::Coordinator, Relationship::Parent);
+    coordinator.depth = 0;
+    let routers = topology.add_neighbors(
+        0x1234,
+        &[
+            coordinator,
+            neighbor(0x9abc, LogicalType::EndDevice, Relationship::Child),
+        ],
+    );
+    assert!(routers.is_empty());
+    assert_eq!(topology.nodes.len(), 4);
+    assert_eq!(topology.links.len(), 4);
+    assert!(topology.nodes[&0x1234].crawled);
+    assert!(!topology.nodes[&0x5678].crawled);
+    assert_eq!(
+        topology.nodes[&0x0000].ieee_address,
+        Some(0x0015_8d00_0000_0000)
+    );
+}
+
+#[test]
+fn record_routes() {
+    let mut topology = Topology::new();
+    topology.add_routes(
+        0x0000,
+        &[Route {
+            destination: 0x5678,
+            status: RouteStatus::Active,
+            memory_constrained: false,
+            many_to_one: false,
+            route_record_required: false,
+            next_hop: 0x1234,
+        }],
+    );
+    assert_eq!(
+        topology.routes,
+        vec![RouteLink {
+            source: 0x0000,
+            destination: 0x5678,
+            next_hop: 0x1234,
+            status: RouteStatus::Active,
+        }]
+    );
+}
+
+#[test]
+fn export_dot() {
+    let mut topology = Topology::new();
+    topology.add_neighbors(
+        0x0000,
+        &[neighbor(0x1234, LogicalType::Router, Relationship::Child)],
+    );
+    let dot = topology.to_dot();
+    assert!(dot.starts_with("digraph topology {\n"));
+    assert!(dot.contains("\"0000\" [label=\"0x0000\", shape=doublecircle];"));
+    assert!(dot.contains("shape=box, style=dashed"));
+    assert!(dot.contains("\"0000\" -> \"1234\" [label=\"200\", style=solid];"));
+}
+
+#[test]
+fn export_json() {
+    let mut topology = Topology::new();
+    topology.add_neighbors(
+        0x0000,
+        &[neighbor(
+            0x1234,
+            LogicalType::EndDevice,
+            Relationship::Child,
+        )],
+    );
+    let json: serde_json::Value = serde_json::from_str(&topology.to_json().unwrap()).unwrap();
+    assert_eq!(json["nodes"][1]["nwk_address"], 0x1234);
+    assert_eq!(json["nodes"][1]["ieee_address"], "00158d0000001234");
+    assert_eq!(json["nodes"][1]["device_type"], "EndDevice");
+    assert_eq!(json["links"][0]["relationship"], "Child");
+    assert_eq!(json["links"][0]["lqi"], 200);
+}
@@ -0,0 +1,8 @@
+//! Basic cluster (0x0000): identity of the device.
+
+pub const CLUSTER_ID: u16 = 0x0000;
+
+pub const MANUFACTURER_NAME: u16 = 0x0004;
+pub const MODEL_IDENTIFIER: u16 = 0x0005;
+pub const POWER_SOURCE: u16 = 0x0007;
+pub const SW_BUILD_ID: u16 = 0x4000;
@@ -0,0 +1,409 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FrameType {
+    Global,
+    ClusterSpecific,
+}
+
+impl FrameType {
+    pub fn code(self) -> u8 {
+        match self {
+            FrameType::Global => 0x0,
+            FrameType::ClusterSpecific => 0x1,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x0 => Some(FrameType::Global),
+            0x1 => Some(FrameType::ClusterSpecific),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Direction {
+    ClientToServer,
+    ServerToClient,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GlobalCommand {
+    ReadAttributes,
+    ReadAttributesResponse,
+    WriteAttributes,
+    WriteAttributesUndivided,
+    WriteAttributesResponse,
+    WriteAttributesNoResponse,
+    ConfigureReporting,
+    ConfigureReportingResponse,
+    ReadReportingConfiguration,
+    ReadReportingConfigurationResponse,
+    ReportAttributes,
+    DefaultResponse,
+    DiscoverAttributes,
+    DiscoverAttributesResponse,
+    DiscoverCommandsReceived,
+    DiscoverCommandsReceivedResponse,
+    DiscoverCommandsGenerated,
+    DiscoverCommandsGeneratedResponse,
+}
+
+impl GlobalCommand {
+    pub fn code(self) -> u8 {
+        match self {
+            GlobalCommand::ReadAttributes => 0x00,
+            GlobalCommand::ReadAttributesResponse => 0x01,
+            GlobalCommand::WriteAttributes => 0x02,
+            GlobalCommand::WriteAttributesUndivided => 0x03,
+            GlobalCommand::WriteAttributesResponse => 0x04,
+            GlobalCommand::WriteAttributesNoResponse => 0x05,
+            GlobalCommand::ConfigureReporting => 0x06,
+            GlobalCommand::ConfigureReportingResponse => 0x07,
+            GlobalCommand::ReadReportingConfiguration => 0x08,
+            GlobalCommand::ReadReportingConfigurationResponse => 0x09,
+            GlobalCommand::ReportAttributes => 0x0a,
+            GlobalCommand::DefaultResponse => 0x0b,
+            GlobalCommand::DiscoverAttributes => 0x0c,
+            GlobalCommand::DiscoverAttributesResponse => 0x0d,
+            GlobalCommand::DiscoverCommandsReceived => 0x11,
+            GlobalCommand::DiscoverCommandsReceivedResponse => 0x12,
+            GlobalCommand::DiscoverCommandsGenerated => 0x13,
+            GlobalCommand::DiscoverCommandsGeneratedResponse => 0x14,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x00 => Some(GlobalCommand::ReadAttributes),
+            0x01 => Some(GlobalCommand::ReadAttributesResponse),
+            0x02 => Some(GlobalCommand::WriteAttributes),
+            0x03 => Some(GlobalCommand::WriteAttributesUndivided),
+            0x04 => Some(GlobalCommand::WriteAttributesResponse),
+            0x05 => Some(GlobalCommand::WriteAttributesNoResponse),
+            0x06 => Some(GlobalCommand::ConfigureReporting),
+            0x07 => Some(GlobalCommand::ConfigureReportingResponse),
+            0x08 => Some(GlobalCommand::ReadReportingConfiguration),
+            0x09 => Some(GlobalCommand::ReadReportingConfigurationResponse),
+            0x0a => Some(GlobalCommand::ReportAttributes),
+            0x0b => Some(GlobalCommand::DefaultResponse),
+            0x0c => Some(GlobalCommand::DiscoverAttributes),
+            0x0d => Some(GlobalCommand::DiscoverAttributesResponse),
+            0x11 => Some(GlobalCommand::DiscoverCommandsReceived),
+            0x12 => Some(GlobalCommand::DiscoverCommandsReceivedResponse),
+            0x13 => Some(GlobalCommand::DiscoverCommandsGenerated),
+            0x14 => Some(GlobalCommand::DiscoverCommandsGeneratedResponse),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ZclStatus {
+    Success,
+    Failure,
+    NotAuthorized,
+    ReservedFieldNotZero,
+    MalformedCommand,
+    UnsupportedClusterCommand,
+    UnsupportedGeneralCommand,
+    UnsupportedManufacturerClusterCommand,
+    UnsupportedManufacturerGeneralCommand,
+    InvalidField,
+    UnsupportedAttribute,
+    InvalidValue,
+    ReadOnly,
+    InsufficientSpace,
+    DuplicateExists,
+    NotFound,
+    UnreportableAttribute,
+    InvalidDataType,
+    InvalidSelector,
+    WriteOnly,
+    InconsistentStartupState,
+    DefinedOutOfBand,
+    Inconsistent,
+    ActionDenied,
+    Timeout,
+    Abort,
+    InvalidImage,
+    WaitForData,
+    NoImageAvailable,
+    RequireMoreImage,
+    NotificationPending,
+    HardwareFailure,
+    SoftwareFailure,
+    CalibrationError,
+    UnsupportedCluster,
+}
+
+impl ZclStatus {
+    pub fn code(self) -> u8 {
+        match self {
+            ZclStatus::Success => 0x00,
+            ZclStatus::Failure => 0x01,
+            ZclStatus::NotAuthorized => 0x7e,
+            ZclStatus::ReservedFieldNotZero => 0x7f,
+            ZclStatus::MalformedCommand => 0x80,
+            ZclStatus::UnsupportedClusterCommand => 0x81,
+            ZclStatus::UnsupportedGeneralCommand => 0x82,
+            ZclStatus::UnsupportedManufacturerClusterCommand => 0x83,
+            ZclStatus::UnsupportedManufacturerGeneralCommand => 0x84,
+            ZclStatus::InvalidField => 0x85,
+            ZclStatus::UnsupportedAttribute => 0x86,
+            ZclStatus::InvalidValue => 0x87,
+            ZclStatus::ReadOnly => 0x88,
+            ZclStatus::InsufficientSpace => 0x89,
+            ZclStatus::DuplicateExists => 0x8a,
+            ZclStatus::NotFound => 0x8b,
+            ZclStatus::UnreportableAttribute => 0x8c,
+            ZclStatus::InvalidDataType => 0x8d,
+            ZclStatus::InvalidSelector => 0x8e,
+            ZclStatus::WriteOnly => 0x8f,
+            ZclStatus::InconsistentStartupState => 0x90,
+            ZclStatus::DefinedOutOfBand => 0x91,
+            ZclStatus::Inconsistent => 0x92,
+            ZclStatus::ActionDenied => 0x93,
+            ZclStatus::Timeout => 0x94,
+            ZclStatus::Abort => 0x95,
+            ZclStatus::InvalidImage => 0x96,
+            ZclStatus::WaitForData => 0x97,
+            ZclStatus::NoImageAvailable => 0x98,
+            ZclStatus::RequireMoreImage => 0x99,
+            ZclStatus::NotificationPending => 0x9a,
+            ZclStatus::HardwareFailure => 0xc0,
+            ZclStatus::SoftwareFailure => 0xc1,
+            ZclStatus::CalibrationError => 0xc2,
+            ZclStatus::UnsupportedCluster => 0xc3,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x00 => Some(ZclStatus::Success),
+            0x01 => Some(ZclStatus::Failure),
+            0x7e => Some(ZclStatus::NotAuthorized),
+            0x7f => Some(ZclStatus::ReservedFieldNotZero),
+            0x80 => Some(ZclStatus::MalformedCommand),
+            0x81 => Some(ZclStatus::UnsupportedClusterCommand),
+            0x82 => Some(ZclStatus::UnsupportedGeneralCommand),
+            0x83 => Some(ZclStatus::UnsupportedManufacturerClusterCommand),
+            0x84 => Some(ZclStatus::UnsupportedManufacturerGeneralCommand),
+            0x85 => Some(ZclStatus::InvalidField),
+            0x86 => Some(ZclStatus::UnsupportedAttribute),
+            0x87 => Some(ZclStatus::InvalidValue),
+            0x88 => Some(ZclStatus::ReadOnly),
+            0x89 => Some(ZclStatus::InsufficientSpace),
+            0x8a => Some(ZclStatus::DuplicateExists),
+            0x8b => Some(ZclStatus::NotFound),
+            0x8c => Some(ZclStatus::UnreportableAttribute),
+            0x8d => Some(ZclStatus::InvalidDataType),
+            0x8e => Some(ZclStatus::InvalidSelector),
+            0x8f => Some(ZclStatus::WriteOnly),
+            0x90 => Some(ZclStatus::InconsistentStartupState),
+            0x91 => Some(ZclStatus::DefinedOutOfBand),
+            0x92 => Some(ZclStatus::Inconsistent),
+            0x93 => Some(ZclStatus::ActionDenied),
+            0x94 => Some(ZclStatus::Timeout),
+            0x95 => Some(ZclStatus::Abort),
+            0x96 => Some(ZclStatus::InvalidImage),
+            0x97 => Some(ZclStatus::WaitForData),
+            0x98 => Some(ZclStatus::NoImageAvailable),
+            0x99 => Some(ZclStatus::RequireMoreImage),
+            0x9a => Some(ZclStatus::NotificationPending),
+            0xc0 => Some(ZclStatus::HardwareFailure),
+            0xc1 => Some(ZclStatus::SoftwareFailure),
+            0xc2 => Some(ZclStatus::CalibrationError),
+            0xc3 => Some(ZclStatus::UnsupportedCluster),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum DataType {
+    NoData,
+    Data8,
+    Data16,
+    Data24,
+    Data32,
+    Bool,
+    Bitmap8,
+    Bitmap16,
+    Bitmap24,
+    Bitmap32,
+    Bitmap64,
+    Uint8,
+    Uint16,
+    Uint24,
+    Uint32,
+    Uint40,
+    Uint48,
+    Uint56,
+    Uint64,
+    Int8,
+    Int16,
+    Int24,
+    Int32,
+    Int40,
+    Int48,
+    Int56,
+    Int64,
+    Enum8,
+    Enum16,
+    Semi,
+    Single,
+    Double,
+    OctetString,
+    CharacterString,
+    LongOctetString,
+    LongCharacterString,
+    Array,
+    Structure,
+    Set,
+    Bag,
+    TimeOfDay,
+    Date,
+    UtcTime,
+    ClusterId,
+    AttributeId,
+    BacnetOid,
+    IeeeAddress,
+    SecurityKey,
+}
+
+impl DataType {
+    pub fn code(self) -> u8 {
+        match self {
+            DataType::NoData => 0x00,
+            DataType::Data8 => 0x08,
+            DataType::Data16 => 0x09,
+            DataType::Data24 => 0x0a,
+            DataType::Data32 => 0x0b,
+            DataType::Bool => 0x10,
+            DataType::Bitmap8 => 0x18,
+            DataType::Bitmap16 => 0x19,
+            DataType::Bitmap24 => 0x1a,
+            DataType::Bitmap32 => 0x1b,
+            DataType::Bitmap64 => 0x1f,
+            DataType::Uint8 => 0x20,
+            DataType::Uint16 => 0x21,
+            DataType::Uint24 => 0x22,
+            DataType::Uint32 => 0x23,
+            DataType::Uint40 => 0x24,
+            DataType::Uint48 => 0x25,
+            DataType::Uint56 => 0x26,
+            DataType::Uint64 => 0x27,
+            DataType::Int8 => 0x28,
+            DataType::Int16 => 0x29,
+            DataType::Int24 => 0x2a,
+            DataType::Int32 => 0x2b,
+            DataType::Int40 => 0x2c,
+            DataType::Int48 => 0x2d,
+            DataType::Int56 => 0x2e,
+            DataType::Int64 => 0x2f,
+            DataType::Enum8 => 0x30,
+            DataType::Enum16 => 0x31,
+            DataType::Semi => 0x38,
+            DataType::Single => 0x39,
+            DataType::Double => 0x3a,
+            DataType::OctetString => 0x41,
+            DataType::CharacterString => 0x42,
+            DataType::LongOctetString => 0x43,
+            DataType::LongCharacterString => 0x44,
+            DataType::Array => 0x48,
+            DataType::Structure => 0x4c,
+            DataType::Set => 0x50,
+            DataType::Bag => 0x51,
+            DataType::TimeOfDay => 0xe0,
+            DataType::Date => 0xe1,
+            DataType::UtcTime => 0xe2,
+            DataType::ClusterId => 0xe8,
+            DataType::AttributeId => 0xe9,
+            DataType::BacnetOid => 0xea,
+            DataType::IeeeAddress => 0xf0,
+            DataType::SecurityKey => 0xf1,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x00 => Some(DataType::NoData),
+            0x08 => Some(DataType::Data8),
+            0x09 => Some(DataType::Data16),
+            0x0a => Some(DataType::Data24),
+            0x0b => Some(DataType::Data32),
+            0x10 => Some(DataType::Bool),
+            0x18 => Some(DataType::Bitmap8),
+            0x19 => Some(DataType::Bitmap16),
+            0x1a => Some(DataType::Bitmap24),
+            0x1b => Some(DataType::Bitmap32),
+            0x1f => Some(DataType::Bitmap64),
+            0x20 => Some(DataType::Uint8),
+            0x21 => Some(DataType::Uint16),
+            0x22 => Some(DataType::Uint24),
+            0x23 => Some(DataType::Uint32),
+            0x24 => Some(DataType::Uint40),
+            0x25 => Some(DataType::Uint48),
+            0x26 => Some(DataType::Uint56),
+            0x27 => Some(DataType::Uint64),
+            0x28 => Some(DataType::Int8),
+            0x29 => Some(DataType::Int16),
+            0x2a => Some(DataType::Int24),
+            0x2b => Some(DataType::Int32),
+            0x2c => Some(DataType::Int40),
+            0x2d => Some(DataType::Int48),
+            0x2e => Some(DataType::Int56),
+            0x2f => Some(DataType::Int64),
+            0x30 => Some(DataType::Enum8),
+            0x31 => Some(DataType::Enum16),
+            0x38 => Some(DataType::Semi),
+            0x39 => Some(DataType::Single),
+            0x3a => Some(DataType::Double),
+            0x41 => Some(DataType::OctetString),
+            0x42 => Some(DataType::CharacterString),
+            0x43 => Some(DataType::LongOctetString),
+            0x44 => Some(DataType::LongCharacterString),
+            0x48 => Some(DataType::Array),
+            0x4c => Some(DataType::Structure),
+            0x50 => Some(DataType::Set),
+            0x51 => Some(DataType::Bag),
+            0xe0 => Some(DataType::TimeOfDay),
+            0xe1 => Some(DataType::Date),
+            0xe2 => Some(DataType::UtcTime),
+            0xe8 => Some(DataType::ClusterId),
+            0xe9 => Some(DataType::AttributeId),
+            0xea => Some(DataType::BacnetOid),
+            0xf0 => Some(DataType::IeeeAddress),
+            0xf1 => Some(DataType::SecurityKey),
+            _ => None,
+        }
+    }
+    /// Analog types carry a reportable change in reporting configurations,
+    /// discrete types do not.
+    pub fn is_analog(self) -> bool {
+        matches!(
+            self,
+            DataType::Uint8
+                | DataType::Uint16
+                | DataType::Uint24
+                | DataType::Uint32
+                | DataType::Uint40
+                | DataType::Uint48
+                | DataType::Uint56
+                | DataType::Uint64
+                | DataType::Int8
+                | DataType::Int16
+                | DataType::Int24
+                | DataType::Int32
+                | DataType::Int40
+                | DataType::Int48
+                | DataType::Int56
+                | DataType::Int64
+                | DataType::Semi
+                | DataType::Single
+                | DataType::Double
+                | DataType::TimeOfDay
+                | DataType::Date
+                | DataType::UtcTime
+        )
+    }
+}
@@ -0,0 +1,395 @@
+use super::constants::{DataType, ZclStatus};
+use super::types::{write_uint, AttributeValue};
+use crate::reader::Reader;
+use crate::Error;
+
+pub(super) fn read_status(reader: &mut Reader) -> Result<ZclStatus, Error> {
+    ZclStatus::from_code(reader.u8()?).ok_or(Error::Decoding("Unknown ZCL status"))
+}
+
+fn read_data_type(reader: &mut Reader) -> Result<DataType, Error> {
+    DataType::from_code(reader.u8()?).ok_or(Error::Decoding("Unsupported ZCL data type"))
+}
+
+fn read_records<T>(
+    payload: &[u8],
+    read: impl Fn(&mut Reader) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    let mut reader = Reader::new(payload);
+    let mut records = Vec::new();
+    while !reader.is_empty() {
+        records.push(read(&mut reader)?);
+    }
+    Ok(records)
+}
+
+/// Payload of a Read Attributes command.
+pub fn write_read_attributes(attributes: &[u16], out: &mut Vec<u8>) {
+    for attribute_id in attributes {
+        write_uint(out, *attribute_id as u64, 2);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadAttributeRecord {
+    pub attribute_id: u16,
+    pub status: ZclStatus,
+    pub value: Option<AttributeValue>,
+}
+
+impl ReadAttributeRecord {
+    pub fn read_all(payload: &[u8]) -> Result<Vec<Self>, Error> {
+        read_records(payload, |reader| {
+            let attribute_id = reader.u16()?;
+            let status = read_status(reader)?;
+            let value = match status {
+                ZclStatus::Success => {
+                    let data_type = read_data_type(reader)?;
+                    Some(AttributeValue::read(data_type, reader)?)
+                }
+                _ => None,
+            };
+            Ok(ReadAttributeRecord {
+                attribute_id,
+                status,
+                value,
+            })
+        })
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        write_uint(out, self.attribute_id as u64, 2);
+        out.push(self.status.code());
+        if let Some(value) = &self.value {
+            out.push(value.data_type().code());
+            value.write(out);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteAttributeRecord {
+    pub attribute_id: u16,
+    pub value: AttributeValue,
+}
+
+impl WriteAttributeRecord {
+    pub fn new(attribute_id: u16, value: AttributeValue) -> Self {
+        WriteAttributeRecord {
+            attribute_id,
+            value,
+        }
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        write_uint(out, self.attribute_id as u64, 2);
+        out.push(self.value.data_type().code());
+        self.value.write(out);
+    }
+}
+
+/// Status record of a Write Attributes response. When every attribute was
+/// written, the device answers with a single success record without attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteAttributeStatus {
+    pub status: ZclStatus,
+    pub attribute_id: Option<u16>,
+}
+
+impl WriteAttributeStatus {
+    pub fn read_all(payload: &[u8]) -> Result<Vec<Self>, Error> {
+        read_records(payload, |reader| {
+            let status = read_status(reader)?;
+            let attribute_id = if reader.is_empty() {
+                None
+            } else {
+                Some(reader.u16()?)
+            };
+            Ok(WriteAttributeStatus {
+                status,
+                attribute_id,
+            })
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReportingDirection {
+    /// The attribute is reported by the device.
+    Reported,
+    /// The device expects to receive reports of the attribute.
+    Received,
+}
+
+impl ReportingDirection {
+    pub fn code(self) -> u8 {
+        match self {
+            ReportingDirection::Reported => 0x0,
+            ReportingDirection::Received => 0x1,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x0 => Some(ReportingDirection::Reported),
+            0x1 => Some(ReportingDirection::Received),
+            _ => None,
+        }
+    }
+}
+
+fn read_direction(reader: &mut Reader) -> Result<ReportingDirection, Error> {
+    ReportingDirection::from_code(reader.u8()?)
+        .ok_or(Error::Decoding("Unknown reporting direction"))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportingConfiguration {
+    Reported {
+        attribute_id: u16,
+        data_type: DataType,
+        min_interval: u16,
+        max_interval: u16,
+        /// Only present for analog data types.
+        reportable_change: Option<AttributeValue>,
+    },
+    Received {
+        attribute_id: u16,
+        timeout: u16,
+    },
+}
+
+impl ReportingConfiguration {
+    pub fn direction(&self) -> ReportingDirection {
+        match self {
+            ReportingConfiguration::Reported { .. } => ReportingDirection::Reported,
+            ReportingConfiguration::Received { .. } => ReportingDirection::Received,
+        }
+    }
+
+    pub fn attribute_id(&self) -> u16 {
+        match self {
+            ReportingConfiguration::Reported { attribute_id, .. }
+            | ReportingConfiguration::Received { attribute_id, .. } => *attribute_id,
+        }
+    }
+
+    fn read_body(
+        direction: ReportingDirection,
+        attribute_id: u16,
+        reader: &mut Reader,
+    ) -> Result<Self, Error> {
+        match direction {
+            ReportingDirection::Reported => {
+                let data_type = read_data_type(reader)?;
+                let min_interval = reader.u16()?;
+                let max_interval = reader.u16()?;
+                let reportable_change = if data_type.is_analog() {
+                    Some(AttributeValue::read(data_type, reader)?)
+                } else {
+                    None
+                };
+                Ok(ReportingConfiguration::Reported {
+                    attribute_id,
+                    data_type,
+                    min_interval,
+                    max_interval,
+                    reportable_change,
+                })
+            }
+            ReportingDirection::Received => Ok(ReportingConfiguration::Received {
+                attribute_id,
+                timeout: reader.u16()?,
+            }),
+        }
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.direction().code());
+        write_uint(out, self.attribute_id() as u64, 2);
+        match self {
+            ReportingConfiguration::Reported {
+                data_type,
+                min_interval,
+                max_interval,
+                reportable_change,
+                ..
+            } => {
+                out.push(data_type.code());
+                write_uint(out, *min_interval as u64, 2);
+                write_uint(out, *max_interval as u64, 2);
+                if let Some(change) = reportable_change {
+                    change.write(out);
+                }
+            }
+            ReportingConfiguration::Received { timeout, .. } => {
+                write_uint(out, *timeout as u64, 2);
+            }
+        }
+    }
+}
+
+/// Status record of a Configure Reporting response. When every attribute was
+/// configured, the device answers with a single success record without attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureReportingStatus {
+    pub status: ZclStatus,
+    pub direction: Option<ReportingDirection>,
+    pub attribute_id: Option<u16>,
+}
+
+impl ConfigureReportingStatus {
+    pub fn read_all(payload: &[u8]) -> Result<Vec<Self>, Error> {
+        read_records(payload, |reader| {
+            let status = read_status(reader)?;
+            if reader.is_empty() {
+                return Ok(ConfigureReportingStatus {
+                    status,
+                    direction: None,
+                    attribute_id: None,
+                });
+            }
+            let direction = read_direction(reader)?;
+            let attribute_id = reader.u16()?;
+            Ok(ConfigureReportingStatus {
+                status,
+                direction: Some(direction),
+                attribute_id: Some(attribute_id),
+            })
+        })
+    }
+}
+
+/// Payload of a Read Reporting Configuration command.
+pub fn write_read_reporting_configuration(
+    attributes: &[(ReportingDirection, u16)],
+    out: &mut Vec<u8>,
+) {
+    for (direction, attribute_id) in attributes {
+        out.push(direction.code());
+        write_uint(out, *attribute_id as u64, 2);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportingConfigurationRecord {
+    pub status: ZclStatus,
+    pub direction: ReportingDirection,
+    pub attribute_id: u16,
+    pub configuration: Option<ReportingConfiguration>,
+}
+
+impl ReportingConfigurationRecord {
+    pub fn read_all(payload: &[u8]) -> Result<Vec<Self>, Error> {
+        read_records(payload, |reader| {
+            let status = read_status(reader)?;
+            let direction = read_direction(reader)?;
+            let attribute_id = reader.u16()?;
+            let configuration = match status {
+                ZclStatus::Success => Some(ReportingConfiguration::read_body(
+                    direction,
+                    attribute_id,
+                    reader,
+                )?),
+                _ => None,
+            };
+            Ok(ReportingConfigurationRecord {
+                status,
+                direction,
+                attribute_id,
+                configuration,
+            })
+        })
+    }
+}
+
+/// Payload of a Discover Attributes command.
+pub fn write_discover_attributes(start_attribute_id: u16, max_attributes: u8, out: &mut Vec<u8>) {
+    write_uint(out, start_attribute_id as u64, 2);
+    out.push(max_attributes);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoverAttributesResponse {
+    pub discovery_complete: bool,
+    pub attributes: Vec<(u16, DataType)>,
+}
+
+impl DiscoverAttributesResponse {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        let discovery_complete = reader.u8()? != 0;
+        let mut attributes = Vec::new();
+        while !reader.is_empty() {
+            let attribute_id = reader.u16()?;
+            let data_type = read_data_type(&mut reader)?;
+            attributes.push((attribute_id, data_type));
+        }
+        Ok(DiscoverAttributesResponse {
+            discovery_complete,
+            attributes,
+        })
+    }
+}
+
+/// Payload of a Discover Commands Received/Generated command.
+pub fn write_discover_commands(start_command_id: u8, max_commands: u8, out: &mut Vec<u8>) {
+    out.push(start_command_id);
+    out.push(max_commands);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoverCommandsResponse {
+    pub discovery_complete: bool,
+    pub commands: Vec<u8>,
+}
+
+impl DiscoverCommandsResponse {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        let discovery_complete = reader.u8()? != 0;
+        Ok(DiscoverCommandsResponse {
+            discovery_complete,
+            commands: Vec::from(reader.rest()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultResponse {
+    pub command_id: u8,
+    pub status: ZclStatus,
+}
+
+impl DefaultResponse {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        let command_id = reader.u8()?;
+        let status = read_status(&mut reader)?;
+        Ok(DefaultResponse { command_id, status })
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.command_id);
+        out.push(self.status.code());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeReport {
+    pub attribute_id: u16,
+    pub value: AttributeValue,
+}
+
+impl AttributeReport {
+    pub fn read_all(payload: &[u8]) -> Result<Vec<Self>, Error> {
+        read_records(payload, |reader| {
+            let attribute_id = reader.u16()?;
+            let data_type = read_data_type(reader)?;
+            let value = AttributeValue::read(data_type, reader)?;
+            Ok(AttributeReport {
+                attribute_id,
+                value,
+            })
+        })
+    }
+}
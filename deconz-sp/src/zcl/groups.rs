@@ -0,0 +1,114 @@
+//! Groups cluster (0x0004): membership of the endpoints of a device in groups.
+
+use super::global::read_status;
+use super::{FrameType, ZclFrame, ZclStatus};
+use crate::reader::Reader;
+use crate::zcl::write_uint;
+use crate::Error;
+
+pub const CLUSTER_ID: u16 = 0x0004;
+
+pub const ADD_GROUP: u8 = 0x00;
+pub const VIEW_GROUP: u8 = 0x01;
+pub const GET_GROUP_MEMBERSHIP: u8 = 0x02;
+pub const REMOVE_GROUP: u8 = 0x03;
+pub const REMOVE_ALL_GROUPS: u8 = 0x04;
+
+pub fn add_group(group_id: u16, name: &str) -> ZclFrame {
+    let mut payload = Vec::new();
+    write_uint(&mut payload, group_id as u64, 2);
+    // Names are optional, and most devices do not store them
+    let name = &name.as_bytes()[..name.len().min(16)];
+    payload.push(name.len() as u8);
+    payload.extend_from_slice(name);
+    ZclFrame::new(FrameType::ClusterSpecific, ADD_GROUP, payload)
+}
+
+pub fn view_group(group_id: u16) -> ZclFrame {
+    let mut payload = Vec::new();
+    write_uint(&mut payload, group_id as u64, 2);
+    ZclFrame::new(FrameType::ClusterSpecific, VIEW_GROUP, payload)
+}
+
+/// Asks which of the groups the endpoint is a member of, or all its groups if
+/// `groups` is empty.
+pub fn get_group_membership(groups: &[u16]) -> ZclFrame {
+    let mut payload = vec![groups.len() as u8];
+    for group_id in groups {
+        write_uint(&mut payload, *group_id as u64, 2);
+    }
+    ZclFrame::new(FrameType::ClusterSpecific, GET_GROUP_MEMBERSHIP, payload)
+}
+
+pub fn remove_group(group_id: u16) -> ZclFrame {
+    let mut payload = Vec::new();
+    write_uint(&mut payload, group_id as u64, 2);
+    ZclFrame::new(FrameType::ClusterSpecific, REMOVE_GROUP, payload)
+}
+
+pub fn remove_all_groups() -> ZclFrame {
+    ZclFrame::new(FrameType::ClusterSpecific, REMOVE_ALL_GROUPS, Vec::new())
+}
+
+/// Add group and remove group responses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupResponse {
+    pub status: ZclStatus,
+    pub group_id: u16,
+}
+
+impl GroupResponse {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        Ok(GroupResponse {
+            status: read_status(&mut reader)?,
+            group_id: reader.u16()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewGroupResponse {
+    pub status: ZclStatus,
+    pub group_id: u16,
+    pub name: String,
+}
+
+impl ViewGroupResponse {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        let status = read_status(&mut reader)?;
+        let group_id = reader.u16()?;
+        // The name is only present on success
+        let name = match reader.u8() {
+            Ok(0xff) | Err(_) => String::new(),
+            Ok(len) => String::from_utf8_lossy(reader.bytes(len as usize)?).into_owned(),
+        };
+        Ok(ViewGroupResponse {
+            status,
+            group_id,
+            name,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupMembership {
+    /// Number of groups which can still be added, 0xfe if unknown and 0xff
+    /// if at least one more group can be added.
+    pub capacity: u8,
+    pub groups: Vec<u16>,
+}
+
+impl GroupMembership {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        let capacity = reader.u8()?;
+        let count = reader.u8()?;
+        let mut groups = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            groups.push(reader.u16()?);
+        }
+        Ok(GroupMembership { capacity, groups })
+    }
+}
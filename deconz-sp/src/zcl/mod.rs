@@ -0,0 +1,125 @@
+//! Zigbee Cluster Library frames, carried in the ASDU of APS data requests and indications.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::reader::Reader;
+use crate::Error;
+
+pub mod basic;
+mod constants;
+mod global;
+pub mod groups;
+pub mod scenes;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use constants::{DataType, Direction, FrameType, GlobalCommand, ZclStatus};
+pub use global::*;
+pub(crate) use types::write_uint;
+pub use types::AttributeValue;
+
+/// Profile used for ZCL requests sent by the client (Home Automation).
+pub const HA_PROFILE_ID: u16 = 0x0104;
+
+#[derive(Debug, Clone)]
+pub struct ZclHeader {
+    pub frame_type: FrameType,
+    pub manufacturer_code: Option<u16>,
+    pub direction: Direction,
+    pub disable_default_response: bool,
+    pub transaction_sequence: u8,
+    pub command_id: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct ZclFrame {
+    pub header: ZclHeader,
+    pub payload: Vec<u8>,
+}
+
+impl ZclFrame {
+    /// Builds a client-to-server frame. The transaction sequence number is
+    /// assigned by the client when the frame is sent.
+    pub fn new(frame_type: FrameType, command_id: u8, payload: Vec<u8>) -> Self {
+        ZclFrame {
+            header: ZclHeader {
+                frame_type,
+                manufacturer_code: None,
+                direction: Direction::ClientToServer,
+                disable_default_response: false,
+                transaction_sequence: 0,
+                command_id,
+            },
+            payload,
+        }
+    }
+
+    pub fn new_global(command: GlobalCommand, payload: Vec<u8>) -> Self {
+        ZclFrame::new(FrameType::Global, command.code(), payload)
+    }
+
+    /// Returns the global command of this frame, if it is a global frame with
+    /// a known command identifier.
+    pub fn global_command(&self) -> Option<GlobalCommand> {
+        match self.header.frame_type {
+            FrameType::Global => GlobalCommand::from_code(self.header.command_id),
+            FrameType::ClusterSpecific => None,
+        }
+    }
+
+    pub fn read(input: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(input);
+        let frame_control = reader.u8()?;
+        let frame_type = FrameType::from_code(frame_control & 0x3)
+            .ok_or(Error::Decoding("Unknown ZCL frame type"))?;
+        let manufacturer_code = if frame_control & 0x4 != 0 {
+            Some(reader.u16()?)
+        } else {
+            None
+        };
+        let direction = if frame_control & 0x8 != 0 {
+            Direction::ServerToClient
+        } else {
+            Direction::ClientToServer
+        };
+        let disable_default_response = frame_control & 0x10 != 0;
+        let transaction_sequence = reader.u8()?;
+        let command_id = reader.u8()?;
+        Ok(ZclFrame {
+            header: ZclHeader {
+                frame_type,
+                manufacturer_code,
+                direction,
+                disable_default_response,
+                transaction_sequence,
+                command_id,
+            },
+            payload: Vec::from(reader.rest()),
+        })
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let header = &self.header;
+        let mut frame_control = header.frame_type.code();
+        if header.manufacturer_code.is_some() {
+            frame_control |= 0x4;
+        }
+        if header.direction == Direction::ServerToClient {
+            frame_control |= 0x8;
+        }
+        if header.disable_default_response {
+            frame_control |= 0x10;
+        }
+        out.push(frame_control);
+        if let Some(code) = header.manufacturer_code {
+            let mut buf = [0; 2];
+            LittleEndian::write_u16(&mut buf, code);
+            out.extend_from_slice(&buf);
+        }
+        out.push(header.transaction_sequence);
+        out.push(header.command_id);
+        out.extend_from_slice(&self.payload);
+    }
+}
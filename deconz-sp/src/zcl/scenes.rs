@@ -0,0 +1,275 @@
+//! Scenes cluster (0x0005): states of the device stored under a scene of a
+//! group, and recalled at once.
+
+use super::global::read_status;
+use super::{FrameType, ZclFrame, ZclStatus};
+use crate::reader::Reader;
+use crate::zcl::write_uint;
+use crate::Error;
+
+pub const CLUSTER_ID: u16 = 0x0005;
+
+pub const ADD_SCENE: u8 = 0x00;
+pub const VIEW_SCENE: u8 = 0x01;
+pub const REMOVE_SCENE: u8 = 0x02;
+pub const REMOVE_ALL_SCENES: u8 = 0x03;
+pub const STORE_SCENE: u8 = 0x04;
+pub const RECALL_SCENE: u8 = 0x05;
+pub const GET_SCENE_MEMBERSHIP: u8 = 0x06;
+
+const ON_OFF_CLUSTER_ID: u16 = 0x0006;
+const LEVEL_CLUSTER_ID: u16 = 0x0008;
+const COLOR_CLUSTER_ID: u16 = 0x0300;
+
+/// State of a cluster of the device in a scene.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionFieldSet {
+    OnOff {
+        on: bool,
+    },
+    Level {
+        level: u8,
+    },
+    /// CIE xy color, or color temperature (in mireds) if set.
+    Color {
+        x: u16,
+        y: u16,
+        color_temperature: Option<u16>,
+    },
+    Other {
+        cluster_id: u16,
+        data: Vec<u8>,
+    },
+}
+
+impl ExtensionFieldSet {
+    pub fn cluster_id(&self) -> u16 {
+        match self {
+            ExtensionFieldSet::OnOff { .. } => ON_OFF_CLUSTER_ID,
+            ExtensionFieldSet::Level { .. } => LEVEL_CLUSTER_ID,
+            ExtensionFieldSet::Color { .. } => COLOR_CLUSTER_ID,
+            ExtensionFieldSet::Other { cluster_id, .. } => *cluster_id,
+        }
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let mut data = Vec::new();
+        match self {
+            ExtensionFieldSet::OnOff { on } => data.push(*on as u8),
+            ExtensionFieldSet::Level { level } => data.push(*level),
+            ExtensionFieldSet::Color {
+                x,
+                y,
+                color_temperature,
+            } => {
+                write_uint(&mut data, *x as u64, 2);
+                write_uint(&mut data, *y as u64, 2);
+                // The fields are in a fixed order: the hue, saturation and
+                // color loop fields come before the color temperature
+                if let Some(color_temperature) = color_temperature {
+                    data.extend_from_slice(&[0; 7]);
+                    write_uint(&mut data, *color_temperature as u64, 2);
+                }
+            }
+            ExtensionFieldSet::Other { data: other, .. } => data.extend_from_slice(other),
+        }
+        write_uint(out, self.cluster_id() as u64, 2);
+        out.push(data.len() as u8);
+        out.extend(data);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, Error> {
+        let cluster_id = reader.u16()?;
+        let len = reader.u8()? as usize;
+        let mut data = Reader::new(reader.bytes(len)?);
+        Ok(match (cluster_id, len) {
+            (ON_OFF_CLUSTER_ID, 1) => ExtensionFieldSet::OnOff {
+                on: data.u8()? != 0,
+            },
+            (LEVEL_CLUSTER_ID, 1) => ExtensionFieldSet::Level { level: data.u8()? },
+            (COLOR_CLUSTER_ID, 4) | (COLOR_CLUSTER_ID, 13) => {
+                let x = data.u16()?;
+                let y = data.u16()?;
+                let color_temperature = if len == 13 {
+                    data.bytes(7)?;
+                    Some(data.u16()?)
+                } else {
+                    None
+                };
+                ExtensionFieldSet::Color {
+                    x,
+                    y,
+                    color_temperature,
+                }
+            }
+            _ => ExtensionFieldSet::Other {
+                cluster_id,
+                data: Vec::from(data.rest()),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scene {
+    pub group_id: u16,
+    pub scene_id: u8,
+    /// In seconds.
+    pub transition_time: u16,
+    pub name: String,
+    pub extension_field_sets: Vec<ExtensionFieldSet>,
+}
+
+impl Scene {
+    pub fn new(group_id: u16, scene_id: u8) -> Self {
+        Scene {
+            group_id,
+            scene_id,
+            transition_time: 0,
+            name: String::new(),
+            extension_field_sets: Vec::new(),
+        }
+    }
+}
+
+fn scene_payload(group_id: u16, scene_id: u8) -> Vec<u8> {
+    let mut payload = Vec::new();
+    write_uint(&mut payload, group_id as u64, 2);
+    payload.push(scene_id);
+    payload
+}
+
+pub fn add_scene(scene: &Scene) -> ZclFrame {
+    let mut payload = scene_payload(scene.group_id, scene.scene_id);
+    write_uint(&mut payload, scene.transition_time as u64, 2);
+    let name = &scene.name.as_bytes()[..scene.name.len().min(16)];
+    payload.push(name.len() as u8);
+    payload.extend_from_slice(name);
+    for set in &scene.extension_field_sets {
+        set.write(&mut payload);
+    }
+    ZclFrame::new(FrameType::ClusterSpecific, ADD_SCENE, payload)
+}
+
+pub fn view_scene(group_id: u16, scene_id: u8) -> ZclFrame {
+    ZclFrame::new(
+        FrameType::ClusterSpecific,
+        VIEW_SCENE,
+        scene_payload(group_id, scene_id),
+    )
+}
+
+pub fn remove_scene(group_id: u16, scene_id: u8) -> ZclFrame {
+    ZclFrame::new(
+        FrameType::ClusterSpecific,
+        REMOVE_SCENE,
+        scene_payload(group_id, scene_id),
+    )
+}
+
+pub fn remove_all_scenes(group_id: u16) -> ZclFrame {
+    let mut payload = Vec::new();
+    write_uint(&mut payload, group_id as u64, 2);
+    ZclFrame::new(FrameType::ClusterSpecific, REMOVE_ALL_SCENES, payload)
+}
+
+/// Stores the current state of the device in the scene.
+pub fn store_scene(group_id: u16, scene_id: u8) -> ZclFrame {
+    ZclFrame::new(
+        FrameType::ClusterSpecific,
+        STORE_SCENE,
+        scene_payload(group_id, scene_id),
+    )
+}
+
+pub fn recall_scene(group_id: u16, scene_id: u8) -> ZclFrame {
+    ZclFrame::new(
+        FrameType::ClusterSpecific,
+        RECALL_SCENE,
+        scene_payload(group_id, scene_id),
+    )
+}
+
+pub fn get_scene_membership(group_id: u16) -> ZclFrame {
+    let mut payload = Vec::new();
+    write_uint(&mut payload, group_id as u64, 2);
+    ZclFrame::new(FrameType::ClusterSpecific, GET_SCENE_MEMBERSHIP, payload)
+}
+
+/// Add scene, remove scene and store scene responses. The scene is not
+/// present in remove all scenes responses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneResponse {
+    pub status: ZclStatus,
+    pub group_id: u16,
+    pub scene_id: Option<u8>,
+}
+
+impl SceneResponse {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        Ok(SceneResponse {
+            status: read_status(&mut reader)?,
+            group_id: reader.u16()?,
+            scene_id: reader.u8().ok(),
+        })
+    }
+}
+
+/// Reads a view scene response. Returns the status if the scene is not found.
+pub fn read_view_scene_response(payload: &[u8]) -> Result<Result<Scene, ZclStatus>, Error> {
+    let mut reader = Reader::new(payload);
+    let status = read_status(&mut reader)?;
+    let group_id = reader.u16()?;
+    let scene_id = reader.u8()?;
+    if status != ZclStatus::Success {
+        return Ok(Err(status));
+    }
+    let transition_time = reader.u16()?;
+    let name = match reader.u8()? {
+        0xff => String::new(),
+        len => String::from_utf8_lossy(reader.bytes(len as usize)?).into_owned(),
+    };
+    let mut extension_field_sets = Vec::new();
+    while !reader.is_empty() {
+        extension_field_sets.push(ExtensionFieldSet::read(&mut reader)?);
+    }
+    Ok(Ok(Scene {
+        group_id,
+        scene_id,
+        transition_time,
+        name,
+        extension_field_sets,
+    }))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneMembership {
+    pub status: ZclStatus,
+    /// Number of scenes which can still be added, 0xfe if unknown.
+    pub capacity: u8,
+    pub group_id: u16,
+    pub scenes: Vec<u8>,
+}
+
+impl SceneMembership {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        let status = read_status(&mut reader)?;
+        let capacity = reader.u8()?;
+        let group_id = reader.u16()?;
+        // The scene list is only present on success
+        let scenes = if status == ZclStatus::Success {
+            let count = reader.u8()?;
+            Vec::from(reader.bytes(count as usize)?)
+        } else {
+            Vec::new()
+        };
+        Ok(SceneMembership {
+            status,
+            capacity,
+            group_id,
+            scenes,
+        })
+    }
+}
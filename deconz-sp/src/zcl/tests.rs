@@ -0,0 +1,342 @@
+use super::*;
+
+#[test]
+fn decode_valid_zcl_frame() {
+    let frame = ZclFrame::read(&[0x18, 0x2a, 0x01, 0x05, 0x00]);
+    assert!(frame.is_ok());
+    let frame = frame.unwrap();
+    assert_eq!(
+        frame.header.frame_type,
+        FrameType::Global,
+        "Invalid frame type"
+    );
+    assert_eq!(frame.header.manufacturer_code, None);
+    assert_eq!(
+        frame.header.direction,
+        Direction::ServerToClient,
+        "Invalid direction"
+    );
+    assert!(frame.header.disable_default_response);
+    assert_eq!(frame.header.transaction_sequence, 42, "Invalid sequence");
+    assert_eq!(
+        frame.global_command(),
+        Some(GlobalCommand::ReadAttributesResponse),
+        "Invalid command"
+    );
+    assert_eq!(frame.payload, [0x05, 0x00], "Invalid payload");
+}
+
+#[test]
+fn decode_invalid_zcl_frame() {
+    // Too short
+    assert!(ZclFrame::read(&[0x18, 0x2a]).is_err());
+    // Missing manufacturer code
+    assert!(ZclFrame::read(&[0x04, 0x2a]).is_err());
+    // Reserved frame type
+    assert!(ZclFrame::read(&[0x02, 0x2a, 0x01]).is_err());
+}
+
+#[test]
+fn encode_valid_zcl_frame_with_manufacturer_code() {
+    let mut frame = ZclFrame::new(FrameType::ClusterSpecific, 0x02, vec![0x1]);
+    frame.header.manufacturer_code = Some(0x115f);
+    frame.header.transaction_sequence = 7;
+    let mut output = Vec::new();
+    frame.write(&mut output);
+    assert_eq!(output, [0x05, 0x5f, 0x11, 0x07, 0x02, 0x01]);
+}
+
+#[test]
+fn encode_valid_read_attributes() {
+    let mut payload = Vec::new();
+    write_read_attributes(&[0x0004, 0x0005], &mut payload);
+    assert_eq!(payload, [0x04, 0x00, 0x05, 0x00]);
+}
+
+#[test]
+fn decode_valid_read_attributes_response() {
+    let payload = [
+        0x05, 0x00, 0x00, 0x42, 0x03, 0x61, 0x62, 0x63, // model identifier
+        0x07, 0x00, 0x00, 0x30, 0x03, // power source
+        0x00, 0x40, 0x86, // unsupported attribute
+    ];
+    let records = ReadAttributeRecord::read_all(&payload);
+    assert!(records.is_ok());
+    let records = records.unwrap();
+    assert_eq!(records.len(), 3, "Invalid records count");
+    assert_eq!(records[0].attribute_id, 0x0005);
+    assert_eq!(records[0].status, ZclStatus::Success);
+    assert_eq!(
+        records[0].value,
+        Some(AttributeValue::CharacterString(String::from("abc")))
+    );
+    assert_eq!(records[1].value, Some(AttributeValue::Enum8(3)));
+    assert_eq!(records[2].attribute_id, 0x4000);
+    assert_eq!(records[2].status, ZclStatus::UnsupportedAttribute);
+    assert_eq!(records[2].value, None);
+}
+
+#[test]
+fn decode_truncated_read_attributes_response() {
+    assert!(ReadAttributeRecord::read_all(&[0x05, 0x00, 0x00, 0x42, 0x03, 0x61]).is_err());
+}
+
+#[test]
+fn encode_decode_attribute_values() {
+    let values = vec![
+        AttributeValue::Bool(true),
+        AttributeValue::Uint24(0x123456),
+        AttributeValue::Int16(-1234),
+        AttributeValue::Int24(-2),
+        AttributeValue::Single(21.5),
+        AttributeValue::IeeeAddress(0x00124b0001020304),
+        AttributeValue::OctetString(vec![0x1, 0x2]),
+        AttributeValue::Array(
+            DataType::Uint8,
+            vec![AttributeValue::Uint8(1), AttributeValue::Uint8(2)],
+        ),
+        AttributeValue::Structure(vec![
+            AttributeValue::Uint16(1),
+            AttributeValue::CharacterString(String::from("a")),
+        ]),
+    ];
+    for value in values {
+        let mut payload = Vec::new();
+        WriteAttributeRecord::new(0x0001, value.clone()).write(&mut payload);
+        let reports = AttributeReport::read_all(&payload);
+        assert!(reports.is_ok(), "Cannot decode {:?}", value);
+        let reports = reports.unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].attribute_id, 0x0001);
+        assert_eq!(reports[0].value, value);
+    }
+}
+
+#[test]
+fn decode_valid_write_attributes_response() {
+    let records = WriteAttributeStatus::read_all(&[0x00]).unwrap();
+    assert_eq!(
+        records,
+        [WriteAttributeStatus {
+            status: ZclStatus::Success,
+            attribute_id: None
+        }]
+    );
+    let records = WriteAttributeStatus::read_all(&[0x88, 0x00, 0x00, 0x86, 0x01, 0x00]).unwrap();
+    assert_eq!(records.len(), 2, "Invalid records count");
+    assert_eq!(records[0].status, ZclStatus::ReadOnly);
+    assert_eq!(records[0].attribute_id, Some(0x0000));
+    assert_eq!(records[1].status, ZclStatus::UnsupportedAttribute);
+    assert_eq!(records[1].attribute_id, Some(0x0001));
+}
+
+#[test]
+fn encode_valid_configure_reporting() {
+    let mut payload = Vec::new();
+    ReportingConfiguration::Reported {
+        attribute_id: 0x0000,
+        data_type: DataType::Int16,
+        min_interval: 30,
+        max_interval: 3600,
+        reportable_change: Some(AttributeValue::Int16(10)),
+    }
+    .write(&mut payload);
+    ReportingConfiguration::Reported {
+        attribute_id: 0x0000,
+        data_type: DataType::Bool,
+        min_interval: 0,
+        max_interval: 600,
+        reportable_change: None,
+    }
+    .write(&mut payload);
+    ReportingConfiguration::Received {
+        attribute_id: 0x0001,
+        timeout: 60,
+    }
+    .write(&mut payload);
+    assert_eq!(
+        payload,
+        [
+            0x00, 0x00, 0x00, 0x29, 0x1e, 0x00, 0x10, 0x0e, 0x0a, 0x00, // analog
+            0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x58, 0x02, // discrete
+            0x01, 0x01, 0x00, 0x3c, 0x00, // received
+        ]
+    );
+}
+
+#[test]
+fn decode_valid_configure_reporting_response() {
+    let records = ConfigureReportingStatus::read_all(&[0x00]).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].status, ZclStatus::Success);
+    assert_eq!(records[0].attribute_id, None);
+    let records = ConfigureReportingStatus::read_all(&[0x8c, 0x00, 0x21, 0x00]).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].status, ZclStatus::UnreportableAttribute);
+    assert_eq!(records[0].direction, Some(ReportingDirection::Reported));
+    assert_eq!(records[0].attribute_id, Some(0x0021));
+}
+
+#[test]
+fn decode_valid_read_reporting_configuration_response() {
+    let payload = [
+        0x00, 0x00, 0x00, 0x00, 0x29, 0x1e, 0x00, 0x10, 0x0e, 0x0a, 0x00, // configured
+        0x86, 0x00, 0x05, 0x00, // unsupported
+    ];
+    let records = ReportingConfigurationRecord::read_all(&payload).unwrap();
+    assert_eq!(records.len(), 2, "Invalid records count");
+    assert_eq!(records[0].status, ZclStatus::Success);
+    assert_eq!(
+        records[0].configuration,
+        Some(ReportingConfiguration::Reported {
+            attribute_id: 0x0000,
+            data_type: DataType::Int16,
+            min_interval: 30,
+            max_interval: 3600,
+            reportable_change: Some(AttributeValue::Int16(10)),
+        })
+    );
+    assert_eq!(records[1].status, ZclStatus::UnsupportedAttribute);
+    assert_eq!(records[1].attribute_id, 0x0005);
+    assert_eq!(records[1].configuration, None);
+}
+
+#[test]
+fn decode_valid_discover_responses() {
+    let response =
+        DiscoverAttributesResponse::read(&[0x01, 0x00, 0x00, 0x20, 0x05, 0x00, 0x42]).unwrap();
+    assert!(response.discovery_complete);
+    assert_eq!(
+        response.attributes,
+        [
+            (0x0000, DataType::Uint8),
+            (0x0005, DataType::CharacterString)
+        ]
+    );
+    let response = DiscoverCommandsResponse::read(&[0x00, 0x00, 0x01, 0x02]).unwrap();
+    assert!(!response.discovery_complete);
+    assert_eq!(response.commands, [0x00, 0x01, 0x02]);
+}
+
+#[test]
+fn decode_valid_default_response() {
+    let response = DefaultResponse::read(&[0x06, 0x82]).unwrap();
+    assert_eq!(response.command_id, 0x06);
+    assert_eq!(response.status, ZclStatus::UnsupportedGeneralCommand);
+}
+
+#[test]
+fn encode_valid_groups_commands() {
+    let mut output = Vec::new();
+    groups::add_group(0x0001, "Living").write(&mut output);
+    assert_eq!(
+        output,
+        vec![0x01, 0x00, 0x00, 0x01, 0x00, 0x06, b'L', b'i', b'v', b'i', b'n', b'g']
+    );
+    let mut output = Vec::new();
+    groups::get_group_membership(&[0x0001, 0x0002]).write(&mut output);
+    assert_eq!(output, vec![0x01, 0x00, 0x02, 0x02, 0x01, 0x00, 0x02, 0x00]);
+}
+
+#[test]
+fn decode_valid_groups_responses() {
+    assert_eq!(
+        groups::GroupResponse::read(&[0x8a, 0x01, 0x00]).unwrap(),
+        groups::GroupResponse {
+            status: ZclStatus::DuplicateExists,
+            group_id: 0x0001,
+        }
+    );
+    let response =
+        groups::ViewGroupResponse::read(&[0x00, 0x01, 0x00, 0x03, b'K', b'i', b't']).unwrap();
+    assert_eq!(response.name, "Kit");
+    let response = groups::ViewGroupResponse::read(&[0x8b, 0x01, 0x00]).unwrap();
+    assert_eq!(response.status, ZclStatus::NotFound);
+    assert_eq!(
+        groups::GroupMembership::read(&[0x05, 0x02, 0x01, 0x00, 0x02, 0x00]).unwrap(),
+        groups::GroupMembership {
+            capacity: 5,
+            groups: vec![0x0001, 0x0002],
+        }
+    );
+}
+
+#[test]
+fn encode_valid_scenes_commands() {
+    let mut scene = scenes::Scene::new(0x0001, 0x02);
+    scene.transition_time = 1;
+    scene.extension_field_sets = vec![
+        scenes::ExtensionFieldSet::OnOff { on: true },
+        scenes::ExtensionFieldSet::Level { level: 0xfe },
+        scenes::ExtensionFieldSet::Color {
+            x: 0x1234,
+            y: 0x5678,
+            color_temperature: None,
+        },
+    ];
+    let mut output = Vec::new();
+    scenes::add_scene(&scene).write(&mut output);
+    assert_eq!(
+        output,
+        vec![
+            0x01, 0x00, 0x00, 0x01, 0x00, 0x02, 0x01, 0x00, 0x00, 0x06, 0x00, 0x01, 0x01, 0x08,
+            0x00, 0x01, 0xfe, 0x00, 0x03, 0x04, 0x34, 0x12, 0x78, 0x56,
+        ]
+    );
+    let mut output = Vec::new();
+    scenes::recall_scene(0x0001, 0x02).write(&mut output);
+    assert_eq!(output, vec![0x01, 0x00, 0x05, 0x01, 0x00, 0x02]);
+}
+
+#[test]
+fn decode_valid_scenes_responses() {
+    assert_eq!(
+        scenes::SceneResponse::read(&[0x00, 0x01, 0x00, 0x02]).unwrap(),
+        scenes::SceneResponse {
+            status: ZclStatus::Success,
+            group_id: 0x0001,
+            scene_id: Some(0x02),
+        }
+    );
+    assert_eq!(
+        scenes::SceneResponse::read(&[0x00, 0x01, 0x00])
+            .unwrap()
+            .scene_id,
+        None
+    );
+    let mut payload = vec![0x00, 0x01, 0x00, 0x02, 0x05, 0x00, 0x02, b'T', b'V'];
+    payload.extend_from_slice(&[0x06, 0x00, 0x01, 0x00]);
+    payload.extend_from_slice(&[0x00, 0x03, 0x0d, 0x00, 0x00, 0x00, 0x00]);
+    payload.extend_from_slice(&[0x00; 7]);
+    payload.extend_from_slice(&[0x72, 0x01]);
+    let scene = scenes::read_view_scene_response(&payload).unwrap().unwrap();
+    assert_eq!(scene.transition_time, 5);
+    assert_eq!(scene.name, "TV");
+    assert_eq!(
+        scene.extension_field_sets,
+        vec![
+            scenes::ExtensionFieldSet::OnOff { on: false },
+            scenes::ExtensionFieldSet::Color {
+                x: 0,
+                y: 0,
+                color_temperature: Some(370),
+            },
+        ]
+    );
+    let mut output = Vec::new();
+    scene.extension_field_sets[1].write(&mut output);
+    assert_eq!(output, payload[13..].to_vec());
+    assert_eq!(
+        scenes::read_view_scene_response(&[0x8b, 0x01, 0x00, 0x02]).unwrap(),
+        Err(ZclStatus::NotFound)
+    );
+    assert_eq!(
+        scenes::SceneMembership::read(&[0x00, 0x0e, 0x01, 0x00, 0x02, 0x01, 0x02]).unwrap(),
+        scenes::SceneMembership {
+            status: ZclStatus::Success,
+            capacity: 14,
+            group_id: 0x0001,
+            scenes: vec![0x01, 0x02],
+        }
+    );
+}
@@ -0,0 +1,405 @@
+use byteorder::{ByteOrder, LittleEndian};
+use serde::{Deserialize, Serialize};
+
+use super::constants::DataType;
+use crate::reader::Reader;
+use crate::Error;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AttributeValue {
+    NoData,
+    Data8(u8),
+    Data16(u16),
+    Data24(u32),
+    Data32(u32),
+    Bool(bool),
+    Bitmap8(u8),
+    Bitmap16(u16),
+    Bitmap24(u32),
+    Bitmap32(u32),
+    Bitmap64(u64),
+    Uint8(u8),
+    Uint16(u16),
+    Uint24(u32),
+    Uint32(u32),
+    Uint40(u64),
+    Uint48(u64),
+    Uint56(u64),
+    Uint64(u64),
+    Int8(i8),
+    Int16(i16),
+    Int24(i32),
+    Int32(i32),
+    Int40(i64),
+    Int48(i64),
+    Int56(i64),
+    Int64(i64),
+    Enum8(u8),
+    Enum16(u16),
+    /// Half-precision float, kept as its raw bits.
+    Semi(u16),
+    Single(#[serde(with = "single_bits")] f32),
+    Double(#[serde(with = "double_bits")] f64),
+    OctetString(Vec<u8>),
+    CharacterString(String),
+    LongOctetString(Vec<u8>),
+    LongCharacterString(String),
+    Array(DataType, Vec<AttributeValue>),
+    Structure(Vec<AttributeValue>),
+    Set(DataType, Vec<AttributeValue>),
+    Bag(DataType, Vec<AttributeValue>),
+    TimeOfDay(u32),
+    Date(u32),
+    UtcTime(u32),
+    ClusterId(u16),
+    AttributeId(u16),
+    BacnetOid(u32),
+    IeeeAddress(u64),
+    SecurityKey([u8; 16]),
+}
+
+/// Floats are saved as their bits, so the NaN of invalid values survives
+/// formats without NaN such as JSON.
+mod single_bits {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(value.to_bits())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        u32::deserialize(deserializer).map(f32::from_bits)
+    }
+}
+
+mod double_bits {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(value.to_bits())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        u64::deserialize(deserializer).map(f64::from_bits)
+    }
+}
+
+impl AttributeValue {
+    /// Whether the values are the same. Unlike `==`, floats are compared by
+    /// their bits, so an invalid reading (NaN) repeated by a device is the
+    /// same value.
+    pub fn same_as(&self, other: &AttributeValue) -> bool {
+        match (self, other) {
+            (AttributeValue::Single(value), AttributeValue::Single(other)) => {
+                value.to_bits() == other.to_bits()
+            }
+            (AttributeValue::Double(value), AttributeValue::Double(other)) => {
+                value.to_bits() == other.to_bits()
+            }
+            _ => self == other,
+        }
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            AttributeValue::NoData => DataType::NoData,
+            AttributeValue::Data8(_) => DataType::Data8,
+            AttributeValue::Data16(_) => DataType::Data16,
+            AttributeValue::Data24(_) => DataType::Data24,
+            AttributeValue::Data32(_) => DataType::Data32,
+            AttributeValue::Bool(_) => DataType::Bool,
+            AttributeValue::Bitmap8(_) => DataType::Bitmap8,
+            AttributeValue::Bitmap16(_) => DataType::Bitmap16,
+            AttributeValue::Bitmap24(_) => DataType::Bitmap24,
+            AttributeValue::Bitmap32(_) => DataType::Bitmap32,
+            AttributeValue::Bitmap64(_) => DataType::Bitmap64,
+            AttributeValue::Uint8(_) => DataType::Uint8,
+            AttributeValue::Uint16(_) => DataType::Uint16,
+            AttributeValue::Uint24(_) => DataType::Uint24,
+            AttributeValue::Uint32(_) => DataType::Uint32,
+            AttributeValue::Uint40(_) => DataType::Uint40,
+            AttributeValue::Uint48(_) => DataType::Uint48,
+            AttributeValue::Uint56(_) => DataType::Uint56,
+            AttributeValue::Uint64(_) => DataType::Uint64,
+            AttributeValue::Int8(_) => DataType::Int8,
+            AttributeValue::Int16(_) => DataType::Int16,
+            AttributeValue::Int24(_) => DataType::Int24,
+            AttributeValue::Int32(_) => DataType::Int32,
+            AttributeValue::Int40(_) => DataType::Int40,
+            AttributeValue::Int48(_) => DataType::Int48,
+            AttributeValue::Int56(_) => DataType::Int56,
+            AttributeValue::Int64(_) => DataType::Int64,
+            AttributeValue::Enum8(_) => DataType::Enum8,
+            AttributeValue::Enum16(_) => DataType::Enum16,
+            AttributeValue::Semi(_) => DataType::Semi,
+            AttributeValue::Single(_) => DataType::Single,
+            AttributeValue::Double(_) => DataType::Double,
+            AttributeValue::OctetString(_) => DataType::OctetString,
+            AttributeValue::CharacterString(_) => DataType::CharacterString,
+            AttributeValue::LongOctetString(_) => DataType::LongOctetString,
+            AttributeValue::LongCharacterString(_) => DataType::LongCharacterString,
+            AttributeValue::Array(..) => DataType::Array,
+            AttributeValue::Structure(_) => DataType::Structure,
+            AttributeValue::Set(..) => DataType::Set,
+            AttributeValue::Bag(..) => DataType::Bag,
+            AttributeValue::TimeOfDay(_) => DataType::TimeOfDay,
+            AttributeValue::Date(_) => DataType::Date,
+            AttributeValue::UtcTime(_) => DataType::UtcTime,
+            AttributeValue::ClusterId(_) => DataType::ClusterId,
+            AttributeValue::AttributeId(_) => DataType::AttributeId,
+            AttributeValue::BacnetOid(_) => DataType::BacnetOid,
+            AttributeValue::IeeeAddress(_) => DataType::IeeeAddress,
+            AttributeValue::SecurityKey(_) => DataType::SecurityKey,
+        }
+    }
+
+    /// Integer view of numeric, enumeration and bitmap values.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            AttributeValue::Data8(value)
+            | AttributeValue::Bitmap8(value)
+            | AttributeValue::Uint8(value)
+            | AttributeValue::Enum8(value) => Some(*value as u64),
+            AttributeValue::Data16(value)
+            | AttributeValue::Bitmap16(value)
+            | AttributeValue::Uint16(value)
+            | AttributeValue::Enum16(value)
+            | AttributeValue::ClusterId(value)
+            | AttributeValue::AttributeId(value) => Some(*value as u64),
+            AttributeValue::Data24(value)
+            | AttributeValue::Data32(value)
+            | AttributeValue::Bitmap24(value)
+            | AttributeValue::Bitmap32(value)
+            | AttributeValue::Uint24(value)
+            | AttributeValue::Uint32(value)
+            | AttributeValue::UtcTime(value) => Some(*value as u64),
+            AttributeValue::Bitmap64(value)
+            | AttributeValue::Uint40(value)
+            | AttributeValue::Uint48(value)
+            | AttributeValue::Uint56(value)
+            | AttributeValue::Uint64(value)
+            | AttributeValue::IeeeAddress(value) => Some(*value),
+            AttributeValue::Bool(value) => Some(*value as u64),
+            AttributeValue::Int8(value) if *value >= 0 => Some(*value as u64),
+            AttributeValue::Int16(value) if *value >= 0 => Some(*value as u64),
+            AttributeValue::Int24(value) | AttributeValue::Int32(value) if *value >= 0 => {
+                Some(*value as u64)
+            }
+            AttributeValue::Int40(value)
+            | AttributeValue::Int48(value)
+            | AttributeValue::Int56(value)
+            | AttributeValue::Int64(value)
+                if *value >= 0 =>
+            {
+                Some(*value as u64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Text view of character string values.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttributeValue::CharacterString(value) | AttributeValue::LongCharacterString(value) => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn read(data_type: DataType, reader: &mut Reader) -> Result<Self, Error> {
+        let value = match data_type {
+            DataType::NoData => AttributeValue::NoData,
+            DataType::Data8 => AttributeValue::Data8(reader.u8()?),
+            DataType::Data16 => AttributeValue::Data16(reader.u16()?),
+            DataType::Data24 => AttributeValue::Data24(reader.uint(3)? as u32),
+            DataType::Data32 => AttributeValue::Data32(reader.u32()?),
+            DataType::Bool => AttributeValue::Bool(reader.u8()? == 0x1),
+            DataType::Bitmap8 => AttributeValue::Bitmap8(reader.u8()?),
+            DataType::Bitmap16 => AttributeValue::Bitmap16(reader.u16()?),
+            DataType::Bitmap24 => AttributeValue::Bitmap24(reader.uint(3)? as u32),
+            DataType::Bitmap32 => AttributeValue::Bitmap32(reader.u32()?),
+            DataType::Bitmap64 => AttributeValue::Bitmap64(reader.u64()?),
+            DataType::Uint8 => AttributeValue::Uint8(reader.u8()?),
+            DataType::Uint16 => AttributeValue::Uint16(reader.u16()?),
+            DataType::Uint24 => AttributeValue::Uint24(reader.uint(3)? as u32),
+            DataType::Uint32 => AttributeValue::Uint32(reader.u32()?),
+            DataType::Uint40 => AttributeValue::Uint40(reader.uint(5)?),
+            DataType::Uint48 => AttributeValue::Uint48(reader.uint(6)?),
+            DataType::Uint56 => AttributeValue::Uint56(reader.uint(7)?),
+            DataType::Uint64 => AttributeValue::Uint64(reader.u64()?),
+            DataType::Int8 => AttributeValue::Int8(reader.i8()?),
+            DataType::Int16 => AttributeValue::Int16(reader.int(2)? as i16),
+            DataType::Int24 => AttributeValue::Int24(reader.int(3)? as i32),
+            DataType::Int32 => AttributeValue::Int32(reader.int(4)? as i32),
+            DataType::Int40 => AttributeValue::Int40(reader.int(5)?),
+            DataType::Int48 => AttributeValue::Int48(reader.int(6)?),
+            DataType::Int56 => AttributeValue::Int56(reader.int(7)?),
+            DataType::Int64 => AttributeValue::Int64(reader.int(8)?),
+            DataType::Enum8 => AttributeValue::Enum8(reader.u8()?),
+            DataType::Enum16 => AttributeValue::Enum16(reader.u16()?),
+            DataType::Semi => AttributeValue::Semi(reader.u16()?),
+            DataType::Single => AttributeValue::Single(f32::from_bits(reader.u32()?)),
+            DataType::Double => AttributeValue::Double(f64::from_bits(reader.u64()?)),
+            DataType::OctetString => {
+                AttributeValue::OctetString(Vec::from(read_short_string(reader)?))
+            }
+            DataType::CharacterString => AttributeValue::CharacterString(
+                String::from_utf8_lossy(read_short_string(reader)?).into_owned(),
+            ),
+            DataType::LongOctetString => {
+                AttributeValue::LongOctetString(Vec::from(read_long_string(reader)?))
+            }
+            DataType::LongCharacterString => AttributeValue::LongCharacterString(
+                String::from_utf8_lossy(read_long_string(reader)?).into_owned(),
+            ),
+            DataType::Array | DataType::Set | DataType::Bag => {
+                let element_type = DataType::from_code(reader.u8()?)
+                    .ok_or(Error::Decoding("Unsupported ZCL data type"))?;
+                let count = reader.u16()?;
+                let mut elements = Vec::new();
+                // 0xffff marks an invalid (absent) collection
+                if count != 0xffff {
+                    for _ in 0..count {
+                        elements.push(AttributeValue::read(element_type, reader)?);
+                    }
+                }
+                match data_type {
+                    DataType::Array => AttributeValue::Array(element_type, elements),
+                    DataType::Set => AttributeValue::Set(element_type, elements),
+                    _ => AttributeValue::Bag(element_type, elements),
+                }
+            }
+            DataType::Structure => {
+                let count = reader.u16()?;
+                let mut elements = Vec::new();
+                if count != 0xffff {
+                    for _ in 0..count {
+                        let element_type = DataType::from_code(reader.u8()?)
+                            .ok_or(Error::Decoding("Unsupported ZCL data type"))?;
+                        elements.push(AttributeValue::read(element_type, reader)?);
+                    }
+                }
+                AttributeValue::Structure(elements)
+            }
+            DataType::TimeOfDay => AttributeValue::TimeOfDay(reader.u32()?),
+            DataType::Date => AttributeValue::Date(reader.u32()?),
+            DataType::UtcTime => AttributeValue::UtcTime(reader.u32()?),
+            DataType::ClusterId => AttributeValue::ClusterId(reader.u16()?),
+            DataType::AttributeId => AttributeValue::AttributeId(reader.u16()?),
+            DataType::BacnetOid => AttributeValue::BacnetOid(reader.u32()?),
+            DataType::IeeeAddress => AttributeValue::IeeeAddress(reader.u64()?),
+            DataType::SecurityKey => {
+                let mut key = [0; 16];
+                key.copy_from_slice(reader.bytes(16)?);
+                AttributeValue::SecurityKey(key)
+            }
+        };
+        Ok(value)
+    }
+
+    /// Writes the value without its data type identifier.
+    pub fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            AttributeValue::NoData => {}
+            AttributeValue::Data8(value)
+            | AttributeValue::Bitmap8(value)
+            | AttributeValue::Uint8(value)
+            | AttributeValue::Enum8(value) => out.push(*value),
+            AttributeValue::Bool(value) => out.push(*value as u8),
+            AttributeValue::Int8(value) => out.push(*value as u8),
+            AttributeValue::Data16(value)
+            | AttributeValue::Bitmap16(value)
+            | AttributeValue::Uint16(value)
+            | AttributeValue::Enum16(value)
+            | AttributeValue::Semi(value)
+            | AttributeValue::ClusterId(value)
+            | AttributeValue::AttributeId(value) => write_uint(out, *value as u64, 2),
+            AttributeValue::Int16(value) => write_int(out, *value as i64, 2),
+            AttributeValue::Data24(value)
+            | AttributeValue::Bitmap24(value)
+            | AttributeValue::Uint24(value) => write_uint(out, *value as u64, 3),
+            AttributeValue::Int24(value) => write_int(out, *value as i64, 3),
+            AttributeValue::Data32(value)
+            | AttributeValue::Bitmap32(value)
+            | AttributeValue::Uint32(value)
+            | AttributeValue::TimeOfDay(value)
+            | AttributeValue::Date(value)
+            | AttributeValue::UtcTime(value)
+            | AttributeValue::BacnetOid(value) => write_uint(out, *value as u64, 4),
+            AttributeValue::Int32(value) => write_int(out, *value as i64, 4),
+            AttributeValue::Uint40(value) => write_uint(out, *value, 5),
+            AttributeValue::Int40(value) => write_int(out, *value, 5),
+            AttributeValue::Uint48(value) => write_uint(out, *value, 6),
+            AttributeValue::Int48(value) => write_int(out, *value, 6),
+            AttributeValue::Uint56(value) => write_uint(out, *value, 7),
+            AttributeValue::Int56(value) => write_int(out, *value, 7),
+            AttributeValue::Bitmap64(value)
+            | AttributeValue::Uint64(value)
+            | AttributeValue::IeeeAddress(value) => write_uint(out, *value, 8),
+            AttributeValue::Int64(value) => write_int(out, *value, 8),
+            AttributeValue::Single(value) => write_uint(out, value.to_bits() as u64, 4),
+            AttributeValue::Double(value) => write_uint(out, value.to_bits(), 8),
+            AttributeValue::OctetString(value) => {
+                out.push(value.len() as u8);
+                out.extend_from_slice(value);
+            }
+            AttributeValue::CharacterString(value) => {
+                out.push(value.len() as u8);
+                out.extend_from_slice(value.as_bytes());
+            }
+            AttributeValue::LongOctetString(value) => {
+                write_uint(out, value.len() as u64, 2);
+                out.extend_from_slice(value);
+            }
+            AttributeValue::LongCharacterString(value) => {
+                write_uint(out, value.len() as u64, 2);
+                out.extend_from_slice(value.as_bytes());
+            }
+            AttributeValue::Array(element_type, elements)
+            | AttributeValue::Set(element_type, elements)
+            | AttributeValue::Bag(element_type, elements) => {
+                out.push(element_type.code());
+                write_uint(out, elements.len() as u64, 2);
+                for element in elements {
+                    element.write(out);
+                }
+            }
+            AttributeValue::Structure(elements) => {
+                write_uint(out, elements.len() as u64, 2);
+                for element in elements {
+                    out.push(element.data_type().code());
+                    element.write(out);
+                }
+            }
+            AttributeValue::SecurityKey(key) => out.extend_from_slice(key),
+        }
+    }
+}
+
+fn read_short_string<'a>(reader: &mut Reader<'a>) -> Result<&'a [u8], Error> {
+    match reader.u8()? {
+        // 0xff marks an invalid (absent) string
+        0xff => Ok(&[]),
+        len => reader.bytes(len as usize),
+    }
+}
+
+fn read_long_string<'a>(reader: &mut Reader<'a>) -> Result<&'a [u8], Error> {
+    match reader.u16()? {
+        0xffff => Ok(&[]),
+        len => reader.bytes(len as usize),
+    }
+}
+
+pub(crate) fn write_uint(out: &mut Vec<u8>, value: u64, len: usize) {
+    let mut buf = [0; 8];
+    LittleEndian::write_uint(&mut buf, value, len);
+    out.extend_from_slice(&buf[0..len]);
+}
+
+fn write_int(out: &mut Vec<u8>, value: i64, len: usize) {
+    let mut buf = [0; 8];
+    LittleEndian::write_int(&mut buf, value, len);
+    out.extend_from_slice(&buf[0..len]);
+}
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+
+pub const NWK_ADDRESS_REQUEST: u16 = 0x0000;
+pub const IEEE_ADDRESS_REQUEST: u16 = 0x0001;
+pub const NODE_DESCRIPTOR_REQUEST: u16 = 0x0002;
+pub const POWER_DESCRIPTOR_REQUEST: u16 = 0x0003;
+pub const SIMPLE_DESCRIPTOR_REQUEST: u16 = 0x0004;
+pub const ACTIVE_ENDPOINTS_REQUEST: u16 = 0x0005;
+pub const MATCH_DESCRIPTOR_REQUEST: u16 = 0x0006;
+pub const DEVICE_ANNOUNCE: u16 = 0x0013;
+pub const BIND_REQUEST: u16 = 0x0021;
+pub const UNBIND_REQUEST: u16 = 0x0022;
+pub const MGMT_LQI_REQUEST: u16 = 0x0031;
+pub const MGMT_RTG_REQUEST: u16 = 0x0032;
+pub const MGMT_BIND_REQUEST: u16 = 0x0033;
+pub const MGMT_LEAVE_REQUEST: u16 = 0x0034;
+pub const MGMT_PERMIT_JOINING_REQUEST: u16 = 0x0036;
+pub const MGMT_NWK_UPDATE_REQUEST: u16 = 0x0038;
+
+/// Responses use the cluster of the request with this bit set.
+pub const RESPONSE_CLUSTER_FLAG: u16 = 0x8000;
+
+/// Broadcast to all devices, including sleepy end devices.
+pub const BROADCAST_ALL: u16 = 0xffff;
+/// Broadcast to devices with receiver on when idle.
+pub const BROADCAST_RX_ON_WHEN_IDLE: u16 = 0xfffd;
+/// Broadcast to routers and coordinator.
+pub const BROADCAST_ROUTERS: u16 = 0xfffc;
+
+/// Returns true if the NWK address is one of the broadcast addresses.
+pub fn is_broadcast(nwk_address: u16) -> bool {
+    nwk_address >= 0xfff8
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ZdoStatus {
+    Success,
+    InvalidRequestType,
+    DeviceNotFound,
+    InvalidEndpoint,
+    NotActive,
+    NotSupported,
+    Timeout,
+    NoMatch,
+    NoEntry,
+    NoDescriptor,
+    InsufficientSpace,
+    NotPermitted,
+    TableFull,
+    NotAuthorized,
+    DeviceBindingTableFull,
+    InvalidIndex,
+}
+
+impl ZdoStatus {
+    pub fn code(self) -> u8 {
+        match self {
+            ZdoStatus::Success => 0x00,
+            ZdoStatus::InvalidRequestType => 0x80,
+            ZdoStatus::DeviceNotFound => 0x81,
+            ZdoStatus::InvalidEndpoint => 0x82,
+            ZdoStatus::NotActive => 0x83,
+            ZdoStatus::NotSupported => 0x84,
+            ZdoStatus::Timeout => 0x85,
+            ZdoStatus::NoMatch => 0x86,
+            ZdoStatus::NoEntry => 0x88,
+            ZdoStatus::NoDescriptor => 0x89,
+            ZdoStatus::InsufficientSpace => 0x8a,
+            ZdoStatus::NotPermitted => 0x8b,
+            ZdoStatus::TableFull => 0x8c,
+            ZdoStatus::NotAuthorized => 0x8d,
+            ZdoStatus::DeviceBindingTableFull => 0x8e,
+            ZdoStatus::InvalidIndex => 0x8f,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x00 => Some(ZdoStatus::Success),
+            0x80 => Some(ZdoStatus::InvalidRequestType),
+            0x81 => Some(ZdoStatus::DeviceNotFound),
+            0x82 => Some(ZdoStatus::InvalidEndpoint),
+            0x83 => Some(ZdoStatus::NotActive),
+            0x84 => Some(ZdoStatus::NotSupported),
+            0x85 => Some(ZdoStatus::Timeout),
+            0x86 => Some(ZdoStatus::NoMatch),
+            0x88 => Some(ZdoStatus::NoEntry),
+            0x89 => Some(ZdoStatus::NoDescriptor),
+            0x8a => Some(ZdoStatus::InsufficientSpace),
+            0x8b => Some(ZdoStatus::NotPermitted),
+            0x8c => Some(ZdoStatus::TableFull),
+            0x8d => Some(ZdoStatus::NotAuthorized),
+            0x8e => Some(ZdoStatus::DeviceBindingTableFull),
+            0x8f => Some(ZdoStatus::InvalidIndex),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum LogicalType {
+    Coordinator,
+    Router,
+    EndDevice,
+}
+
+impl LogicalType {
+    pub fn code(self) -> u8 {
+        match self {
+            LogicalType::Coordinator => 0x0,
+            LogicalType::Router => 0x1,
+            LogicalType::EndDevice => 0x2,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x0 => Some(LogicalType::Coordinator),
+            0x1 => Some(LogicalType::Router),
+            0x2 => Some(LogicalType::EndDevice),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Relationship {
+    Parent,
+    Child,
+    Sibling,
+    None,
+    PreviousChild,
+}
+
+impl Relationship {
+    pub fn code(self) -> u8 {
+        match self {
+            Relationship::Parent => 0x0,
+            Relationship::Child => 0x1,
+            Relationship::Sibling => 0x2,
+            Relationship::None => 0x3,
+            Relationship::PreviousChild => 0x4,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x0 => Some(Relationship::Parent),
+            0x1 => Some(Relationship::Child),
+            0x2 => Some(Relationship::Sibling),
+            0x3 => Some(Relationship::None),
+            0x4 => Some(Relationship::PreviousChild),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RouteStatus {
+    Active,
+    DiscoveryUnderway,
+    DiscoveryFailed,
+    Inactive,
+    ValidationUnderway,
+}
+
+impl RouteStatus {
+    pub fn code(self) -> u8 {
+        match self {
+            RouteStatus::Active => 0x0,
+            RouteStatus::DiscoveryUnderway => 0x1,
+            RouteStatus::DiscoveryFailed => 0x2,
+            RouteStatus::Inactive => 0x3,
+            RouteStatus::ValidationUnderway => 0x4,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x0 => Some(RouteStatus::Active),
+            0x1 => Some(RouteStatus::DiscoveryUnderway),
+            0x2 => Some(RouteStatus::DiscoveryFailed),
+            0x3 => Some(RouteStatus::Inactive),
+            0x4 => Some(RouteStatus::ValidationUnderway),
+            _ => None,
+        }
+    }
+}
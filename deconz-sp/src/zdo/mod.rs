@@ -0,0 +1,270 @@
+//! Zigbee Device Profile frames, exchanged with the ZDO endpoint (0) of remote
+//! devices on profile 0x0000.
+
+use crate::reader::Reader;
+use crate::zcl::write_uint;
+use crate::Error;
+
+mod constants;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use constants::*;
+pub use types::*;
+
+pub const ZDO_PROFILE_ID: u16 = 0x0000;
+pub const ZDO_ENDPOINT: u8 = 0x00;
+
+#[derive(Debug, Clone)]
+pub struct ZdoFrame {
+    pub transaction_sequence: u8,
+    pub payload: Vec<u8>,
+}
+
+impl ZdoFrame {
+    pub fn read(input: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(input);
+        let transaction_sequence = reader.u8()?;
+        Ok(ZdoFrame {
+            transaction_sequence,
+            payload: Vec::from(reader.rest()),
+        })
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.transaction_sequence);
+        out.extend_from_slice(&self.payload);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AddressRequestType {
+    /// Only the address of the device.
+    Single,
+    /// The address of the device, and the addresses of its associated devices.
+    Extended,
+}
+
+impl AddressRequestType {
+    pub fn code(self) -> u8 {
+        match self {
+            AddressRequestType::Single => 0x0,
+            AddressRequestType::Extended => 0x1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NwkUpdate {
+    /// Measures the energy on each channel of the mask, `count` times.
+    EnergyScan {
+        channels: u32,
+        duration: u8,
+        count: u8,
+    },
+    /// Moves the network to the single channel of the mask.
+    ChannelChange { channels: u32, nwk_update_id: u8 },
+    /// Updates the channel mask and the network manager address.
+    ManagerUpdate {
+        channels: u32,
+        nwk_update_id: u8,
+        nwk_manager_address: u16,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZdoRequest {
+    NwkAddress {
+        ieee_address: u64,
+        request_type: AddressRequestType,
+        start_index: u8,
+    },
+    IeeeAddress {
+        nwk_address: u16,
+        request_type: AddressRequestType,
+        start_index: u8,
+    },
+    NodeDescriptor {
+        nwk_address: u16,
+    },
+    PowerDescriptor {
+        nwk_address: u16,
+    },
+    SimpleDescriptor {
+        nwk_address: u16,
+        endpoint: u8,
+    },
+    ActiveEndpoints {
+        nwk_address: u16,
+    },
+    MatchDescriptor {
+        nwk_address: u16,
+        profile_id: u16,
+        input_clusters: Vec<u16>,
+        output_clusters: Vec<u16>,
+    },
+    DeviceAnnounce(DeviceAnnounce),
+    Bind(BindingEntry),
+    Unbind(BindingEntry),
+    MgmtLqi {
+        start_index: u8,
+    },
+    MgmtRtg {
+        start_index: u8,
+    },
+    MgmtBind {
+        start_index: u8,
+    },
+    MgmtLeave {
+        ieee_address: u64,
+        remove_children: bool,
+        rejoin: bool,
+    },
+    MgmtPermitJoining {
+        duration: u8,
+        trust_center_significance: bool,
+    },
+    MgmtNwkUpdate(NwkUpdate),
+}
+
+impl ZdoRequest {
+    pub fn cluster_id(&self) -> u16 {
+        match self {
+            ZdoRequest::NwkAddress { .. } => NWK_ADDRESS_REQUEST,
+            ZdoRequest::IeeeAddress { .. } => IEEE_ADDRESS_REQUEST,
+            ZdoRequest::NodeDescriptor { .. } => NODE_DESCRIPTOR_REQUEST,
+            ZdoRequest::PowerDescriptor { .. } => POWER_DESCRIPTOR_REQUEST,
+            ZdoRequest::SimpleDescriptor { .. } => SIMPLE_DESCRIPTOR_REQUEST,
+            ZdoRequest::ActiveEndpoints { .. } => ACTIVE_ENDPOINTS_REQUEST,
+            ZdoRequest::MatchDescriptor { .. } => MATCH_DESCRIPTOR_REQUEST,
+            ZdoRequest::DeviceAnnounce(_) => DEVICE_ANNOUNCE,
+            ZdoRequest::Bind(_) => BIND_REQUEST,
+            ZdoRequest::Unbind(_) => UNBIND_REQUEST,
+            ZdoRequest::MgmtLqi { .. } => MGMT_LQI_REQUEST,
+            ZdoRequest::MgmtRtg { .. } => MGMT_RTG_REQUEST,
+            ZdoRequest::MgmtBind { .. } => MGMT_BIND_REQUEST,
+            ZdoRequest::MgmtLeave { .. } => MGMT_LEAVE_REQUEST,
+            ZdoRequest::MgmtPermitJoining { .. } => MGMT_PERMIT_JOINING_REQUEST,
+            ZdoRequest::MgmtNwkUpdate(_) => MGMT_NWK_UPDATE_REQUEST,
+        }
+    }
+
+    /// Device announces and network updates other than energy scans are not
+    /// answered.
+    pub fn has_response(&self) -> bool {
+        match self {
+            ZdoRequest::DeviceAnnounce(_) => false,
+            ZdoRequest::MgmtNwkUpdate(update) => matches!(update, NwkUpdate::EnergyScan { .. }),
+            _ => true,
+        }
+    }
+
+    /// Writes the request payload, without the transaction sequence number.
+    pub fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            ZdoRequest::NwkAddress {
+                ieee_address,
+                request_type,
+                start_index,
+            } => {
+                write_uint(out, *ieee_address, 8);
+                out.push(request_type.code());
+                out.push(*start_index);
+            }
+            ZdoRequest::IeeeAddress {
+                nwk_address,
+                request_type,
+                start_index,
+            } => {
+                write_uint(out, *nwk_address as u64, 2);
+                out.push(request_type.code());
+                out.push(*start_index);
+            }
+            ZdoRequest::NodeDescriptor { nwk_address }
+            | ZdoRequest::PowerDescriptor { nwk_address }
+            | ZdoRequest::ActiveEndpoints { nwk_address } => {
+                write_uint(out, *nwk_address as u64, 2);
+            }
+            ZdoRequest::SimpleDescriptor {
+                nwk_address,
+                endpoint,
+            } => {
+                write_uint(out, *nwk_address as u64, 2);
+                out.push(*endpoint);
+            }
+            ZdoRequest::MatchDescriptor {
+                nwk_address,
+                profile_id,
+                input_clusters,
+                output_clusters,
+            } => {
+                write_uint(out, *nwk_address as u64, 2);
+                write_uint(out, *profile_id as u64, 2);
+                for clusters in &[input_clusters, output_clusters] {
+                    out.push(clusters.len() as u8);
+                    for cluster_id in clusters.iter() {
+                        write_uint(out, *cluster_id as u64, 2);
+                    }
+                }
+            }
+            ZdoRequest::DeviceAnnounce(announce) => announce.write(out),
+            ZdoRequest::Bind(entry) | ZdoRequest::Unbind(entry) => entry.write(out),
+            ZdoRequest::MgmtLqi { start_index }
+            | ZdoRequest::MgmtRtg { start_index }
+            | ZdoRequest::MgmtBind { start_index } => out.push(*start_index),
+            ZdoRequest::MgmtLeave {
+                ieee_address,
+                remove_children,
+                rejoin,
+            } => {
+                write_uint(out, *ieee_address, 8);
+                let mut flags = 0;
+                if *remove_children {
+                    flags |= 0x40;
+                }
+                if *rejoin {
+                    flags |= 0x80;
+                }
+                out.push(flags);
+            }
+            ZdoRequest::MgmtPermitJoining {
+                duration,
+                trust_center_significance,
+            } => {
+                out.push(*duration);
+                out.push(*trust_center_significance as u8);
+            }
+            ZdoRequest::MgmtNwkUpdate(update) => match update {
+                NwkUpdate::EnergyScan {
+                    channels,
+                    duration,
+                    count,
+                } => {
+                    write_uint(out, *channels as u64, 4);
+                    out.push(*duration);
+                    out.push(*count);
+                }
+                NwkUpdate::ChannelChange {
+                    channels,
+                    nwk_update_id,
+                } => {
+                    write_uint(out, *channels as u64, 4);
+                    out.push(0xfe);
+                    out.push(*nwk_update_id);
+                }
+                NwkUpdate::ManagerUpdate {
+                    channels,
+                    nwk_update_id,
+                    nwk_manager_address,
+                } => {
+                    write_uint(out, *channels as u64, 4);
+                    out.push(0xff);
+                    out.push(*nwk_update_id);
+                    write_uint(out, *nwk_manager_address as u64, 2);
+                }
+            },
+        }
+    }
+}
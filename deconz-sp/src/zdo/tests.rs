@@ -0,0 +1,239 @@
+use super::*;
+
+#[test]
+fn encode_valid_zdo_frame() {
+    let request = ZdoRequest::SimpleDescriptor {
+        nwk_address: 0x1234,
+        endpoint: 0x01,
+    };
+    let mut payload = Vec::new();
+    request.write(&mut payload);
+    let mut out = Vec::new();
+    ZdoFrame {
+        transaction_sequence: 0x2a,
+        payload,
+    }
+    .write(&mut out);
+    assert_eq!(request.cluster_id(), SIMPLE_DESCRIPTOR_REQUEST);
+    assert_eq!(out, [0x2a, 0x34, 0x12, 0x01]);
+}
+
+#[test]
+fn decode_valid_zdo_frame() {
+    let frame = ZdoFrame::read(&[0x2a, 0x00, 0x34, 0x12]).unwrap();
+    assert_eq!(frame.transaction_sequence, 0x2a, "Invalid sequence");
+    assert_eq!(frame.payload, [0x00, 0x34, 0x12], "Invalid payload");
+    assert!(ZdoFrame::read(&[]).is_err());
+}
+
+#[test]
+fn encode_valid_requests() {
+    let mut out = Vec::new();
+    ZdoRequest::NwkAddress {
+        ieee_address: 0x0011_2233_4455_6677,
+        request_type: AddressRequestType::Extended,
+        start_index: 0,
+    }
+    .write(&mut out);
+    assert_eq!(
+        out,
+        [0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x01, 0x00]
+    );
+
+    out.clear();
+    ZdoRequest::MatchDescriptor {
+        nwk_address: 0xfffd,
+        profile_id: 0x0104,
+        input_clusters: vec![0x0006],
+        output_clusters: vec![],
+    }
+    .write(&mut out);
+    assert_eq!(out, [0xfd, 0xff, 0x04, 0x01, 0x01, 0x06, 0x00, 0x00]);
+
+    out.clear();
+    ZdoRequest::MgmtLeave {
+        ieee_address: 0x0011_2233_4455_6677,
+        remove_children: false,
+        rejoin: true,
+    }
+    .write(&mut out);
+    assert_eq!(out, [0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x80]);
+
+    out.clear();
+    ZdoRequest::MgmtNwkUpdate(NwkUpdate::ChannelChange {
+        channels: 1 << 15,
+        nwk_update_id: 3,
+    })
+    .write(&mut out);
+    assert_eq!(out, [0x00, 0x80, 0x00, 0x00, 0xfe, 0x03]);
+}
+
+#[test]
+fn requests_with_response() {
+    assert!(ZdoRequest::MgmtLqi { start_index: 0 }.has_response());
+    assert!(ZdoRequest::MgmtNwkUpdate(NwkUpdate::EnergyScan {
+        channels: 0x07ff_f800,
+        duration: 2,
+        count: 1,
+    })
+    .has_response());
+    assert!(!ZdoRequest::MgmtNwkUpdate(NwkUpdate::ChannelChange {
+        channels: 1 << 11,
+        nwk_update_id: 1,
+    })
+    .has_response());
+}
+
+#[test]
+fn encode_valid_binding_entry() {
+    let entry = BindingEntry {
+        source_address: 0x0011_2233_4455_6677,
+        source_endpoint: 0x01,
+        cluster_id: 0x0006,
+        destination: BindingTarget::Device {
+            ieee_address: 0x8899_aabb_ccdd_eeff,
+            endpoint: 0x01,
+        },
+    };
+    let mut out = Vec::new();
+    ZdoRequest::Bind(entry).write(&mut out);
+    assert_eq!(
+        out,
+        [
+            0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x01, 0x06, 0x00, 0x03, 0xff, 0xee,
+            0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88, 0x01
+        ]
+    );
+}
+
+#[test]
+fn decode_valid_address_response() {
+    let response = AddressResponse::read(&[
+        0x00, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x34, 0x12, 0x02, 0x00, 0x01, 0x00,
+        0x02, 0x00,
+    ])
+    .unwrap();
+    assert_eq!(response.ieee_address, 0x0011_2233_4455_6677);
+    assert_eq!(response.nwk_address, 0x1234);
+    assert_eq!(response.associated_devices, vec![0x0001, 0x0002]);
+}
+
+#[test]
+fn decode_error_status() {
+    match AddressResponse::read(&[0x81]) {
+        Err(Error::ZdoStatus(ZdoStatus::DeviceNotFound)) => (),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn decode_valid_node_descriptor() {
+    let (nwk_address, descriptor) = NodeDescriptor::read_response(&[
+        0x00, 0x34, 0x12, 0x01, 0x40, 0x8e, 0x7c, 0x11, 0x52, 0x52, 0x00, 0x00, 0x2c, 0x52, 0x00,
+        0x00,
+    ])
+    .unwrap();
+    assert_eq!(nwk_address, 0x1234);
+    assert_eq!(descriptor.logical_type, LogicalType::Router);
+    assert_eq!(descriptor.frequency_band, 0x08);
+    assert!(descriptor.mac_capabilities.router);
+    assert!(descriptor.mac_capabilities.mains_powered);
+    assert!(descriptor.mac_capabilities.rx_on_when_idle);
+    assert!(descriptor.mac_capabilities.allocate_address);
+    assert_eq!(descriptor.mac_capabilities.byte(), 0x8e);
+    assert_eq!(descriptor.manufacturer_code, 0x117c);
+    assert_eq!(descriptor.maximum_buffer_size, 0x52);
+}
+
+#[test]
+fn decode_valid_simple_descriptor() {
+    let (nwk_address, descriptor) = SimpleDescriptor::read_response(&[
+        0x00, 0x34, 0x12, 0x0e, 0x01, 0x04, 0x01, 0x00, 0x01, 0x01, 0x02, 0x00, 0x00, 0x06, 0x00,
+        0x01, 0x19, 0x00,
+    ])
+    .unwrap();
+    assert_eq!(nwk_address, 0x1234);
+    assert_eq!(descriptor.endpoint, 0x01);
+    assert_eq!(descriptor.profile_id, 0x0104);
+    assert_eq!(descriptor.device_id, 0x0100);
+    assert_eq!(descriptor.device_version, 1);
+    assert_eq!(descriptor.input_clusters, vec![0x0000, 0x0006]);
+    assert_eq!(descriptor.output_clusters, vec![0x0019]);
+}
+
+#[test]
+fn decode_valid_active_endpoints() {
+    let (nwk_address, endpoints) =
+        read_endpoints_response(&[0x00, 0x34, 0x12, 0x02, 0x01, 0xf2]).unwrap();
+    assert_eq!(nwk_address, 0x1234);
+    assert_eq!(endpoints, vec![0x01, 0xf2]);
+}
+
+#[test]
+fn decode_valid_neighbor_table() {
+    let page = TablePage::<Neighbor>::read(&[
+        0x00, 0x03, 0x00, 0x01, 0xdd, 0xdd, 0xdd, 0xdd, 0xdd, 0xdd, 0xdd, 0xdd, 0x77, 0x66, 0x55,
+        0x44, 0x33, 0x22, 0x11, 0x00, 0x34, 0x12, 0x25, 0x02, 0x01, 0xa0,
+    ])
+    .unwrap();
+    assert_eq!(page.total_entries, 3);
+    assert!(page.has_more());
+    let neighbor = &page.entries[0];
+    assert_eq!(neighbor.ieee_address, 0x0011_2233_4455_6677);
+    assert_eq!(neighbor.nwk_address, 0x1234);
+    assert_eq!(neighbor.device_type, Some(LogicalType::Router));
+    assert_eq!(neighbor.rx_on_when_idle, Some(true));
+    assert_eq!(neighbor.relationship, Relationship::Sibling);
+    assert_eq!(neighbor.permit_joining, None);
+    assert_eq!(neighbor.depth, 1);
+    assert_eq!(neighbor.lqi, 0xa0);
+}
+
+#[test]
+fn decode_valid_routing_table() {
+    let page =
+        TablePage::<Route>::read(&[0x00, 0x01, 0x00, 0x01, 0x34, 0x12, 0x10, 0x78, 0x56]).unwrap();
+    assert!(!page.has_more());
+    let route = &page.entries[0];
+    assert_eq!(route.destination, 0x1234);
+    assert_eq!(route.status, RouteStatus::Active);
+    assert!(route.many_to_one);
+    assert_eq!(route.next_hop, 0x5678);
+}
+
+#[test]
+fn decode_valid_device_announce() {
+    let announce = DeviceAnnounce::read(&[
+        0x34, 0x12, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x80,
+    ])
+    .unwrap();
+    assert_eq!(announce.nwk_address, 0x1234);
+    assert_eq!(announce.ieee_address, 0x0011_2233_4455_6677);
+    assert!(announce.capabilities.allocate_address);
+    assert!(!announce.capabilities.router);
+    let mut out = Vec::new();
+    announce.write(&mut out);
+    assert_eq!(
+        out,
+        [0x34, 0x12, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x80]
+    );
+}
+
+#[test]
+fn decode_valid_nwk_update_notify() {
+    let notify = NwkUpdateNotify::read(&[
+        0x00, 0x00, 0x00, 0x08, 0x00, 0x10, 0x00, 0x02, 0x00, 0x02, 0x50, 0x60,
+    ])
+    .unwrap();
+    assert_eq!(notify.scanned_channels, 0x0008_0000);
+    assert_eq!(notify.total_transmissions, 16);
+    assert_eq!(notify.transmission_failures, 2);
+    assert_eq!(notify.energy_values, vec![0x50, 0x60]);
+}
+
+#[test]
+fn broadcast_addresses() {
+    assert!(is_broadcast(BROADCAST_ALL));
+    assert!(is_broadcast(BROADCAST_ROUTERS));
+    assert!(!is_broadcast(0x0000));
+}
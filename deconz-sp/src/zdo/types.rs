@@ -0,0 +1,476 @@
+use serde::{Deserialize, Serialize};
+
+use super::constants::{LogicalType, Relationship, RouteStatus, ZdoStatus};
+use crate::reader::Reader;
+use crate::zcl::write_uint;
+use crate::Error;
+
+/// Reads the status leading every response, and fails unless it is a success.
+pub(crate) fn read_status(reader: &mut Reader) -> Result<(), Error> {
+    match ZdoStatus::from_code(reader.u8()?) {
+        None => Err(Error::Decoding("Unknown ZDO status")),
+        Some(ZdoStatus::Success) => Ok(()),
+        Some(status) => Err(Error::ZdoStatus(status)),
+    }
+}
+
+/// Capability flags sent by a device when it joins the network.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct MacCapabilities {
+    pub alternate_pan_coordinator: bool,
+    /// Full function device, i.e. a router.
+    pub router: bool,
+    pub mains_powered: bool,
+    pub rx_on_when_idle: bool,
+    pub security: bool,
+    pub allocate_address: bool,
+}
+
+impl MacCapabilities {
+    pub fn from_byte(flags: u8) -> Self {
+        MacCapabilities {
+            alternate_pan_coordinator: flags & 0x01 != 0,
+            router: flags & 0x02 != 0,
+            mains_powered: flags & 0x04 != 0,
+            rx_on_when_idle: flags & 0x08 != 0,
+            security: flags & 0x40 != 0,
+            allocate_address: flags & 0x80 != 0,
+        }
+    }
+
+    pub fn byte(&self) -> u8 {
+        let mut flags = 0;
+        if self.alternate_pan_coordinator {
+            flags |= 0x01;
+        }
+        if self.router {
+            flags |= 0x02;
+        }
+        if self.mains_powered {
+            flags |= 0x04;
+        }
+        if self.rx_on_when_idle {
+            flags |= 0x08;
+        }
+        if self.security {
+            flags |= 0x40;
+        }
+        if self.allocate_address {
+            flags |= 0x80;
+        }
+        flags
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressResponse {
+    pub ieee_address: u64,
+    pub nwk_address: u16,
+    pub start_index: u8,
+    /// Only filled for extended requests.
+    pub associated_devices: Vec<u16>,
+}
+
+impl AddressResponse {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        read_status(&mut reader)?;
+        let ieee_address = reader.u64()?;
+        let nwk_address = reader.u16()?;
+        let mut start_index = 0;
+        let mut associated_devices = Vec::new();
+        if !reader.is_empty() {
+            let count = reader.u8()?;
+            if count > 0 {
+                start_index = reader.u8()?;
+                for _ in 0..count {
+                    associated_devices.push(reader.u16()?);
+                }
+            }
+        }
+        Ok(AddressResponse {
+            ieee_address,
+            nwk_address,
+            start_index,
+            associated_devices,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeDescriptor {
+    pub logical_type: LogicalType,
+    pub complex_descriptor_available: bool,
+    pub user_descriptor_available: bool,
+    pub aps_flags: u8,
+    pub frequency_band: u8,
+    pub mac_capabilities: MacCapabilities,
+    pub manufacturer_code: u16,
+    pub maximum_buffer_size: u8,
+    pub maximum_incoming_transfer_size: u16,
+    pub server_mask: u16,
+    pub maximum_outgoing_transfer_size: u16,
+    pub descriptor_capabilities: u8,
+}
+
+impl NodeDescriptor {
+    /// Reads a Node_Desc_rsp payload.
+    pub fn read_response(payload: &[u8]) -> Result<(u16, Self), Error> {
+        let mut reader = Reader::new(payload);
+        read_status(&mut reader)?;
+        let nwk_address = reader.u16()?;
+        let flags = reader.u8()?;
+        let logical_type =
+            LogicalType::from_code(flags & 0x7).ok_or(Error::Decoding("Unknown logical type"))?;
+        let band = reader.u8()?;
+        Ok((
+            nwk_address,
+            NodeDescriptor {
+                logical_type,
+                complex_descriptor_available: flags & 0x8 != 0,
+                user_descriptor_available: flags & 0x10 != 0,
+                aps_flags: band & 0x7,
+                frequency_band: band >> 3,
+                mac_capabilities: MacCapabilities::from_byte(reader.u8()?),
+                manufacturer_code: reader.u16()?,
+                maximum_buffer_size: reader.u8()?,
+                maximum_incoming_transfer_size: reader.u16()?,
+                server_mask: reader.u16()?,
+                maximum_outgoing_transfer_size: reader.u16()?,
+                descriptor_capabilities: reader.u8()?,
+            },
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerDescriptor {
+    pub current_power_mode: u8,
+    /// Bitmap: 0x1 mains, 0x2 rechargeable battery, 0x4 disposable battery.
+    pub available_power_sources: u8,
+    pub current_power_source: u8,
+    /// 0x0 critical, 0x4 33%, 0x8 66%, 0xc 100%.
+    pub current_power_source_level: u8,
+}
+
+impl PowerDescriptor {
+    /// Reads a Power_Desc_rsp payload.
+    pub fn read_response(payload: &[u8]) -> Result<(u16, Self), Error> {
+        let mut reader = Reader::new(payload);
+        read_status(&mut reader)?;
+        let nwk_address = reader.u16()?;
+        let mode = reader.u8()?;
+        let source = reader.u8()?;
+        Ok((
+            nwk_address,
+            PowerDescriptor {
+                current_power_mode: mode & 0xf,
+                available_power_sources: mode >> 4,
+                current_power_source: source & 0xf,
+                current_power_source_level: source >> 4,
+            },
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimpleDescriptor {
+    pub endpoint: u8,
+    pub profile_id: u16,
+    pub device_id: u16,
+    pub device_version: u8,
+    pub input_clusters: Vec<u16>,
+    pub output_clusters: Vec<u16>,
+}
+
+fn read_clusters(reader: &mut Reader) -> Result<Vec<u16>, Error> {
+    let count = reader.u8()?;
+    let mut clusters = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        clusters.push(reader.u16()?);
+    }
+    Ok(clusters)
+}
+
+impl SimpleDescriptor {
+    /// Reads a Simple_Desc_rsp payload.
+    pub fn read_response(payload: &[u8]) -> Result<(u16, Self), Error> {
+        let mut reader = Reader::new(payload);
+        read_status(&mut reader)?;
+        let nwk_address = reader.u16()?;
+        let _length = reader.u8()?;
+        Ok((
+            nwk_address,
+            SimpleDescriptor {
+                endpoint: reader.u8()?,
+                profile_id: reader.u16()?,
+                device_id: reader.u16()?,
+                device_version: reader.u8()? & 0xf,
+                input_clusters: read_clusters(&mut reader)?,
+                output_clusters: read_clusters(&mut reader)?,
+            },
+        ))
+    }
+}
+
+/// Reads an Active_EP_rsp or Match_Desc_rsp payload.
+pub fn read_endpoints_response(payload: &[u8]) -> Result<(u16, Vec<u8>), Error> {
+    let mut reader = Reader::new(payload);
+    read_status(&mut reader)?;
+    let nwk_address = reader.u16()?;
+    let count = reader.u8()?;
+    Ok((nwk_address, Vec::from(reader.bytes(count as usize)?)))
+}
+
+/// Reads the payload of responses only made of a status, such as Bind_rsp.
+pub fn read_status_response(payload: &[u8]) -> Result<(), Error> {
+    read_status(&mut Reader::new(payload))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeviceAnnounce {
+    pub nwk_address: u16,
+    pub ieee_address: u64,
+    pub capabilities: MacCapabilities,
+}
+
+impl DeviceAnnounce {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        Ok(DeviceAnnounce {
+            nwk_address: reader.u16()?,
+            ieee_address: reader.u64()?,
+            capabilities: MacCapabilities::from_byte(reader.u8()?),
+        })
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        write_uint(out, self.nwk_address as u64, 2);
+        write_uint(out, self.ieee_address, 8);
+        out.push(self.capabilities.byte());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BindingTarget {
+    Group(u16),
+    Device { ieee_address: u64, endpoint: u8 },
+}
+
+/// Destination of a binding created with `Client::bind_cluster`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindingDestination {
+    /// The endpoint of the client, on the coordinator.
+    Coordinator,
+    Group(u16),
+    Device {
+        ieee_address: u64,
+        endpoint: u8,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BindingEntry {
+    pub source_address: u64,
+    pub source_endpoint: u8,
+    pub cluster_id: u16,
+    pub destination: BindingTarget,
+}
+
+impl BindingEntry {
+    pub fn write(&self, out: &mut Vec<u8>) {
+        write_uint(out, self.source_address, 8);
+        out.push(self.source_endpoint);
+        write_uint(out, self.cluster_id as u64, 2);
+        match self.destination {
+            BindingTarget::Group(group) => {
+                out.push(0x01);
+                write_uint(out, group as u64, 2);
+            }
+            BindingTarget::Device {
+                ieee_address,
+                endpoint,
+            } => {
+                out.push(0x03);
+                write_uint(out, ieee_address, 8);
+                out.push(endpoint);
+            }
+        }
+    }
+}
+
+impl BindingEntry {
+    fn read_entry(reader: &mut Reader) -> Result<Self, Error> {
+        let source_address = reader.u64()?;
+        let source_endpoint = reader.u8()?;
+        let cluster_id = reader.u16()?;
+        let destination = match reader.u8()? {
+            0x01 => BindingTarget::Group(reader.u16()?),
+            0x03 => BindingTarget::Device {
+                ieee_address: reader.u64()?,
+                endpoint: reader.u8()?,
+            },
+            _ => return Err(Error::Decoding("Unknown address mode for binding")),
+        };
+        Ok(BindingEntry {
+            source_address,
+            source_endpoint,
+            cluster_id,
+            destination,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbor {
+    pub extended_pan_id: u64,
+    pub ieee_address: u64,
+    pub nwk_address: u16,
+    /// `None` when the device type is unknown.
+    pub device_type: Option<LogicalType>,
+    pub rx_on_when_idle: Option<bool>,
+    pub relationship: Relationship,
+    pub permit_joining: Option<bool>,
+    pub depth: u8,
+    pub lqi: u8,
+}
+
+fn tristate(code: u8) -> Option<bool> {
+    match code {
+        0x0 => Some(false),
+        0x1 => Some(true),
+        _ => None,
+    }
+}
+
+impl Neighbor {
+    fn read_entry(reader: &mut Reader) -> Result<Self, Error> {
+        let extended_pan_id = reader.u64()?;
+        let ieee_address = reader.u64()?;
+        let nwk_address = reader.u16()?;
+        let flags = reader.u8()?;
+        let relationship = Relationship::from_code((flags >> 4) & 0x7)
+            .ok_or(Error::Decoding("Unknown neighbor relationship"))?;
+        Ok(Neighbor {
+            extended_pan_id,
+            ieee_address,
+            nwk_address,
+            device_type: LogicalType::from_code(flags & 0x3),
+            rx_on_when_idle: tristate((flags >> 2) & 0x3),
+            relationship,
+            permit_joining: tristate(reader.u8()? & 0x3),
+            depth: reader.u8()?,
+            lqi: reader.u8()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub destination: u16,
+    pub status: RouteStatus,
+    pub memory_constrained: bool,
+    pub many_to_one: bool,
+    pub route_record_required: bool,
+    pub next_hop: u16,
+}
+
+impl Route {
+    fn read_entry(reader: &mut Reader) -> Result<Self, Error> {
+        let destination = reader.u16()?;
+        let flags = reader.u8()?;
+        let status =
+            RouteStatus::from_code(flags & 0x7).ok_or(Error::Decoding("Unknown route status"))?;
+        Ok(Route {
+            destination,
+            status,
+            memory_constrained: flags & 0x08 != 0,
+            many_to_one: flags & 0x10 != 0,
+            route_record_required: flags & 0x20 != 0,
+            next_hop: reader.u16()?,
+        })
+    }
+}
+
+/// One page of a remote table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TablePage<T> {
+    pub total_entries: u8,
+    pub start_index: u8,
+    pub entries: Vec<T>,
+}
+
+fn read_page<T>(
+    payload: &[u8],
+    read_entry: fn(&mut Reader) -> Result<T, Error>,
+) -> Result<TablePage<T>, Error> {
+    let mut reader = Reader::new(payload);
+    read_status(&mut reader)?;
+    let total_entries = reader.u8()?;
+    let start_index = reader.u8()?;
+    let count = reader.u8()?;
+    let mut entries = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        entries.push(read_entry(&mut reader)?);
+    }
+    Ok(TablePage {
+        total_entries,
+        start_index,
+        entries,
+    })
+}
+
+impl TablePage<Neighbor> {
+    /// Reads a Mgmt_Lqi_rsp payload.
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        read_page(payload, Neighbor::read_entry)
+    }
+}
+
+impl TablePage<Route> {
+    /// Reads a Mgmt_Rtg_rsp payload.
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        read_page(payload, Route::read_entry)
+    }
+}
+
+impl TablePage<BindingEntry> {
+    /// Reads a Mgmt_Bind_rsp payload.
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        read_page(payload, BindingEntry::read_entry)
+    }
+}
+
+impl<T> TablePage<T> {
+    /// Returns true if entries remain after this page.
+    pub fn has_more(&self) -> bool {
+        !self.entries.is_empty()
+            && (self.start_index as usize + self.entries.len()) < self.total_entries as usize
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NwkUpdateNotify {
+    pub scanned_channels: u32,
+    pub total_transmissions: u16,
+    pub transmission_failures: u16,
+    /// Energy measured on each scanned channel, in increasing channel order.
+    pub energy_values: Vec<u8>,
+}
+
+impl NwkUpdateNotify {
+    pub fn read(payload: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(payload);
+        read_status(&mut reader)?;
+        let scanned_channels = reader.u32()?;
+        let total_transmissions = reader.u16()?;
+        let transmission_failures = reader.u16()?;
+        let count = reader.u8()?;
+        Ok(NwkUpdateNotify {
+            scanned_channels,
+            total_transmissions,
+            transmission_failures,
+            energy_values: Vec::from(reader.bytes(count as usize)?),
+        })
+    }
+}
@@ -1,46 +1,67 @@
-use deconz_sp::{constants, types, IncomingPayload};
+use deconz_sp::network::NetworkConfig;
+use deconz_sp::{Event, IncomingPayload};
 use futures::future::{Either, Future};
 use futures::{self, stream::Stream};
 
 fn main() {
     env_logger::init();
     tokio::run(futures::lazy(|| {
-        let (client, notifications) = deconz_sp::Client::new("/dev/tty.usbserial-DM00ZSS9")
+        let adapter = deconz_sp::discover()
+            .expect("Cannot list serial adapters")
+            .into_iter()
+            .next()
+            .expect("No serial adapter found");
+        println!(
+            "Using {:?} adapter at {}",
+            adapter.kind,
+            adapter.path.display()
+        );
+        let options = deconz_sp::ClientOptions {
+            baud_rate: adapter.baud_rate,
+            ..Default::default()
+        };
+        let (client, notifications) = deconz_sp::Client::with_options(&adapter.path, options)
             .expect("Cannot initialize DeCONZ client");
 
-        let client = std::sync::Arc::new(client);
-
-        let set_security_mode = client.write_parameter(
-            constants::ParameterCode::SecurityMode,
-            types::ParameterValue::U8(1),
-        );
+        // The network configuration is kept in a TOML file, and only the
+        // parameters which differ from it are written
+        let config = std::fs::read_to_string("network.toml")
+            .map_err(deconz_sp::Error::from)
+            .and_then(|toml| NetworkConfig::from_toml(&toml));
+        let reconcile = match config {
+            Ok(config) => Either::A(client.reconcile(config, false)),
+            Err(error) => {
+                println!("Cannot read network configuration: {}", error);
+                Either::B(futures::future::ok(vec![]))
+            }
+        };
 
-        let handle_notifications = notifications.for_each(move |notif| match notif {
-            // For each APSDE-DATA.indication, send a Read Received Data request
-            IncomingPayload::DeviceState {
-                apsde_data_indication,
-                ..
-            } if apsde_data_indication => Either::A(
-                client
-                    .clone()
-                    .aps_data_indication()
-                    .and_then(|data| {
-                        println!("Data received: {:?}", data);
-                        futures::future::ok(())
-                    })
-                    .map_err(|err| {
-                        println!("Error occured when reading data: {}", err);
-                        ()
-                    }),
-            ),
-            // Unhandled notification
-            _ => Either::B(futures::future::ok(())),
+        // APSDE-DATA.indications are read by the client as soon as the device signals them
+        let handle_notifications = notifications.for_each(|notif| {
+            match notif {
+                Event::DeviceJoined { ieee, nwk, .. } => {
+                    println!("Device joined: {:#018x} ({:#06x})", ieee, nwk)
+                }
+                Event::Notification(data @ IncomingPayload::ApsDataIndication { .. }) => {
+                    println!("Data received: {:?}", data)
+                }
+                Event::Notification(IncomingPayload::DeviceState { state, .. }) => {
+                    println!("Network state: {:?}", state)
+                }
+                // Unhandled notification
+                _ => (),
+            };
+            futures::future::ok(())
         });
 
-        set_security_mode.then(|result| {
+        reconcile.then(|result| {
             match result {
-                Err(error) => println!("Cannot change security mode: {}", error),
-                Ok(_) => println!("SecurityMode changed"),
+                Err(error) => println!("Cannot configure network: {}", error),
+                Ok(changes) => {
+                    for change in changes {
+                        println!("Changed {}", change)
+                    }
+                }
             };
             handle_notifications
         })